@@ -0,0 +1,49 @@
+// Generates typed bindings for the contracts exported to exports/: each ABI is
+// turned into a Solidity interface and expanded by `alloy::sol!`, one module
+// per contract so shared type names do not clash
+
+use std::path::PathBuf;
+
+use alloy_json_abi::JsonAbi;
+use serde::Deserialize;
+
+/// Contract name, as in `exports/<name>.json`, and the module it goes in
+const CONTRACTS: &[(&str, &str)] = &[
+    ("SocialAccountRegistry", "social_account_registry"),
+    ("RiscZeroSocialVerifier", "risc_zero_social_verifier"),
+    ("AbunfiVault", "abunfi_vault"),
+    ("AaveStrategy", "aave_strategy"),
+];
+
+/// Shape of the files in `exports/`
+#[derive(Deserialize)]
+struct ContractExport {
+    abi: JsonAbi,
+}
+
+fn main() {
+    let exports = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../exports");
+    let mut bindings = String::new();
+    for (name, module) in CONTRACTS {
+        let path = exports.join(format!("{}.json", name));
+        println!("cargo:rerun-if-changed={}", path.display());
+        let contents =
+            std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("Cannot read ABI {}: {}", path.display(), e));
+        let export: ContractExport =
+            serde_json::from_str(&contents).unwrap_or_else(|e| panic!("Invalid ABI {}: {}", path.display(), e));
+        bindings.push_str(&format!(
+            "/// `{name}`, generated from `exports/{name}.json`\n\
+             pub mod {module} {{\n\
+             alloy::sol! {{\n\
+             #![sol(rpc)]\n\
+             {sol}\n\
+             }}\n\
+             }}\n\
+             pub use {module}::{name};\n\n",
+            sol = export.abi.to_sol(name, None),
+        ));
+    }
+
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    std::fs::write(out_dir.join("bindings.rs"), bindings).expect("Cannot write bindings.rs");
+}
@@ -0,0 +1,261 @@
+// Deployments loader
+// Reads `deployments/<network>-core.json` and `deployments/<network>-deployment.json`
+// and hands out contract instances by network name. Both files group addresses
+// by role (`core`, `strategies`, ...) under `contracts`, but name them
+// differently (`vault` vs `AbunfiVault`), so each contract is looked up by its
+// known keys. Where both files list a contract, `-core.json` wins.
+
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::{AaveStrategy, AbunfiVault, RiscZeroSocialVerifier, SocialAccountRegistry};
+
+/// `deployments/` at the repository root
+pub const DEFAULT_DEPLOYMENTS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../deployments");
+
+/// Contracts with generated bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contract {
+    SocialAccountRegistry,
+    RiscZeroSocialVerifier,
+    AbunfiVault,
+    AaveStrategy,
+}
+
+impl Contract {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Contract::SocialAccountRegistry => "SocialAccountRegistry",
+            Contract::RiscZeroSocialVerifier => "RiscZeroSocialVerifier",
+            Contract::AbunfiVault => "AbunfiVault",
+            Contract::AaveStrategy => "AaveStrategy",
+        }
+    }
+
+    /// Keys the contract is listed under in a deployment file
+    fn keys(&self) -> &'static [&'static str] {
+        match self {
+            Contract::SocialAccountRegistry => &["SocialAccountRegistry", "socialAccountRegistry"],
+            Contract::RiscZeroSocialVerifier => &["RiscZeroSocialVerifier", "riscZeroSocialVerifier"],
+            Contract::AbunfiVault => &["AbunfiVault", "vault"],
+            Contract::AaveStrategy => &["AaveStrategy", "aave"],
+        }
+    }
+}
+
+/// One file in `deployments/`. Only what the loader needs is read
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentFile {
+    pub network: String,
+    pub chain_id: u64,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    /// Role (`core`, `strategies`, `mocks`, ...) to contract key to entry
+    #[serde(default)]
+    pub contracts: BTreeMap<String, BTreeMap<String, ContractEntry>>,
+}
+
+/// A bare address, or an object with an `address` and other details
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ContractEntry {
+    Address(Address),
+    Detailed { address: Address },
+}
+
+impl ContractEntry {
+    pub fn address(&self) -> Address {
+        match self {
+            ContractEntry::Address(address) | ContractEntry::Detailed { address } => *address,
+        }
+    }
+}
+
+impl DeploymentFile {
+    fn find(&self, contract: Contract) -> Option<Address> {
+        let keys = contract.keys();
+        self.contracts
+            .values()
+            .flat_map(|group| group.iter())
+            .find(|(key, _)| keys.contains(&key.as_str()))
+            .map(|(_, entry)| entry.address())
+    }
+}
+
+/// Every deployment file found for a network, `-core.json` first
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub network: String,
+    pub chain_id: u64,
+    /// Block the deployment was made at, where recorded
+    pub block_number: Option<u64>,
+    files: Vec<DeploymentFile>,
+}
+
+impl Deployment {
+    /// Load `network` from [`DEFAULT_DEPLOYMENTS_DIR`]
+    pub fn load(network: &str) -> Result<Self> {
+        Self::load_from(DEFAULT_DEPLOYMENTS_DIR, network)
+    }
+
+    /// Load `<network>-core.json` and `<network>-deployment.json` from `dir`,
+    /// whichever exist. They must agree on the network and chain ID
+    pub fn load_from(dir: impl AsRef<Path>, network: &str) -> Result<Self> {
+        let mut files = Vec::new();
+        for suffix in ["core", "deployment"] {
+            let path = dir.as_ref().join(format!("{}-{}.json", network, suffix));
+            if !path.exists() {
+                continue;
+            }
+            files.push(read_file(&path, network)?);
+        }
+
+        let first = files.first().ok_or_else(|| {
+            anyhow!(
+                "No deployment for network {} in {}",
+                network,
+                dir.as_ref().display()
+            )
+        })?;
+        if let Some(other) = files.iter().find(|file| file.chain_id != first.chain_id) {
+            return Err(anyhow!(
+                "Deployments for {} disagree on the chain ID: {} vs {}",
+                network,
+                first.chain_id,
+                other.chain_id
+            ));
+        }
+
+        Ok(Self {
+            network: network.to_string(),
+            chain_id: first.chain_id,
+            block_number: files.iter().find_map(|file| file.block_number),
+            files,
+        })
+    }
+
+    /// Address of `contract` on this network
+    pub fn address(&self, contract: Contract) -> Result<Address> {
+        self.files
+            .iter()
+            .find_map(|file| file.find(contract))
+            .ok_or_else(|| anyhow!("{} is not deployed on {}", contract.name(), self.network))
+    }
+
+    /// Connect to `rpc_url`, checking it serves this network's chain
+    pub async fn connect(&self, rpc_url: &str) -> Result<DynProvider> {
+        let provider = ProviderBuilder::new().connect_http(rpc_url.parse()?).erased();
+        let chain_id = provider.get_chain_id().await?;
+        if chain_id != self.chain_id {
+            return Err(anyhow!(
+                "{} is on chain {}, but {} is chain {}",
+                rpc_url,
+                chain_id,
+                self.network,
+                self.chain_id
+            ));
+        }
+        Ok(provider)
+    }
+
+    pub fn social_account_registry<P: Provider>(
+        &self,
+        provider: P,
+    ) -> Result<SocialAccountRegistry::SocialAccountRegistryInstance<P>> {
+        Ok(SocialAccountRegistry::new(self.address(Contract::SocialAccountRegistry)?, provider))
+    }
+
+    pub fn risc_zero_social_verifier<P: Provider>(
+        &self,
+        provider: P,
+    ) -> Result<RiscZeroSocialVerifier::RiscZeroSocialVerifierInstance<P>> {
+        Ok(RiscZeroSocialVerifier::new(self.address(Contract::RiscZeroSocialVerifier)?, provider))
+    }
+
+    pub fn abunfi_vault<P: Provider>(&self, provider: P) -> Result<AbunfiVault::AbunfiVaultInstance<P>> {
+        Ok(AbunfiVault::new(self.address(Contract::AbunfiVault)?, provider))
+    }
+
+    pub fn aave_strategy<P: Provider>(&self, provider: P) -> Result<AaveStrategy::AaveStrategyInstance<P>> {
+        Ok(AaveStrategy::new(self.address(Contract::AaveStrategy)?, provider))
+    }
+}
+
+fn read_file(path: &Path, network: &str) -> Result<DeploymentFile> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read deployment {}: {}", path.display(), e))?;
+    let file: DeploymentFile =
+        serde_json::from_str(&contents).map_err(|e| anyhow!("Invalid deployment {}: {}", path.display(), e))?;
+    if file.network != network {
+        return Err(anyhow!(
+            "{} is for network {}, not {}",
+            path.display(),
+            file.network,
+            network
+        ));
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    #[test]
+    fn test_sepolia() {
+        let deployment = Deployment::load("sepolia").unwrap();
+        assert_eq!(deployment.chain_id, 11155111);
+        assert_eq!(deployment.block_number, Some(9284844));
+        assert_eq!(
+            deployment.address(Contract::AbunfiVault).unwrap(),
+            address!("094eDDFADDd34336853Ca4f738165f39D78532EE")
+        );
+        // Both files list the Aave strategy; the core file is the current one
+        assert_eq!(
+            deployment.address(Contract::AaveStrategy).unwrap(),
+            address!("050B21B2191eA6dEB0f12fD4fd40C7b59f6E397a")
+        );
+        let error = deployment.address(Contract::SocialAccountRegistry).unwrap_err().to_string();
+        assert!(error.contains("SocialAccountRegistry") && error.contains("sepolia"), "{}", error);
+
+        assert!(Deployment::load("mainnet").is_err());
+    }
+
+    #[test]
+    fn test_load_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+        std::fs::write(
+            dir.path().join("local-core.json"),
+            format!(
+                r#"{{"network": "local", "chainId": 31337,
+                    "contracts": {{"social": {{"socialAccountRegistry": "{}"}}}}}}"#,
+                registry
+            ),
+        )
+        .unwrap();
+        let deployment = Deployment::load_from(dir.path(), "local").unwrap();
+        assert_eq!(deployment.address(Contract::SocialAccountRegistry).unwrap(), registry.parse::<Address>().unwrap());
+        assert_eq!(deployment.block_number, None);
+
+        std::fs::write(
+            dir.path().join("local-deployment.json"),
+            r#"{"network": "local", "chainId": 1, "contracts": {}}"#,
+        )
+        .unwrap();
+        assert!(Deployment::load_from(dir.path(), "local").is_err());
+
+        std::fs::write(
+            dir.path().join("other-core.json"),
+            r#"{"network": "local", "chainId": 31337}"#,
+        )
+        .unwrap();
+        assert!(Deployment::load_from(dir.path(), "other").is_err());
+    }
+}
@@ -0,0 +1,27 @@
+// Typed contract bindings generated by build.rs from exports/*.json, and the
+// deployments that say where each contract lives
+
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+mod deployments;
+
+pub use deployments::{Contract, Deployment, DeploymentFile, DEFAULT_DEPLOYMENTS_DIR};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::sol_types::{SolCall, SolEvent};
+
+    #[test]
+    fn test_generated_signatures() {
+        assert_eq!(
+            RiscZeroSocialVerifier::requestVerificationWithCommitmentCall::SIGNATURE,
+            "requestVerificationWithCommitment(uint8,bytes32,address)"
+        );
+        assert_eq!(
+            SocialAccountRegistry::SocialAccountUnlinked::SIGNATURE,
+            "SocialAccountUnlinked(bytes32,address,uint8)"
+        );
+        assert_eq!(AbunfiVault::totalAssetsCall::SIGNATURE, "totalAssets()");
+    }
+}
@@ -0,0 +1,260 @@
+// Batch aggregation
+// Collects completed verification receipts and proves the aggregator guest
+// over them, compressed to one Groth16 seal, so a single transaction can link
+// every account in the batch.
+
+use anyhow::{anyhow, Result};
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{default_prover, is_dev_mode, InnerReceipt, ProverOpts, Receipt};
+use serde::{Deserialize, Serialize};
+use social_verifier_methods::{AGGREGATOR_ID, SOCIAL_VERIFIER_ID};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::{
+    proof_hash, AggregationInput, AggregationOutput, ProofEnvelope, ProverKind, SealEncoding,
+    SocialVerificationService, VerificationOutput,
+};
+
+/// Selector of the dev-mode verifier in the RISC Zero verifier router
+const FAKE_SEAL_SELECTOR: [u8; 4] = [0xFF; 4];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AggregatedProof {
+    pub output: AggregationOutput,
+    pub receipt: ProofEnvelope,
+    /// Seal for `IRiscZeroVerifier.verify(seal, AGGREGATOR_ID, sha256(journal))`
+    #[serde(with = "crate::envelope::hex_bytes")]
+    pub seal: Vec<u8>,
+    pub journal_digest: [u8; 32],
+    pub prover: ProverKind,
+}
+
+impl SocialVerificationService {
+    /// Prove one aggregation over `envelopes`. Each must verify for this
+    /// deployment; the guest refuses the batch otherwise.
+    pub async fn aggregate(&self, envelopes: &[ProofEnvelope]) -> Result<AggregatedProof> {
+        let mut journals = Vec::with_capacity(envelopes.len());
+        let mut assumptions = Vec::with_capacity(envelopes.len());
+        for envelope in envelopes {
+            self.check_aggregatable(envelope)?;
+            journals.push(envelope.journal.clone());
+            assumptions.push(succinct_assumption(envelope.to_receipt()?)?);
+        }
+
+        let input = AggregationInput {
+            verifier_image_id: SOCIAL_VERIFIER_ID,
+            journals,
+        };
+        let receipt = self.prover.prove_aggregation(&input, &assumptions).await?;
+        let output: AggregationOutput = receipt.journal.decode()?;
+
+        Ok(AggregatedProof {
+            output,
+            receipt: ProofEnvelope::from_receipt(&receipt, Digest::from(AGGREGATOR_ID), SealEncoding::Bincode)?,
+            seal: onchain_seal(&receipt)?,
+            journal_digest: proof_hash(&receipt.journal.bytes),
+            prover: self.prover.kind(),
+        })
+    }
+
+    /// Reject up front what would make the aggregator guest fail
+    fn check_aggregatable(&self, envelope: &ProofEnvelope) -> Result<VerificationOutput> {
+        let output = self.verify_proof(envelope)?;
+        // Composition needs the image ID the guest is built with
+        if envelope.image_id()? != Digest::from(SOCIAL_VERIFIER_ID) {
+            return Err(anyhow!("Receipt {} is from another guest image", envelope.image_id));
+        }
+        Ok(output)
+    }
+}
+
+/// Assumptions are resolved as succinct receipts
+fn succinct_assumption(receipt: Receipt) -> Result<Receipt> {
+    match receipt.inner {
+        InnerReceipt::Composite(_) if !is_dev_mode() => default_prover().compress(&ProverOpts::succinct(), &receipt),
+        _ => Ok(receipt),
+    }
+}
+
+/// Seal in the form the RISC Zero verifier router takes: the verifier
+/// selector followed by the proof
+pub fn onchain_seal(receipt: &Receipt) -> Result<Vec<u8>> {
+    match &receipt.inner {
+        InnerReceipt::Groth16(inner) => Ok([&inner.verifier_parameters.as_bytes()[..4], inner.seal.as_slice()].concat()),
+        InnerReceipt::Fake(inner) => Ok([&FAKE_SEAL_SELECTOR[..], inner.claim.digest().as_bytes()].concat()),
+        _ => Err(anyhow!("Aggregated receipt is not a Groth16 receipt")),
+    }
+}
+
+/// Collects receipts and aggregates them `batch_size` at a time. The queue is
+/// held while a batch proves and cleared only once it is proven, so a failed
+/// attempt leaves every receipt queued for the next one.
+pub struct Aggregator {
+    service: Arc<SocialVerificationService>,
+    batch_size: usize,
+    pending: Mutex<Vec<ProofEnvelope>>,
+}
+
+impl Aggregator {
+    pub fn new(service: Arc<SocialVerificationService>, batch_size: usize) -> Self {
+        Self {
+            service,
+            batch_size: batch_size.max(1),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue a completed verification. Returns the aggregated proof once
+    /// `batch_size` receipts are queued.
+    pub async fn add(&self, envelope: ProofEnvelope) -> Result<Option<AggregatedProof>> {
+        let output = self.service.check_aggregatable(&envelope)?;
+        let mut pending = self.pending.lock().await;
+        for queued in pending.iter() {
+            let queued: VerificationOutput = risc0_zkvm::serde::from_slice(&queued.journal)?;
+            if queued.social_account_hash == output.social_account_hash {
+                return Err(anyhow!("Social account is already in the pending batch"));
+            }
+        }
+        pending.push(envelope);
+        if pending.len() < self.batch_size {
+            return Ok(None);
+        }
+        self.aggregate(&mut pending).await.map(Some)
+    }
+
+    /// Aggregate whatever is queued, if anything
+    pub async fn flush(&self) -> Result<Option<AggregatedProof>> {
+        let mut pending = self.pending.lock().await;
+        if pending.is_empty() {
+            return Ok(None);
+        }
+        self.aggregate(&mut pending).await.map(Some)
+    }
+
+    pub async fn pending(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    async fn aggregate(&self, pending: &mut Vec<ProofEnvelope>) -> Result<AggregatedProof> {
+        let aggregated = self.service.aggregate(pending).await?;
+        pending.clear();
+        Ok(aggregated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        link_leaf, merkle_proof, verify_merkle_proof, DeploymentDomain, PolicyOutcome, ProverBackend, RemoteProver, RiskModel,
+        SocialPlatform, VerificationType,
+    };
+    use crate::prover::dev_mode::DevModeGuard;
+
+    const REGISTRY: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+
+    #[tokio::test]
+    async fn test_aggregate_batch_in_dev_mode() {
+        let _dev_mode = DevModeGuard::enable().await;
+
+        let service = Arc::new(
+            SocialVerificationService::new(DeploymentDomain::new(11155111, REGISTRY)).with_prover(ProverBackend::DevMode),
+        );
+        let twitter = service
+            .verify_social_account(
+                SocialPlatform::Twitter,
+                "mock_twitter_token_12345".to_string(),
+                "0x1234567890123456789012345678901234567890".to_string(),
+                1,
+            )
+            .await
+            .unwrap();
+        let github = service
+            .verify_social_account(
+                SocialPlatform::Github,
+                "ghp_github_token_1234567890".to_string(),
+                "0x1111222233334444555566667777888899990000".to_string(),
+                1,
+            )
+            .await
+            .unwrap();
+
+        let aggregator = Aggregator::new(service.clone(), 2);
+        assert!(aggregator.add(twitter.receipt.clone()).await.unwrap().is_none());
+        // The same account twice never reaches the guest
+        assert!(aggregator.add(twitter.receipt.clone()).await.is_err());
+        assert_eq!(aggregator.pending().await, 1);
+
+        let aggregated = aggregator.add(github.receipt.clone()).await.unwrap().unwrap();
+        assert_eq!(aggregator.pending().await, 0);
+        assert_eq!(aggregated.output.links.len(), 2);
+        assert_eq!(aggregated.output.links[0].proof_hash, twitter.proof_hash);
+        assert_eq!(aggregated.seal[..4], FAKE_SEAL_SELECTOR);
+        aggregated.receipt.to_receipt().unwrap().verify(AGGREGATOR_ID).unwrap();
+
+        let leaves: Vec<[u8; 32]> = aggregated
+            .output
+            .links
+            .iter()
+            .map(|link| link_leaf(&link.wallet_address, &link.social_account_hash).unwrap())
+            .collect();
+        assert!(verify_merkle_proof(&aggregated.output.merkle_root, &leaves[1], &merkle_proof(&leaves, 1)));
+
+        // A proof for another deployment is refused before proving
+        let mainnet = SocialVerificationService::new(DeploymentDomain::new(1, REGISTRY)).with_prover(ProverBackend::DevMode);
+        assert!(mainnet.aggregate(&[twitter.receipt]).await.is_err());
+        assert!(aggregator.flush().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_stays_queued() {
+        let _dev_mode = DevModeGuard::enable().await;
+        let domain = DeploymentDomain::new(11155111, REGISTRY);
+        // Verifies single receipts, but cannot aggregate
+        let service = Arc::new(
+            SocialVerificationService::new(domain.clone())
+                .with_prover(ProverBackend::Remote(RemoteProver::new("http://127.0.0.1:9", None))),
+        );
+        let envelope = |platform: SocialPlatform| {
+            let output = VerificationOutput {
+                social_account_hash: [platform as u8; 32],
+                wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+                platform,
+                account_age: 0,
+                follower_count: 150,
+                timestamp: 1640995200,
+                expires_at: 1640995200 + 3600,
+                nonce: 1,
+                social_account_id: "123456789".to_string(),
+                verification_type: VerificationType::NewAccount,
+                account_consistency_score: 100,
+                chain_id: domain.chain_id,
+                verifying_contract: domain.verifying_contract.clone(),
+                verification_success: true,
+                policy_hash: [0u8; 32],
+                policy_outcome: PolicyOutcome::Passed,
+                profile_commitment: [0u8; 32],
+                previous_profile_commitment: None,
+                self_image_id: SOCIAL_VERIFIER_ID,
+                previous_proof_hash: None,
+                sybil_risk_score: 0,
+                risk_model_version: 1,
+                risk_model_hash: RiskModel::default().hash(),
+                oauth_token_hash: [0u8; 32],
+            };
+            let image_id = Digest::from(SOCIAL_VERIFIER_ID);
+            let journal: Vec<u8> = risc0_zkvm::serde::to_vec(&output).unwrap().iter().flat_map(|w| w.to_le_bytes()).collect();
+            let claim = risc0_zkvm::ReceiptClaim::ok(image_id, journal.clone());
+            let receipt = Receipt::new(InnerReceipt::Fake(risc0_zkvm::FakeReceipt::new(claim)), journal);
+            ProofEnvelope::from_receipt(&receipt, image_id, SealEncoding::Bincode).unwrap()
+        };
+
+        let aggregator = Aggregator::new(service, 2);
+        assert!(aggregator.add(envelope(SocialPlatform::Twitter)).await.unwrap().is_none());
+        assert!(aggregator.add(envelope(SocialPlatform::Github)).await.is_err());
+        assert_eq!(aggregator.pending().await, 2);
+        assert!(aggregator.flush().await.is_err());
+        assert_eq!(aggregator.pending().await, 2);
+    }
+}
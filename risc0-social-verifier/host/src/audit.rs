@@ -0,0 +1,234 @@
+// Offline proof verification for third parties
+// `host verify` checks a proof envelope against a pinned image ID without
+// running the service, and `host inspect` decodes one without verifying it.
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+use risc0_zkvm::{sha::Digest, Journal};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{CommandError, Report};
+use crate::{DeploymentDomain, ProofEnvelope, ReceiptKind, ReceiptVerifier, RegistryPolicies, SealEncoding, VerificationOutput};
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Envelope file (JSON, hex, base64 or bincode); `-` or omitted reads stdin
+    #[arg(long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// Guest image ID to trust; auditors must pin it explicitly
+    #[arg(long)]
+    pub image_id: String,
+    /// Registry policy file to check minimum age and followers against
+    #[arg(long, value_name = "PATH")]
+    pub policy: Option<PathBuf>,
+    /// Expected deployment chain ID
+    #[arg(long, requires = "contract")]
+    pub chain_id: Option<u64>,
+    /// Expected verifying contract address
+    #[arg(long, requires = "chain_id")]
+    pub contract: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    /// Envelope file; `-` or omitted reads stdin
+    #[arg(long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub valid: bool,
+    pub image_id: String,
+    pub output: VerificationOutput,
+}
+
+impl Report for VerifyReport {
+    fn print_text(&self) {
+        println!("Proof: VALID");
+        println!("Image ID: {}", self.image_id);
+        print_output(&self.output);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InspectReport {
+    pub version: u32,
+    pub kind: ReceiptKind,
+    pub image_id: String,
+    pub encoding: SealEncoding,
+    pub journal_bytes: usize,
+    pub seal_bytes: usize,
+    /// Decoded journal, if it is a `VerificationOutput`. Not verified.
+    pub output: Option<VerificationOutput>,
+}
+
+impl Report for InspectReport {
+    fn print_text(&self) {
+        println!("Envelope Version: {}", self.version);
+        println!("Receipt Kind: {:?}", self.kind);
+        println!("Image ID: {}", self.image_id);
+        println!("Seal Encoding: {:?}", self.encoding);
+        println!("Journal Size: {} bytes", self.journal_bytes);
+        println!("Seal Size: {} bytes", self.seal_bytes);
+        match &self.output {
+            Some(output) => {
+                println!("Journal (unverified):");
+                print_output(output);
+            }
+            None => println!("Journal: not a verification output"),
+        }
+    }
+}
+
+/// Verify against the pinned image ID plus any deployment and policy checks
+pub fn verify(args: &VerifyArgs) -> std::result::Result<VerifyReport, CommandError> {
+    let pinned = parse_image_id(&args.image_id)?;
+
+    let envelope = read_envelope(args.input.as_deref())?;
+    let receipt = envelope.to_receipt().map_err(CommandError::Rejected)?;
+    let output = ReceiptVerifier::new(vec![pinned]).verify(&receipt)?;
+
+    if let (Some(chain_id), Some(contract)) = (args.chain_id, &args.contract) {
+        DeploymentDomain::new(chain_id, contract.as_str()).check(&output)?;
+    }
+
+    if let Some(policy) = &args.policy {
+        RegistryPolicies::load(policy)?
+            .check(&output)
+            .map_err(CommandError::Rejected)?;
+    }
+
+    Ok(VerifyReport {
+        valid: true,
+        image_id: args.image_id.clone(),
+        output,
+    })
+}
+
+pub fn inspect(args: &InspectArgs) -> Result<InspectReport> {
+    let envelope = read_envelope(args.input.as_deref())?;
+    Ok(InspectReport {
+        version: envelope.version,
+        kind: envelope.kind,
+        image_id: envelope.image_id.clone(),
+        encoding: envelope.encoding,
+        journal_bytes: envelope.journal.len(),
+        seal_bytes: envelope.seal.len(),
+        output: Journal::new(envelope.journal.clone()).decode().ok(),
+    })
+}
+
+fn parse_image_id(image_id: &str) -> Result<Digest> {
+    let bytes = hex::decode(image_id.trim_start_matches("0x"))?;
+    Digest::try_from(bytes.as_slice()).map_err(|_| anyhow!("Image ID must be 32 bytes: {}", image_id))
+}
+
+/// Read an envelope from a file or stdin, in any of its encodings
+pub fn read_envelope(input: Option<&Path>) -> Result<ProofEnvelope> {
+    let mut bytes = Vec::new();
+    match input {
+        Some(path) if path != Path::new("-") => bytes = std::fs::read(path)?,
+        _ => {
+            std::io::stdin().read_to_end(&mut bytes)?;
+        }
+    }
+
+    match std::str::from_utf8(&bytes) {
+        Ok(text) => ProofEnvelope::decode_str(text),
+        Err(_) => ProofEnvelope::from_bincode(&bytes),
+    }
+}
+
+pub fn print_output(output: &VerificationOutput) {
+    println!("Platform: {:?}", output.platform);
+    println!("Wallet Address: {}", output.wallet_address);
+    println!("Social Account Hash: {}", hex::encode(output.social_account_hash));
+    println!("OAuth Token Hash: {}", hex::encode(output.oauth_token_hash));
+    println!("Account Age: {} seconds", output.account_age);
+    println!("Follower Count: {}", output.follower_count);
+    println!("Verification Type: {:?}", output.verification_type);
+    println!("Consistency Score: {}", output.account_consistency_score);
+    println!(
+        "Sybil Risk Score: {} (model v{}, {})",
+        output.sybil_risk_score,
+        output.risk_model_version,
+        hex::encode(output.risk_model_hash)
+    );
+    println!("Profile Commitment: {}", hex::encode(output.profile_commitment));
+    if let Some(previous) = output.previous_profile_commitment {
+        println!("Previous Profile Commitment: {}", hex::encode(previous));
+    }
+    if let Some(previous) = output.previous_proof_hash {
+        println!("Composes Proof: {}", hex::encode(previous));
+    }
+    println!("Policy: {:?} (hash {})", output.policy_outcome, hex::encode(output.policy_hash));
+    println!("Chain ID: {}", output.chain_id);
+    println!("Verifying Contract: {}", output.verifying_contract);
+    println!("Timestamp: {}", output.timestamp);
+    println!("Expires At: {}", output.expires_at);
+    println!("Nonce: {}", output.nonce);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::envelope::ENVELOPE_VERSION;
+
+    #[test]
+    fn test_parse_image_id() {
+        let image_id = Digest::from([7u32; 8]);
+        let encoded = format!("0x{}", hex::encode(image_id.as_bytes()));
+        assert_eq!(parse_image_id(&encoded).unwrap(), image_id);
+        assert!(parse_image_id("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_read_and_inspect_envelope() {
+        let envelope = ProofEnvelope {
+            version: ENVELOPE_VERSION,
+            kind: ReceiptKind::Groth16,
+            image_id: format!("0x{}", hex::encode([7u8; 32])),
+            journal: vec![1, 2, 3, 4],
+            encoding: SealEncoding::Json,
+            seal: vec![5; 10],
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.hex");
+        std::fs::write(&path, envelope.to_hex().unwrap()).unwrap();
+
+        let report = inspect(&InspectArgs { input: Some(path) }).unwrap();
+        assert_eq!(report.kind, ReceiptKind::Groth16);
+        assert_eq!(report.journal_bytes, 4);
+        assert_eq!(report.seal_bytes, 10);
+        assert!(report.output.is_none());
+    }
+
+    #[test]
+    fn test_unverifiable_envelope_is_rejected() {
+        let envelope = ProofEnvelope {
+            version: ENVELOPE_VERSION,
+            kind: ReceiptKind::Succinct,
+            image_id: format!("0x{}", hex::encode([7u8; 32])),
+            journal: vec![1, 2, 3, 4],
+            encoding: SealEncoding::Bincode,
+            seal: vec![5; 10],
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("proof.json");
+        std::fs::write(&path, envelope.to_json().unwrap()).unwrap();
+
+        let args = VerifyArgs {
+            input: Some(path),
+            image_id: envelope.image_id.clone(),
+            policy: None,
+            chain_id: None,
+            contract: None,
+        };
+        assert!(matches!(verify(&args), Err(CommandError::Rejected(_))));
+    }
+}
@@ -0,0 +1,144 @@
+// Identity bundle proofs
+// Proves several platforms for one wallet in one receipt with the
+// identity-bundle guest, and checks such receipts.
+
+use anyhow::{anyhow, Result};
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use social_verifier_methods::IDENTITY_BUNDLE_ID;
+
+use crate::{
+    proof_hash, IdentityBundleInput, IdentityBundleOutput, ProofEnvelope, ProofVerificationError, ProverKind,
+    SealEncoding, SocialPlatform, SocialVerificationService,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleProofResult {
+    pub bundle_output: IdentityBundleOutput,
+    pub receipt: ProofEnvelope,
+    pub proof_hash: [u8; 32],
+    pub prover: ProverKind,
+}
+
+impl SocialVerificationService {
+    /// Prove every `(platform, oauth_token)` for `wallet_address` in one
+    /// receipt. The bundle succeeds if at least `minimum_platforms` distinct
+    /// platforms verify and meet their policy.
+    pub async fn verify_identity_bundle(
+        &self,
+        accounts: Vec<(SocialPlatform, String)>,
+        wallet_address: String,
+        nonce: u64,
+        minimum_platforms: u32,
+    ) -> Result<BundleProofResult> {
+        if accounts.is_empty() {
+            return Err(anyhow!("An identity bundle needs at least one platform"));
+        }
+
+        let mut platforms = Vec::with_capacity(accounts.len());
+        let mut assumptions = Vec::new();
+        for (platform, oauth_token) in accounts {
+            let input = self.build_input(platform, oauth_token, wallet_address.clone(), nonce).await?;
+            assumptions.extend(self.assumptions_for(&input)?);
+            platforms.push(input);
+        }
+        // The guest requires one timestamp for the whole bundle
+        let (timestamp, expires_at) = (platforms[0].timestamp, platforms[0].expires_at);
+        for input in &mut platforms {
+            input.timestamp = timestamp;
+            input.expires_at = expires_at;
+        }
+
+        let input = IdentityBundleInput {
+            platforms,
+            minimum_platforms,
+        };
+        let receipt = self.prover.prove_bundle(&input, &assumptions).await?;
+        let bundle_output: IdentityBundleOutput = receipt.journal.decode()?;
+
+        Ok(BundleProofResult {
+            bundle_output,
+            receipt: ProofEnvelope::from_receipt(&receipt, Digest::from(IDENTITY_BUNDLE_ID), SealEncoding::Bincode)?,
+            proof_hash: proof_hash(&receipt.journal.bytes),
+            prover: self.prover.kind(),
+        })
+    }
+
+    /// Verify a bundle receipt for this deployment, returning its journal
+    pub fn verify_bundle_proof(
+        &self,
+        envelope: &ProofEnvelope,
+    ) -> std::result::Result<IdentityBundleOutput, ProofVerificationError> {
+        let receipt = envelope
+            .to_receipt()
+            .map_err(|e| ProofVerificationError::MalformedReceipt(e.to_string()))?;
+        receipt
+            .verify(IDENTITY_BUNDLE_ID)
+            .map_err(|_| ProofVerificationError::UnknownImageId)?;
+
+        let bundle_output: IdentityBundleOutput = receipt
+            .journal
+            .decode()
+            .map_err(|e| ProofVerificationError::MalformedJournal(e.to_string()))?;
+        // Composed receipts were only checked against this committed ID
+        if !self
+            .receipt_verifier
+            .accepted_image_ids
+            .contains(&Digest::from(bundle_output.verifier_image_id))
+        {
+            return Err(ProofVerificationError::SelfImageIdMismatch);
+        }
+        if !bundle_output.bundle_success {
+            return Err(ProofVerificationError::VerificationFailed);
+        }
+        self.domain
+            .check_binding(bundle_output.chain_id, &bundle_output.verifying_contract)?;
+
+        Ok(bundle_output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeploymentDomain, ProverBackend, ReputationTier};
+    use crate::prover::dev_mode::DevModeGuard;
+
+    #[tokio::test]
+    async fn test_twitter_and_github_in_one_receipt() {
+        let _dev_mode = DevModeGuard::enable().await;
+
+        let domain = DeploymentDomain::new(11155111, "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8");
+        let service = SocialVerificationService::new(domain).with_prover(ProverBackend::DevMode);
+        let accounts = vec![
+            (SocialPlatform::Twitter, "mock_twitter_token_12345".to_string()),
+            (SocialPlatform::Github, "ghp_github_token_1234567890".to_string()),
+        ];
+
+        let result = service
+            .verify_identity_bundle(accounts, "0x1234567890123456789012345678901234567890".to_string(), 1, 2)
+            .await
+            .unwrap();
+        let output = service.verify_bundle_proof(&result.receipt).unwrap();
+        assert_eq!(output, result.bundle_output);
+        assert_eq!(output.distinct_platforms, 2);
+        assert_eq!(output.reputation_tier, ReputationTier::Verified);
+        assert_eq!(output.attestations.len(), 2);
+        assert_ne!(output.attestations[0].nullifier, output.attestations[1].nullifier);
+
+        // Same accounts, but three platforms required
+        let accounts = vec![
+            (SocialPlatform::Twitter, "mock_twitter_token_12345".to_string()),
+            (SocialPlatform::Github, "ghp_github_token_1234567890".to_string()),
+        ];
+        let result = service
+            .verify_identity_bundle(accounts, "0x1234567890123456789012345678901234567890".to_string(), 2, 3)
+            .await
+            .unwrap();
+        assert!(!result.bundle_output.bundle_success);
+        assert_eq!(
+            service.verify_bundle_proof(&result.receipt),
+            Err(ProofVerificationError::VerificationFailed)
+        );
+    }
+}
@@ -0,0 +1,961 @@
+// Command line interface
+// Every subcommand prints text by default or JSON with `--json`, and the exit
+// code says whether something was rejected or could not be checked at all.
+
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::audit::{self, InspectArgs, VerifyArgs};
+use crate::config::ENV_OVERRIDES;
+use crate::image_id::{self, ImageIdArgs};
+use crate::web_service::VerificationServer;
+use crate::{
+    parse_platform, proof_hash, AggregationOutput, HostConfig, IdentityBundleOutput, PreflightReport, ProofExpectations,
+    PollReport, ProofVerificationError, ProverKind, RelayJob, RelayStatus, SocialPlatform,
+    VerificationOutput,
+};
+
+/// The proof or input was checked and rejected
+pub const EXIT_REJECTED: u8 = 1;
+// clap exits with 2 on usage errors
+/// The command failed before reaching a verdict
+pub const EXIT_FAILED: u8 = 3;
+
+#[derive(Debug, Parser)]
+#[command(name = "host", version, about = "RISC Zero social account verification")]
+pub struct Cli {
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// TOML config file; environment variables and flags override it
+    #[arg(long, global = true, env = "SOCIAL_VERIFIER_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Verify a social account and generate a proof
+    Prove(ProveArgs),
+    /// Prove several platforms for one wallet in a single receipt
+    ProveBundle(ProveBundleArgs),
+    /// Aggregate many users' proofs into one Groth16 proof
+    Aggregate(AggregateArgs),
+    /// Run the guest in the executor and report its cost, without proving
+    Preflight(VerificationArgs),
+    /// Verify a proof envelope against a pinned image ID
+    Verify(VerifyArgs),
+    /// Decode a proof envelope without verifying it
+    Inspect(InspectArgs),
+    /// Run the HTTP verification service
+    Serve(ServeArgs),
+    /// Sign a verified proof with the verifier key for on-chain submission
+    Sign(SignArgs),
+    /// Sign and send a verified proof to the verifier and registry contracts
+    Relay(RelayArgs),
+    /// Watch the verifier contract and fulfil verification requests
+    Oracle(OracleArgs),
+    /// Check the guest image ID against the lockfile
+    ImageId(ImageIdArgs),
+    /// Inspect the effective configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Validate the configuration and print it with all overrides applied
+    Check,
+}
+
+/// Why a command did not succeed
+#[derive(Debug)]
+pub enum CommandError {
+    /// Checked and rejected: invalid proof, failed verification, mismatch
+    Rejected(anyhow::Error),
+    /// Could not get as far as a verdict: I/O, configuration, prover errors
+    Failed(anyhow::Error),
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        // The service reports verdicts such as a policy miss as errors too
+        if e.downcast_ref::<ProofVerificationError>().is_some() {
+            Self::Rejected(e)
+        } else {
+            Self::Failed(e)
+        }
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Failed(e.into())
+    }
+}
+
+impl From<ProofVerificationError> for CommandError {
+    fn from(e: ProofVerificationError) -> Self {
+        Self::Rejected(e.into())
+    }
+}
+
+/// Command output that can be printed as text or JSON
+pub trait Report: Serialize {
+    fn print_text(&self);
+}
+
+/// Where to read the OAuth token from. Tokens are never taken from argv,
+/// where they would end up in shell history and `ps`.
+#[derive(Debug, Args)]
+pub struct TokenArgs {
+    /// Read the OAuth token from stdin
+    #[arg(long, conflicts_with = "token_file")]
+    pub token_stdin: bool,
+    /// Read the OAuth token from a file
+    #[arg(long, value_name = "PATH")]
+    pub token_file: Option<PathBuf>,
+    /// Read the OAuth token from this environment variable
+    #[arg(long, value_name = "VAR", default_value = "OAUTH_TOKEN")]
+    pub token_env: String,
+}
+
+impl TokenArgs {
+    pub fn read(&self) -> Result<String> {
+        let token = if self.token_stdin {
+            let mut token = String::new();
+            std::io::stdin().read_to_string(&mut token)?;
+            token
+        } else if let Some(path) = &self.token_file {
+            std::fs::read_to_string(path)?
+        } else {
+            std::env::var(&self.token_env)
+                .map_err(|_| anyhow!("No OAuth token: set {} or use --token-stdin / --token-file", self.token_env))?
+        };
+
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err(anyhow!("OAuth token is empty"));
+        }
+        Ok(token)
+    }
+}
+
+/// Flags overriding the deployment and storage settings of the config
+#[derive(Debug, Args)]
+pub struct ServiceArgs {
+    /// Deployment to bind proofs to, from `[chain.deployments]`
+    #[arg(long)]
+    pub network: Option<String>,
+    /// Deployment file providing the chain ID
+    #[arg(long, value_name = "PATH")]
+    pub deployment_file: Option<PathBuf>,
+    /// Registry address proofs are bound to
+    #[arg(long)]
+    pub verifying_contract: Option<String>,
+    /// Keep generated receipts in this directory
+    #[arg(long, value_name = "DIR")]
+    pub receipt_store: Option<PathBuf>,
+    /// Reject inputs whose execution exceeds this many cycles
+    #[arg(long)]
+    pub max_cycles: Option<u64>,
+}
+
+impl ServiceArgs {
+    /// Flags take the same path through the config as their environment variables
+    pub fn apply(&self, config: &mut HostConfig) -> Result<()> {
+        config.apply_env(|name| match name {
+            "NETWORK" => self.network.clone(),
+            "DEPLOYMENT_FILE" => self.deployment_file.as_ref().map(|path| path.display().to_string()),
+            "VERIFYING_CONTRACT" => self.verifying_contract.clone(),
+            "RECEIPT_STORE" => self.receipt_store.as_ref().map(|path| path.display().to_string()),
+            "MAX_CYCLES" => self.max_cycles.map(|max_cycles| max_cycles.to_string()),
+            _ => None,
+        })
+    }
+}
+
+/// Load, override and validate the config
+fn load_config(path: Option<&Path>, service: Option<&ServiceArgs>) -> Result<HostConfig> {
+    let mut config = HostConfig::load(path)?;
+    if let Some(service) = service {
+        service.apply(&mut config)?;
+    }
+    config.validate()?;
+    Ok(config)
+}
+
+#[derive(Debug, Args)]
+pub struct VerificationArgs {
+    #[arg(long, value_parser = parse_platform_arg)]
+    pub platform: SocialPlatform,
+    /// Wallet the social account is linked to
+    #[arg(long)]
+    pub wallet: String,
+    /// Registry nonce for the wallet
+    #[arg(long, default_value_t = 0)]
+    pub nonce: u64,
+    #[command(flatten)]
+    pub token: TokenArgs,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct ProveArgs {
+    #[command(flatten)]
+    pub verification: VerificationArgs,
+    /// Write the proof envelope as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ProveBundleArgs {
+    /// `PLATFORM=VAR`: prove PLATFORM with the OAuth token in environment
+    /// variable VAR. Repeat once per platform.
+    #[arg(long = "account", value_name = "PLATFORM=VAR", value_parser = parse_bundle_account, required = true)]
+    pub accounts: Vec<(SocialPlatform, String)>,
+    /// Wallet the social accounts are linked to
+    #[arg(long)]
+    pub wallet: String,
+    /// Registry nonce for the wallet
+    #[arg(long, default_value_t = 0)]
+    pub nonce: u64,
+    /// Distinct platforms that must verify, e.g. 2 for "any two of"
+    #[arg(long, default_value_t = 1)]
+    pub minimum_platforms: u32,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+    /// Write the proof envelope as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct AggregateArgs {
+    /// Proof envelope files to aggregate; repeat once per proof
+    #[arg(long = "input", value_name = "PATH", required = true)]
+    pub inputs: Vec<PathBuf>,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+    /// Write the aggregated proof as JSON to this file
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    /// Overrides `server.listen`
+    #[arg(long)]
+    pub listen: Option<SocketAddr>,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct SignArgs {
+    /// Envelope file; `-` or omitted reads stdin
+    #[arg(long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// Sign for `RiscZeroSocialVerifier.submitProof` with this request ID
+    /// instead of for `SocialAccountRegistry.linkSocialAccount`
+    #[arg(long)]
+    pub request_id: Option<String>,
+    /// JSON keystore holding the verifier key; overrides `signer.keystore`
+    #[arg(long, value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct RelayArgs {
+    /// Envelope file; `-` or omitted reads stdin
+    #[arg(long, value_name = "PATH")]
+    pub input: Option<PathBuf>,
+    /// Answer this `RiscZeroSocialVerifier` request with `submitProof` before
+    /// linking the account
+    #[arg(long)]
+    pub request_id: Option<String>,
+    /// JSON keystore holding the verifier key; overrides `signer.keystore`
+    #[arg(long, value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
+    /// JSON-RPC endpoint of the deployment
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+    /// Overrides `relayer.verifier_contract`
+    #[arg(long)]
+    pub verifier_contract: Option<String>,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+}
+
+#[derive(Debug, Args)]
+pub struct OracleArgs {
+    /// Poll once and exit instead of watching
+    #[arg(long)]
+    pub once: bool,
+    /// First block to scan when there is no checkpoint; overrides `oracle.start_block`
+    #[arg(long)]
+    pub start_block: Option<u64>,
+    /// Read tokens from `<DIR>/<requestId>` files; overrides `oracle.token_dir`
+    #[arg(long, value_name = "DIR", conflicts_with = "token_url")]
+    pub token_dir: Option<PathBuf>,
+    /// Fetch tokens from `<URL>/<requestId>`; overrides `oracle.token_url`
+    #[arg(long, value_name = "URL")]
+    pub token_url: Option<String>,
+    /// JSON keystore holding the verifier key; overrides `signer.keystore`
+    #[arg(long, value_name = "PATH")]
+    pub keystore: Option<PathBuf>,
+    /// JSON-RPC endpoint of the deployment
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+    /// Overrides `relayer.verifier_contract`
+    #[arg(long)]
+    pub verifier_contract: Option<String>,
+    #[command(flatten)]
+    pub service: ServiceArgs,
+}
+
+fn parse_platform_arg(name: &str) -> std::result::Result<SocialPlatform, String> {
+    parse_platform(name).ok_or_else(|| format!("unsupported platform {} (twitter, discord, github, telegram, linkedin)", name))
+}
+
+fn parse_bundle_account(value: &str) -> std::result::Result<(SocialPlatform, String), String> {
+    let (platform, var) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected PLATFORM=VAR, got {}", value))?;
+    Ok((parse_platform_arg(platform)?, var.to_string()))
+}
+
+pub async fn run(cli: Cli) -> ExitCode {
+    let json = cli.json;
+    let config = cli.config.as_deref();
+    let result = match cli.command {
+        Command::Prove(args) => prove(config, &args).await.map(|report| emit(json, &report)),
+        Command::ProveBundle(args) => prove_bundle(config, &args).await.map(|report| emit(json, &report)),
+        Command::Aggregate(args) => aggregate(config, &args).await.map(|report| emit(json, &report)),
+        Command::Preflight(args) => preflight(config, &args).await.map(|report| emit(json, &report)),
+        Command::Verify(args) => audit::verify(&args).map(|report| emit(json, &report)),
+        Command::Inspect(args) => audit::inspect(&args).map(|report| emit(json, &report)).map_err(Into::into),
+        Command::Serve(args) => serve(config, &args).await,
+        Command::Sign(args) => sign(config, &args).map(|report| emit(json, &report)),
+        Command::Relay(args) => relay(config, &args).await.map(|report| emit(json, &report)),
+        Command::Oracle(args) => oracle(config, &args, json).await,
+        Command::ImageId(args) => image_id::run(&args).map(|report| emit(json, &report)),
+        Command::Config(ConfigCommand::Check) => config_check(config).map(|report| emit(json, &report)),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CommandError::Rejected(e)) => {
+            emit_error(json, "rejected", &e);
+            ExitCode::from(EXIT_REJECTED)
+        }
+        Err(CommandError::Failed(e)) => {
+            emit_error(json, "failed", &e);
+            ExitCode::from(EXIT_FAILED)
+        }
+    }
+}
+
+fn emit(json: bool, report: &impl Report) {
+    if json {
+        match serde_json::to_string_pretty(report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    } else {
+        report.print_text();
+    }
+}
+
+fn emit_error(json: bool, status: &str, e: &anyhow::Error) {
+    if json {
+        println!("{}", serde_json::json!({ "success": false, "status": status, "error": format!("{:#}", e) }));
+    } else {
+        eprintln!("Error: {:#}", e);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProveReport {
+    pub success: bool,
+    pub proof_hash: String,
+    pub prover: ProverKind,
+    pub output: VerificationOutput,
+    /// Base64 envelope, when not written to a file
+    pub receipt: Option<String>,
+    pub receipt_file: Option<PathBuf>,
+}
+
+impl Report for ProveReport {
+    fn print_text(&self) {
+        println!("=== Verification Result ===");
+        println!("Success: {}", self.success);
+        audit::print_output(&self.output);
+        println!("Proof Hash: {}", self.proof_hash);
+        println!("Prover: {:?}", self.prover);
+        if let Some(path) = &self.receipt_file {
+            println!("Proof written to {}", path.display());
+        }
+    }
+}
+
+async fn prove(config: Option<&Path>, args: &ProveArgs) -> std::result::Result<ProveReport, CommandError> {
+    let verification = &args.verification;
+    let service = load_config(config, Some(&verification.service))?.build_service()?;
+    let result = service
+        .verify_social_account(
+            verification.platform,
+            verification.token.read()?,
+            verification.wallet.clone(),
+            verification.nonce,
+        )
+        .await?;
+
+    if !result.verification_output.verification_success {
+        return Err(CommandError::Rejected(anyhow!("Social account verification failed")));
+    }
+
+    // Check our own proof the way a consumer would
+    let expectations = ProofExpectations::new(verification.wallet.as_str(), verification.nonce);
+    service.verify_with_policy(&result.receipt, &expectations)?;
+
+    let (receipt, receipt_file) = match &args.output {
+        Some(path) => {
+            std::fs::write(path, result.receipt.to_json()?)?;
+            (None, Some(path.clone()))
+        }
+        None => (Some(result.receipt.to_base64()?), None),
+    };
+
+    Ok(ProveReport {
+        success: true,
+        proof_hash: format!("0x{}", hex::encode(result.proof_hash)),
+        prover: result.prover,
+        output: result.verification_output,
+        receipt,
+        receipt_file,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct BundleReport {
+    pub success: bool,
+    pub proof_hash: String,
+    pub prover: ProverKind,
+    pub output: IdentityBundleOutput,
+    /// Base64 envelope, when not written to a file
+    pub receipt: Option<String>,
+    pub receipt_file: Option<PathBuf>,
+}
+
+impl Report for BundleReport {
+    fn print_text(&self) {
+        println!("=== Identity Bundle ===");
+        println!("Success: {}", self.success);
+        println!("Wallet Address: {}", self.output.wallet_address);
+        for attestation in &self.output.attestations {
+            println!(
+                "{:?}: {} ({:?}, nullifier {})",
+                attestation.platform,
+                if attestation.verification_success { "verified" } else { "failed" },
+                attestation.policy_outcome,
+                hex::encode(attestation.nullifier)
+            );
+        }
+        println!(
+            "Distinct Platforms: {} (minimum {})",
+            self.output.distinct_platforms, self.output.minimum_platforms
+        );
+        println!("Reputation Tier: {:?}", self.output.reputation_tier);
+        println!("Proof Hash: {}", self.proof_hash);
+        println!("Prover: {:?}", self.prover);
+        if let Some(path) = &self.receipt_file {
+            println!("Proof written to {}", path.display());
+        }
+    }
+}
+
+async fn prove_bundle(config: Option<&Path>, args: &ProveBundleArgs) -> std::result::Result<BundleReport, CommandError> {
+    let service = load_config(config, Some(&args.service))?.build_service()?;
+    let accounts = args
+        .accounts
+        .iter()
+        .map(|(platform, var)| {
+            let token = TokenArgs {
+                token_stdin: false,
+                token_file: None,
+                token_env: var.clone(),
+            };
+            Ok((*platform, token.read()?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let result = service
+        .verify_identity_bundle(accounts, args.wallet.clone(), args.nonce, args.minimum_platforms)
+        .await?;
+    // Rejects a bundle short of `minimum_platforms`
+    service.verify_bundle_proof(&result.receipt)?;
+
+    let (receipt, receipt_file) = match &args.output {
+        Some(path) => {
+            std::fs::write(path, result.receipt.to_json()?)?;
+            (None, Some(path.clone()))
+        }
+        None => (Some(result.receipt.to_base64()?), None),
+    };
+
+    Ok(BundleReport {
+        success: true,
+        proof_hash: format!("0x{}", hex::encode(result.proof_hash)),
+        prover: result.prover,
+        output: result.bundle_output,
+        receipt,
+        receipt_file,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateReport {
+    pub proof_count: usize,
+    pub output: AggregationOutput,
+    pub journal_digest: String,
+    pub seal: String,
+    pub prover: ProverKind,
+    pub output_file: Option<PathBuf>,
+}
+
+impl Report for AggregateReport {
+    fn print_text(&self) {
+        println!("=== Aggregated Proof ===");
+        println!("Proofs: {}", self.proof_count);
+        for link in &self.output.links {
+            println!(
+                "{:?}: {} -> {}",
+                link.platform,
+                link.wallet_address,
+                hex::encode(link.social_account_hash)
+            );
+        }
+        println!("Merkle Root: 0x{}", hex::encode(self.output.merkle_root));
+        println!("Journal Digest: {}", self.journal_digest);
+        println!("Seal: {}", self.seal);
+        println!("Prover: {:?}", self.prover);
+        if let Some(path) = &self.output_file {
+            println!("Aggregated proof written to {}", path.display());
+        }
+    }
+}
+
+async fn aggregate(config: Option<&Path>, args: &AggregateArgs) -> std::result::Result<AggregateReport, CommandError> {
+    let service = load_config(config, Some(&args.service))?.build_service()?;
+    let envelopes = args
+        .inputs
+        .iter()
+        .map(|path| audit::read_envelope(Some(path)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // A proof that does not verify here would make the guest refuse the batch
+    for (path, envelope) in args.inputs.iter().zip(&envelopes) {
+        service
+            .verify_proof(envelope)
+            .map_err(|e| CommandError::Rejected(anyhow!("{}: {}", path.display(), e)))?;
+    }
+    let aggregated = service.aggregate(&envelopes).await?;
+
+    if let Some(path) = &args.output {
+        std::fs::write(path, serde_json::to_string_pretty(&aggregated).map_err(anyhow::Error::from)?)?;
+    }
+
+    Ok(AggregateReport {
+        proof_count: envelopes.len(),
+        journal_digest: format!("0x{}", hex::encode(aggregated.journal_digest)),
+        seal: format!("0x{}", hex::encode(&aggregated.seal)),
+        output: aggregated.output,
+        prover: aggregated.prover,
+        output_file: args.output.clone(),
+    })
+}
+
+impl Report for PreflightReport {
+    fn print_text(&self) {
+        println!("=== Preflight ===");
+        println!("Platform: {:?}", self.platform);
+        println!("Total Cycles: {}", self.total_cycles);
+        println!("User Cycles: {}", self.user_cycles);
+        println!("Segments: {}", self.segment_count);
+        println!("Verification Success: {}", self.verification_output.verification_success);
+        println!("Policy: {:?}", self.verification_output.policy_outcome);
+    }
+}
+
+async fn preflight(config: Option<&Path>, args: &VerificationArgs) -> std::result::Result<PreflightReport, CommandError> {
+    let service = load_config(config, Some(&args.service))?.build_service()?;
+    let input = service
+        .build_input(args.platform, args.token.read()?, args.wallet.clone(), args.nonce)
+        .await?;
+    // Over budget is a verdict on the input, not a failure to run it
+    service.preflight(&input).map_err(CommandError::Rejected)
+}
+
+async fn serve(config: Option<&Path>, args: &ServeArgs) -> std::result::Result<(), CommandError> {
+    let config = load_config(config, Some(&args.service))?;
+    let listen = args.listen.unwrap_or(config.server.listen);
+    let mut server = VerificationServer::from_service(config.build_service()?).with_rate_limit(config.rate_limit);
+    // The oracle reads what the server accepts, so both share the directory
+    if let Some(dir) = &config.oracle.token_dir {
+        server = server.with_token_inbox(dir);
+    }
+    eprintln!("Listening on http://{}", listen);
+    server.serve(listen).await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignReport {
+    pub signer: String,
+    pub request_id: Option<String>,
+    pub proof_hash: String,
+    pub signature: String,
+}
+
+impl Report for SignReport {
+    fn print_text(&self) {
+        println!("Signer: {}", self.signer);
+        if let Some(request_id) = &self.request_id {
+            println!("Request ID: {}", request_id);
+        }
+        println!("Proof Hash: {}", self.proof_hash);
+        println!("Signature: {}", self.signature);
+    }
+}
+
+fn sign(config: Option<&Path>, args: &SignArgs) -> std::result::Result<SignReport, CommandError> {
+    let config = load_config(config, Some(&args.service))?;
+    let signer = config.verifier_signer(args.keystore.as_deref())?;
+
+    // Only sign what the server would accept: proven by the embedded guest for
+    // an enabled platform on this deployment, and not yet expired
+    let envelope = audit::read_envelope(args.input.as_deref())?;
+    let output = config.build_service()?.verify_unexpired(&envelope)?;
+    let proof_hash = proof_hash(&envelope.journal);
+
+    let signature = match &args.request_id {
+        Some(request_id) => {
+            let request_id = request_id.parse().map_err(|e| anyhow!("Invalid request ID {}: {}", request_id, e))?;
+            signer.sign_submission(request_id, &output)?
+        }
+        None => signer.sign_registry_proof(&output, proof_hash.into())?,
+    };
+
+    Ok(SignReport {
+        signer: signer.address().to_string(),
+        request_id: args.request_id.clone(),
+        proof_hash: format!("0x{}", hex::encode(proof_hash)),
+        signature: format!("0x{}", hex::encode(signature)),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelayReport {
+    pub success: bool,
+    pub sender: String,
+    pub proof_hash: String,
+    pub jobs: Vec<RelayJob>,
+}
+
+impl Report for RelayReport {
+    fn print_text(&self) {
+        println!("=== Relay ===");
+        println!("Sender: {}", self.sender);
+        println!("Proof Hash: {}", self.proof_hash);
+        for job in &self.jobs {
+            println!(
+                "{:?}: {:?} (tx {}, block {}, {} attempt(s))",
+                job.step,
+                job.status,
+                job.tx_hash.as_deref().unwrap_or("-"),
+                job.block_number.map_or("-".to_string(), |block| block.to_string()),
+                job.attempts
+            );
+            if let Some(error) = &job.error {
+                println!("  {}", error);
+            }
+        }
+    }
+}
+
+async fn relay(config: Option<&Path>, args: &RelayArgs) -> std::result::Result<RelayReport, CommandError> {
+    let mut host_config = HostConfig::load(config)?;
+    args.service.apply(&mut host_config)?;
+    host_config.apply_env(|name| match name {
+        "RPC_URL" => args.rpc_url.clone(),
+        "VERIFIER_CONTRACT" => args.verifier_contract.clone(),
+        _ => None,
+    })?;
+    host_config.validate()?;
+    let relayer = host_config.relayer(args.keystore.as_deref())?;
+
+    let envelope = audit::read_envelope(args.input.as_deref())?;
+    let request_id = args
+        .request_id
+        .as_ref()
+        .map(|request_id| request_id.parse().map_err(|e| anyhow!("Invalid request ID {}: {}", request_id, e)))
+        .transpose()?;
+    let jobs = relayer.relay(&envelope, request_id).await?;
+
+    // The contract's answer is a verdict; running out of retries is not
+    if let Some(job) = jobs.iter().find(|job| job.status == RelayStatus::Reverted) {
+        return Err(CommandError::Rejected(anyhow!(
+            "{:?} reverted: {}",
+            job.step,
+            job.error.as_deref().unwrap_or("no reason")
+        )));
+    }
+    if let Some(job) = jobs.iter().find(|job| job.status != RelayStatus::Confirmed) {
+        return Err(CommandError::Failed(anyhow!(
+            "{:?} did not confirm after {} attempt(s): {}",
+            job.step,
+            job.attempts,
+            job.error.as_deref().unwrap_or("no error")
+        )));
+    }
+    Ok(RelayReport {
+        success: true,
+        sender: relayer.sender().to_string(),
+        proof_hash: format!("0x{}", hex::encode(proof_hash(&envelope.journal))),
+        jobs,
+    })
+}
+
+impl Report for PollReport {
+    fn print_text(&self) {
+        if let Some((from, to)) = self.scanned {
+            println!("Scanned blocks {}-{}: {} new request(s)", from, to, self.new_requests);
+        }
+        for request in &self.requests {
+            print!("0x{} ({:?}, {}): {:?}", request.request_id, request.platform, request.wallet_address, request.status);
+            match &request.error {
+                Some(error) => println!(" - {}", error),
+                None => println!(),
+            }
+        }
+    }
+}
+
+async fn oracle(config: Option<&Path>, args: &OracleArgs, json: bool) -> std::result::Result<(), CommandError> {
+    let mut host_config = HostConfig::load(config)?;
+    args.service.apply(&mut host_config)?;
+    host_config.apply_env(|name| match name {
+        "RPC_URL" => args.rpc_url.clone(),
+        "VERIFIER_CONTRACT" => args.verifier_contract.clone(),
+        "ORACLE_START_BLOCK" => args.start_block.map(|block| block.to_string()),
+        _ => None,
+    })?;
+    if args.token_dir.is_some() || args.token_url.is_some() {
+        host_config.oracle.token_dir = args.token_dir.clone();
+        host_config.oracle.token_url = args.token_url.clone();
+    }
+    host_config.validate()?;
+    let oracle = host_config.oracle(args.keystore.as_deref())?;
+
+    if args.once {
+        emit(json, &oracle.poll().await?);
+        return Ok(());
+    }
+    loop {
+        // A failed poll is retried on the next tick; its checkpoint did not move
+        match oracle.poll().await {
+            Ok(report) if report.new_requests > 0 || !report.requests.is_empty() => emit(json, &report),
+            Ok(_) => {}
+            Err(e) => emit_error(json, "failed", &e),
+        }
+        tokio::time::sleep(oracle.poll_interval()).await;
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigReport {
+    pub file: Option<PathBuf>,
+    /// Environment variables that overrode the file
+    pub env_overrides: Vec<String>,
+    pub config: HostConfig,
+}
+
+impl Report for ConfigReport {
+    fn print_text(&self) {
+        match &self.file {
+            Some(file) => println!("# Config file: {}", file.display()),
+            None => println!("# No config file, using defaults"),
+        }
+        for name in &self.env_overrides {
+            println!("# Overridden by {}", name);
+        }
+        match self.config.to_toml() {
+            Ok(toml) => print!("{}", toml),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
+
+fn config_check(path: Option<&Path>) -> std::result::Result<ConfigReport, CommandError> {
+    // Invalid config is the verdict this command exists to give
+    let config = load_config(path, None).map_err(CommandError::Rejected)?;
+    Ok(ConfigReport {
+        file: path.map(Path::to_path_buf),
+        env_overrides: ENV_OVERRIDES
+            .iter()
+            .filter(|(name, _)| std::env::var_os(name).is_some())
+            .map(|(name, key)| format!("{} ({})", name, key))
+            .collect(),
+        config,
+    })
+}
+
+impl Report for image_id::ImageIdReport {
+    fn print_text(&self) {
+        println!("Image ID: {}", self.image_id);
+        if self.updated {
+            println!("Updated {}", self.lockfile.display());
+        } else {
+            println!("Image ID matches {}", self.lockfile.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_token_is_not_positional() {
+        let cli = Cli::try_parse_from([
+            "host",
+            "prove",
+            "--platform",
+            "github",
+            "--wallet",
+            "0x1234567890123456789012345678901234567890",
+            "--verifying-contract",
+            "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8",
+            "--token-file",
+            "token.txt",
+            "--json",
+        ])
+        .unwrap();
+        assert!(cli.json);
+        match cli.command {
+            Command::Prove(args) => {
+                assert_eq!(args.verification.platform, SocialPlatform::Github);
+                assert_eq!(args.verification.token.token_file, Some(PathBuf::from("token.txt")));
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+
+        // A bare token argument is a usage error
+        assert!(Cli::try_parse_from(["host", "prove", "--platform", "github", "ghp_secret_token"]).is_err());
+
+        // Bundles name the variable holding each token
+        let cli = Cli::try_parse_from([
+            "host",
+            "prove-bundle",
+            "--account",
+            "twitter=TWITTER_TOKEN",
+            "--account",
+            "github=GITHUB_TOKEN",
+            "--wallet",
+            "0x1234567890123456789012345678901234567890",
+            "--minimum-platforms",
+            "2",
+        ])
+        .unwrap();
+        match cli.command {
+            Command::ProveBundle(args) => {
+                assert_eq!(
+                    args.accounts,
+                    vec![
+                        (SocialPlatform::Twitter, "TWITTER_TOKEN".to_string()),
+                        (SocialPlatform::Github, "GITHUB_TOKEN".to_string()),
+                    ]
+                );
+                assert_eq!(args.minimum_platforms, 2);
+            }
+            other => panic!("Unexpected command {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["host", "prove-bundle", "--account", "twitter:ghp_secret", "--wallet", "0x0"]).is_err());
+        assert!(Cli::try_parse_from(["host", "prove", "--platform", "myspace", "--wallet", "0x00"]).is_err());
+    }
+
+    #[test]
+    fn test_token_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token");
+        std::fs::write(&path, "ghp_from_file_1234567890\n").unwrap();
+
+        let from_file = TokenArgs {
+            token_stdin: false,
+            token_file: Some(path),
+            token_env: "UNUSED_TOKEN_VAR".to_string(),
+        };
+        assert_eq!(from_file.read().unwrap(), "ghp_from_file_1234567890");
+
+        std::env::set_var("CLI_TEST_OAUTH_TOKEN", "ghp_from_env_1234567890");
+        let from_env = TokenArgs {
+            token_stdin: false,
+            token_file: None,
+            token_env: "CLI_TEST_OAUTH_TOKEN".to_string(),
+        };
+        assert_eq!(from_env.read().unwrap(), "ghp_from_env_1234567890");
+
+        let missing = TokenArgs {
+            token_stdin: false,
+            token_file: None,
+            token_env: "CLI_TEST_MISSING_TOKEN".to_string(),
+        };
+        assert!(missing.read().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_policy_miss_is_rejected() {
+        let strict = crate::RegistryPolicies::from_json(
+            r#"{ "github": { "isEnabled": true, "minimumAccountAge": 0, "minimumFollowers": 1000000 } }"#,
+        )
+        .unwrap();
+        let service = crate::SocialVerificationService::new(crate::DeploymentDomain::new(
+            11155111,
+            "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8",
+        ))
+        .with_platform_policies(strict);
+
+        // Refused at preflight, before any proving
+        let error = service
+            .verify_social_account(
+                SocialPlatform::Github,
+                "ghp_github_token_1234567890".to_string(),
+                "0x1234567890123456789012345678901234567890".to_string(),
+                1,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(CommandError::from(error), CommandError::Rejected(_)));
+
+        let disabled = anyhow::Error::from(ProofVerificationError::PlatformDisabled(SocialPlatform::Telegram));
+        assert!(matches!(CommandError::from(disabled.context("prove")), CommandError::Rejected(_)));
+        assert!(matches!(CommandError::from(anyhow!("RPC unreachable")), CommandError::Failed(_)));
+    }
+}
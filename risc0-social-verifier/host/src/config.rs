@@ -0,0 +1,891 @@
+// Host configuration
+// Built-in defaults, overridden by a TOML file, overridden by environment
+// variables. The result is validated once at startup so a bad value fails with
+// the key that caused it instead of surfacing mid-request.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::policy_source::DEFAULT_REGISTRY_ABI;
+use crate::relayer::DEFAULT_VERIFIER_ABI;
+use crate::{
+    parse_platform, CycleBudget, DeploymentDomain, PlatformPolicy, PolicyCache, PolicySource, ProverBackend,
+    Oracle, ReceiptStore, ReceiptVerifier, RegistryPolicies, RegistryReader, Relayer, RemoteProver, RiskModel,
+    SocialPlatform, SocialVerificationService, SponsorshipTiers, TokenSource, VerifierSigner,
+};
+use alloy::signers::local::PrivateKeySigner;
+use risc0_zkvm::sha::Digest;
+use social_verifier_methods::SOCIAL_VERIFIER_ID;
+
+/// `deployments/sepolia-core.json` in this repository, wherever the host runs from
+const DEFAULT_DEPLOYMENT_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../deployments/sepolia-core.json");
+
+/// Environment variables that override the file, and the key each one sets
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("LISTEN_ADDR", "server.listen"),
+    ("PROVER_BACKEND", "prover.backend"),
+    ("REMOTE_PROVER_URL", "prover.remote_url"),
+    ("MAX_CYCLES", "prover.max_cycles"),
+    ("VERIFIER_KEYSTORE", "signer.keystore"),
+    ("RECEIPT_STORE", "storage.receipt_store"),
+    ("NETWORK", "chain.network"),
+    ("DEPLOYMENT_FILE", "chain.deployments.<network>.deployment_file"),
+    ("VERIFYING_CONTRACT", "chain.deployments.<network>.verifying_contract"),
+    ("RPC_URL", "chain.deployments.<network>.rpc_url"),
+    ("POLICY_SOURCE", "policy.source"),
+    ("ENABLED_PLATFORMS", "platforms.<name>.isEnabled"),
+    ("RATE_LIMIT_PER_MINUTE", "rate_limit.requests_per_minute"),
+    ("VERIFIER_CONTRACT", "relayer.verifier_contract"),
+    ("ORACLE_START_BLOCK", "oracle.start_block"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HostConfig {
+    pub server: ServerConfig,
+    pub prover: ProverConfig,
+    pub signer: SignerConfig,
+    pub storage: StorageConfig,
+    pub rate_limit: RateLimitConfig,
+    pub chain: ChainConfig,
+    pub policy: PolicyConfig,
+    /// Sybil-risk model the guest scores with; its hash is committed
+    pub risk: RiskModel,
+    /// `EIP7702Paymaster` policies by verification level
+    pub sponsorship: SponsorshipTiers,
+    pub relayer: RelayerConfig,
+    pub oracle: OracleConfig,
+    /// Keyed by platform name, using the registry's `PlatformConfig` field
+    /// names so the table can be compared with `platformConfigs` directly
+    pub platforms: BTreeMap<String, PlatformPolicy>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub listen: SocketAddr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverBackendKind {
+    Local,
+    Dev,
+    Remote,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProverConfig {
+    pub backend: ProverBackendKind,
+    pub remote_url: Option<String>,
+    /// Environment variable holding the remote prover API key; secrets stay
+    /// out of the config file
+    pub remote_api_key_env: String,
+    /// Reject inputs whose execution exceeds this many cycles
+    pub max_cycles: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignerConfig {
+    /// JSON keystore holding the verifier key
+    pub keystore: Option<PathBuf>,
+    pub keystore_password_env: String,
+    /// Raw private key variable, used when no keystore is configured
+    pub private_key_env: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Keep generated receipts in this directory
+    pub receipt_store: Option<PathBuf>,
+}
+
+/// Per-client limits on `POST /verify`; `requests_per_minute = 0` disables them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainConfig {
+    /// Which entry of `deployments` proofs are bound to
+    pub network: String,
+    pub deployments: BTreeMap<String, DeploymentConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeploymentConfig {
+    /// Deployment file providing the chain ID. A relative path in a config
+    /// file is relative to that file
+    pub deployment_file: PathBuf,
+    /// `SocialAccountRegistry` address
+    pub verifying_contract: Option<String>,
+    /// JSON-RPC endpoint for reading the deployment
+    pub rpc_url: Option<String>,
+}
+
+/// How `host relay` sends transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RelayerConfig {
+    /// `RiscZeroSocialVerifier` address, needed to answer verification requests
+    pub verifier_contract: Option<String>,
+    pub verifier_abi: PathBuf,
+    /// Environment variable holding the key transactions are sent from. It
+    /// must be an authorized verifier for `submitProof`.
+    pub private_key_env: String,
+    /// Blocks a transaction must be buried under before it counts
+    pub confirmations: u64,
+    /// Sends per step before giving up on transient errors
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each one after
+    pub retry_delay_ms: u64,
+    /// Gas limit as a percentage of the node's estimate
+    pub gas_multiplier_percent: u64,
+    /// Wait this long for a receipt before sending again
+    pub receipt_timeout_secs: u64,
+}
+
+/// How `host oracle` watches for verification requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OracleConfig {
+    /// First block to scan when the store has no checkpoint yet
+    pub start_block: u64,
+    /// Blocks behind the head a request must be before it is read
+    pub confirmations: u64,
+    /// Most blocks read per `eth_getLogs` call
+    pub max_block_range: u64,
+    pub poll_interval_secs: u64,
+    /// Skip requests with less than this long left before `requestTimeout`,
+    /// since the proof would not land in time
+    pub timeout_margin_secs: u64,
+    /// Directory the app drops tokens into, one `<requestId>` file each
+    pub token_dir: Option<PathBuf>,
+    /// App endpoint serving `GET <token_url>/<requestId>`
+    pub token_url: Option<String>,
+    /// Environment variable holding the bearer key for `token_url`
+    pub token_api_key_env: String,
+}
+
+/// Where platform requirements come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicySourceKind {
+    /// The `[platforms]` table
+    Config,
+    /// A JSON snapshot of `platformConfigs`
+    Snapshot,
+    /// `platformConfigs` on the selected deployment's registry
+    Registry,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    pub source: PolicySourceKind,
+    pub snapshot: Option<PathBuf>,
+    pub registry_abi: PathBuf,
+    /// How long a fetched policy is used before it is read again
+    pub ttl_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 8080)),
+        }
+    }
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        Self {
+            backend: ProverBackendKind::Local,
+            remote_url: None,
+            remote_api_key_env: "REMOTE_PROVER_API_KEY".to_string(),
+            max_cycles: None,
+        }
+    }
+}
+
+impl Default for SignerConfig {
+    fn default() -> Self {
+        Self {
+            keystore: None,
+            keystore_password_env: "VERIFIER_KEYSTORE_PASSWORD".to_string(),
+            private_key_env: "VERIFIER_PRIVATE_KEY".to_string(),
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 30,
+            burst: 5,
+        }
+    }
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            source: PolicySourceKind::Config,
+            snapshot: None,
+            registry_abi: PathBuf::from(DEFAULT_REGISTRY_ABI),
+            ttl_secs: 300,
+        }
+    }
+}
+
+impl Default for RelayerConfig {
+    fn default() -> Self {
+        Self {
+            verifier_contract: None,
+            verifier_abi: PathBuf::from(DEFAULT_VERIFIER_ABI),
+            private_key_env: "RELAYER_PRIVATE_KEY".to_string(),
+            confirmations: 1,
+            max_attempts: 3,
+            retry_delay_ms: 2000,
+            gas_multiplier_percent: 120,
+            receipt_timeout_secs: 120,
+        }
+    }
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            start_block: 0,
+            confirmations: 2,
+            max_block_range: 1000,
+            poll_interval_secs: 12,
+            timeout_margin_secs: 300,
+            token_dir: None,
+            token_url: None,
+            token_api_key_env: "ORACLE_TOKEN_API_KEY".to_string(),
+        }
+    }
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        let mut deployments = BTreeMap::new();
+        deployments.insert(
+            "sepolia".to_string(),
+            DeploymentConfig {
+                deployment_file: PathBuf::from(DEFAULT_DEPLOYMENT_FILE),
+                verifying_contract: None,
+                rpc_url: None,
+            },
+        );
+        Self {
+            network: "sepolia".to_string(),
+            deployments,
+        }
+    }
+}
+
+impl Default for HostConfig {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::default(),
+            prover: ProverConfig::default(),
+            signer: SignerConfig::default(),
+            storage: StorageConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            chain: ChainConfig::default(),
+            policy: PolicyConfig::default(),
+            risk: RiskModel::default(),
+            sponsorship: SponsorshipTiers::default(),
+            relayer: RelayerConfig::default(),
+            oracle: OracleConfig::default(),
+            platforms: RegistryPolicies::registry_defaults().platforms,
+        }
+    }
+}
+
+impl HostConfig {
+    /// Defaults, then `path` if given, then the process environment. Callers
+    /// apply their own overrides and then `validate`.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    /// Read a config file, resolving its relative deployment files against
+    /// the file's directory rather than wherever the host was started
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read config {}: {}", path.display(), e))?;
+        let mut config = Self::from_toml(&contents).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+        if let Some(dir) = path.parent() {
+            for deployment in config.chain.deployments.values_mut() {
+                if deployment.deployment_file.is_relative() {
+                    deployment.deployment_file = dir.join(&deployment.deployment_file);
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Parse a file; tables it leaves out keep their defaults
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+
+    /// Apply the overrides in `ENV_OVERRIDES`, reading variables through `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let parse_error = |name: &str, value: &str, e: &dyn std::fmt::Display| anyhow!("{}={}: {}", name, value, e);
+
+        if let Some(listen) = var("LISTEN_ADDR") {
+            self.server.listen = listen.parse().map_err(|e| parse_error("LISTEN_ADDR", &listen, &e))?;
+        }
+        if let Some(backend) = var("PROVER_BACKEND") {
+            self.prover.backend = match backend.as_str() {
+                "local" => ProverBackendKind::Local,
+                "dev" => ProverBackendKind::Dev,
+                "remote" => ProverBackendKind::Remote,
+                _ => return Err(parse_error("PROVER_BACKEND", &backend, &"expected local, dev or remote")),
+            };
+        }
+        if let Some(url) = var("REMOTE_PROVER_URL") {
+            self.prover.remote_url = Some(url);
+        }
+        if let Some(max_cycles) = var("MAX_CYCLES") {
+            self.prover.max_cycles = Some(max_cycles.parse().map_err(|e| parse_error("MAX_CYCLES", &max_cycles, &e))?);
+        }
+        if let Some(keystore) = var("VERIFIER_KEYSTORE") {
+            self.signer.keystore = Some(PathBuf::from(keystore));
+        }
+        if let Some(store) = var("RECEIPT_STORE") {
+            self.storage.receipt_store = Some(PathBuf::from(store));
+        }
+        if let Some(network) = var("NETWORK") {
+            self.chain.network = network;
+        }
+
+        // Deployment overrides apply to the selected network, creating it if needed
+        let deployment_file = var("DEPLOYMENT_FILE");
+        let verifying_contract = var("VERIFYING_CONTRACT");
+        let rpc_url = var("RPC_URL");
+        if deployment_file.is_some() || verifying_contract.is_some() || rpc_url.is_some() {
+            let network = self.chain.network.clone();
+            let deployment = match self.chain.deployments.get_mut(&network) {
+                Some(deployment) => deployment,
+                None => {
+                    let deployment_file = deployment_file
+                        .clone()
+                        .ok_or_else(|| anyhow!("DEPLOYMENT_FILE must be set for network {}", network))?;
+                    self.chain.deployments.entry(network).or_insert(DeploymentConfig {
+                        deployment_file: PathBuf::from(deployment_file),
+                        verifying_contract: None,
+                        rpc_url: None,
+                    })
+                }
+            };
+            if let Some(deployment_file) = deployment_file {
+                deployment.deployment_file = PathBuf::from(deployment_file);
+            }
+            if verifying_contract.is_some() {
+                deployment.verifying_contract = verifying_contract;
+            }
+            if rpc_url.is_some() {
+                deployment.rpc_url = rpc_url;
+            }
+        }
+        if let Some(source) = var("POLICY_SOURCE") {
+            self.policy.source = match source.as_str() {
+                "config" => PolicySourceKind::Config,
+                "snapshot" => PolicySourceKind::Snapshot,
+                "registry" => PolicySourceKind::Registry,
+                _ => return Err(parse_error("POLICY_SOURCE", &source, &"expected config, snapshot or registry")),
+            };
+        }
+
+        if let Some(enabled) = var("ENABLED_PLATFORMS") {
+            let enabled: Vec<&str> = enabled.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+            for name in &enabled {
+                if !self.platforms.contains_key(*name) {
+                    return Err(parse_error("ENABLED_PLATFORMS", name, &"no [platforms] entry for this platform"));
+                }
+            }
+            for (name, policy) in self.platforms.iter_mut() {
+                policy.is_enabled = enabled.contains(&name.as_str());
+            }
+        }
+        if let Some(limit) = var("RATE_LIMIT_PER_MINUTE") {
+            self.rate_limit.requests_per_minute =
+                limit.parse().map_err(|e| parse_error("RATE_LIMIT_PER_MINUTE", &limit, &e))?;
+        }
+        if let Some(verifier) = var("VERIFIER_CONTRACT") {
+            self.relayer.verifier_contract = Some(verifier);
+        }
+        if let Some(start_block) = var("ORACLE_START_BLOCK") {
+            self.oracle.start_block =
+                start_block.parse().map_err(|e| parse_error("ORACLE_START_BLOCK", &start_block, &e))?;
+        }
+        Ok(())
+    }
+
+    /// Report every invalid key at once rather than one per restart
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.prover.backend == ProverBackendKind::Remote && self.prover.remote_url.is_none() {
+            problems.push("prover.remote_url must be set when prover.backend = \"remote\"".to_string());
+        }
+        if let Some(url) = &self.prover.remote_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(format!("prover.remote_url must be an http(s) URL, got {}", url));
+            }
+        }
+        if self.prover.max_cycles == Some(0) {
+            problems.push("prover.max_cycles must be greater than 0".to_string());
+        }
+
+        if self.rate_limit.requests_per_minute > 0 && self.rate_limit.burst == 0 {
+            problems.push("rate_limit.burst must be at least 1 when rate limiting is enabled".to_string());
+        }
+
+        if !self.chain.deployments.contains_key(&self.chain.network) {
+            problems.push(format!(
+                "chain.network = \"{}\" has no [chain.deployments.{}] entry",
+                self.chain.network, self.chain.network
+            ));
+        }
+        for (network, deployment) in &self.chain.deployments {
+            if let Some(address) = &deployment.verifying_contract {
+                if !is_address(address) {
+                    problems.push(format!(
+                        "chain.deployments.{}.verifying_contract is not a 20-byte hex address: {}",
+                        network, address
+                    ));
+                }
+            }
+        }
+
+        let deployment = self.chain.deployments.get(&self.chain.network);
+        match self.policy.source {
+            PolicySourceKind::Config => {}
+            PolicySourceKind::Snapshot if self.policy.snapshot.is_none() => {
+                problems.push("policy.snapshot must be set when policy.source = \"snapshot\"".to_string());
+            }
+            PolicySourceKind::Snapshot => {}
+            PolicySourceKind::Registry => {
+                if deployment.is_none_or(|deployment| deployment.rpc_url.is_none()) {
+                    problems.push(format!(
+                        "chain.deployments.{}.rpc_url must be set when policy.source = \"registry\"",
+                        self.chain.network
+                    ));
+                }
+            }
+        }
+
+        if self.risk.version == 0 {
+            problems.push("risk.version must be greater than 0".to_string());
+        }
+        if self.risk.young_account_age > self.risk.mature_account_age {
+            problems.push("risk.young_account_age must not exceed risk.mature_account_age".to_string());
+        }
+
+        problems.extend(self.sponsorship.problems());
+
+        if let Some(address) = &self.relayer.verifier_contract {
+            if !is_address(address) {
+                problems.push(format!("relayer.verifier_contract is not a 20-byte hex address: {}", address));
+            }
+        }
+        if self.relayer.max_attempts == 0 {
+            problems.push("relayer.max_attempts must be at least 1".to_string());
+        }
+        if self.relayer.gas_multiplier_percent < 100 {
+            problems.push("relayer.gas_multiplier_percent must be at least 100".to_string());
+        }
+
+        if self.oracle.max_block_range == 0 {
+            problems.push("oracle.max_block_range must be at least 1".to_string());
+        }
+        if self.oracle.poll_interval_secs == 0 {
+            problems.push("oracle.poll_interval_secs must be greater than 0".to_string());
+        }
+        if self.oracle.token_dir.is_some() && self.oracle.token_url.is_some() {
+            problems.push("set only one of oracle.token_dir and oracle.token_url".to_string());
+        }
+        if let Some(url) = &self.oracle.token_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(format!("oracle.token_url must be an http(s) URL, got {}", url));
+            }
+        }
+
+        for name in self.platforms.keys() {
+            if parse_platform(name).is_none() {
+                problems.push(format!(
+                    "platforms.{} is not a supported platform (twitter, discord, github, telegram, linkedin)",
+                    name
+                ));
+            }
+        }
+        if self.enabled_platforms().is_empty() {
+            problems.push("at least one platform must be enabled".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid configuration:\n  - {}", problems.join("\n  - ")))
+        }
+    }
+
+    pub fn enabled_platforms(&self) -> Vec<SocialPlatform> {
+        self.platforms
+            .iter()
+            .filter(|(_, policy)| policy.is_enabled)
+            .filter_map(|(name, _)| parse_platform(name))
+            .collect()
+    }
+
+    /// Platform policies in the form `verify --policy` checks proofs against
+    pub fn registry_policies(&self) -> RegistryPolicies {
+        RegistryPolicies {
+            platforms: self.platforms.clone(),
+        }
+    }
+
+    /// The `chain.deployments` entry for `chain.network`
+    pub fn deployment(&self) -> Result<&DeploymentConfig> {
+        self.chain
+            .deployments
+            .get(&self.chain.network)
+            .ok_or_else(|| anyhow!("No deployment for network {}", self.chain.network))
+    }
+
+    /// The deployment proofs are bound to. Fails if its registry address is unset.
+    pub fn domain(&self) -> Result<DeploymentDomain> {
+        let deployment = self.deployment()?;
+        let verifying_contract = deployment.verifying_contract.as_deref().ok_or_else(|| {
+            anyhow!(
+                "chain.deployments.{}.verifying_contract (or VERIFYING_CONTRACT) must be set to the registry address",
+                self.chain.network
+            )
+        })?;
+        DeploymentDomain::from_deployment_file(&deployment.deployment_file, verifying_contract)
+    }
+
+    /// Platform requirements from the configured source, cached for `policy.ttl_secs`
+    pub fn policy_cache(&self) -> Result<PolicyCache> {
+        let ttl = Duration::from_secs(self.policy.ttl_secs);
+        let source = match self.policy.source {
+            PolicySourceKind::Config => return Ok(PolicyCache::fixed(self.registry_policies())),
+            PolicySourceKind::Snapshot => {
+                let snapshot = self.policy.snapshot.clone().ok_or_else(|| anyhow!("policy.snapshot is not set"))?;
+                PolicySource::Snapshot(snapshot)
+            }
+            PolicySourceKind::Registry => {
+                let deployment = self.deployment()?;
+                let rpc_url = deployment.rpc_url.as_deref().ok_or_else(|| anyhow!("No RPC URL for {}", self.chain.network))?;
+                let registry = self.domain()?.verifying_contract;
+                PolicySource::Registry(RegistryReader::new(rpc_url, &registry, &self.policy.registry_abi)?)
+            }
+        };
+        Ok(PolicyCache::new(source, ttl))
+    }
+
+    pub fn prover_backend(&self) -> ProverBackend {
+        match self.prover.backend {
+            ProverBackendKind::Local => ProverBackend::Local,
+            ProverBackendKind::Dev => ProverBackend::DevMode,
+            ProverBackendKind::Remote => ProverBackend::Remote(RemoteProver::new(
+                self.prover.remote_url.clone().unwrap_or_default(),
+                std::env::var(&self.prover.remote_api_key_env).ok(),
+            )),
+        }
+    }
+
+    /// A verification service wired up as configured
+    pub fn build_service(&self) -> Result<SocialVerificationService> {
+        let receipt_verifier =
+            ReceiptVerifier::new(vec![Digest::from(SOCIAL_VERIFIER_ID)]).with_enabled_platforms(self.enabled_platforms());
+        let mut service = SocialVerificationService::new(self.domain()?)
+            .with_prover(self.prover_backend())
+            .with_receipt_verifier(receipt_verifier)
+            .with_policy_cache(Arc::new(self.policy_cache()?))
+            .with_risk_model(self.risk)
+            .with_sponsorship_tiers(self.sponsorship.clone());
+        if let Some(store) = &self.storage.receipt_store {
+            service = service.with_store(Arc::new(ReceiptStore::open(store)?));
+        }
+        if let Some(max_total_cycles) = self.prover.max_cycles {
+            service = service.with_cycle_budget(CycleBudget { max_total_cycles });
+        }
+        Ok(service)
+    }
+
+    /// The verifier key from `keystore`, `signer.keystore` or `signer.private_key_env`
+    pub fn verifier_signer(&self, keystore: Option<&Path>) -> Result<VerifierSigner> {
+        match keystore.or(self.signer.keystore.as_deref()) {
+            Some(keystore) => {
+                let password = std::env::var(&self.signer.keystore_password_env)
+                    .map_err(|_| anyhow!("{} must hold the keystore password", self.signer.keystore_password_env))?;
+                VerifierSigner::from_keystore(keystore, &password)
+            }
+            None => {
+                let private_key = std::env::var(&self.signer.private_key_env)
+                    .map_err(|_| anyhow!("Set {} or configure signer.keystore", self.signer.private_key_env))?;
+                VerifierSigner::from_private_key(&private_key)
+            }
+        }
+    }
+
+    /// A relayer for the selected deployment, sending from `relayer.private_key_env`
+    pub fn relayer(&self, keystore: Option<&Path>) -> Result<Relayer> {
+        let deployment = self.deployment()?;
+        let rpc_url = deployment.rpc_url.as_deref().ok_or_else(|| {
+            anyhow!("chain.deployments.{}.rpc_url (or RPC_URL) must be set to relay", self.chain.network)
+        })?;
+        let sender: PrivateKeySigner = std::env::var(&self.relayer.private_key_env)
+            .map_err(|_| anyhow!("Set {} to the relayer's private key", self.relayer.private_key_env))?
+            .trim()
+            .parse()?;
+        Relayer::connect(
+            Arc::new(self.build_service()?),
+            self.verifier_signer(keystore)?,
+            sender,
+            rpc_url,
+            &self.policy.registry_abi,
+            self.relayer.clone(),
+        )
+    }
+
+    /// An oracle answering requests on `relayer.verifier_contract`
+    pub fn oracle(&self, keystore: Option<&Path>) -> Result<Oracle> {
+        let tokens = match (&self.oracle.token_dir, &self.oracle.token_url) {
+            (Some(dir), None) => TokenSource::Directory(dir.clone()),
+            (None, Some(url)) => TokenSource::http(url.clone(), std::env::var(&self.oracle.token_api_key_env).ok()),
+            _ => return Err(anyhow!("Set one of oracle.token_dir or oracle.token_url for the oracle's tokens")),
+        };
+        Oracle::new(self.relayer(keystore)?, tokens, self.oracle.clone())
+    }
+}
+
+fn is_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const REGISTRY: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = HostConfig::default();
+        config.validate().unwrap();
+        assert_eq!(
+            config.enabled_platforms(),
+            vec![SocialPlatform::Discord, SocialPlatform::Github, SocialPlatform::Twitter]
+        );
+        // Round trips through TOML, so `config check` output can be used as a config file
+        assert_eq!(HostConfig::from_toml(&config.to_toml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_layering() {
+        let mut config = HostConfig::from_toml(
+            r#"
+            [prover]
+            backend = "remote"
+            remote_url = "https://prover.example.com"
+
+            [chain.deployments.sepolia]
+            deployment_file = "../deployments/sepolia-core.json"
+            verifying_contract = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8"
+
+            [platforms.github]
+            isEnabled = true
+            minimumAccountAge = 86400
+            minimumFollowers = 0
+            "#,
+        )
+        .unwrap();
+
+        // Tables left out keep their defaults, but a listed [platforms] table replaces them
+        assert_eq!(config.server, ServerConfig::default());
+        assert_eq!(config.enabled_platforms(), vec![SocialPlatform::Github]);
+        assert_eq!(config.chain.deployments["sepolia"].verifying_contract.as_deref(), Some(REGISTRY));
+
+        config
+            .apply_env(env(&[("PROVER_BACKEND", "dev"), ("LISTEN_ADDR", "0.0.0.0:9000"), ("MAX_CYCLES", "1000000")]))
+            .unwrap();
+        assert_eq!(config.prover.backend, ProverBackendKind::Dev);
+        assert_eq!(config.server.listen, "0.0.0.0:9000".parse().unwrap());
+        assert_eq!(config.prover.max_cycles, Some(1_000_000));
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_env_deployment_and_platforms() {
+        let mut config = HostConfig::default();
+        config
+            .apply_env(env(&[("VERIFYING_CONTRACT", REGISTRY), ("ENABLED_PLATFORMS", "twitter")]))
+            .unwrap();
+        assert_eq!(config.chain.deployments["sepolia"].verifying_contract.as_deref(), Some(REGISTRY));
+        assert_eq!(config.enabled_platforms(), vec![SocialPlatform::Twitter]);
+
+        // A new network needs its deployment file
+        let mut config = HostConfig::default();
+        assert!(config.apply_env(env(&[("NETWORK", "mainnet"), ("VERIFYING_CONTRACT", REGISTRY)])).is_err());
+
+        assert!(HostConfig::default().apply_env(env(&[("ENABLED_PLATFORMS", "myspace")])).is_err());
+        assert!(HostConfig::default().apply_env(env(&[("PROVER_BACKEND", "gpu")])).is_err());
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let config = HostConfig::from_toml(
+            r#"
+            [prover]
+            backend = "remote"
+
+            [rate_limit]
+            requests_per_minute = 10
+            burst = 0
+
+            [chain]
+            network = "mainnet"
+
+            [risk]
+            version = 0
+
+            [relayer]
+            verifier_contract = "0x1234"
+            max_attempts = 0
+
+            [oracle]
+            max_block_range = 0
+            token_url = "ftp://tokens.example.com"
+
+            [[sponsorship.tiers]]
+            id = 1
+            name = "verified"
+            minimum_verification_level = 1
+            maximum_risk_score = 50
+            minimum_account_age = 0
+            policy = { dailyGasLimit = 1, perTxGasLimit = 2, dailyTxLimit = 1, requiresWhitelist = false, requiresSocialVerification = true, minimumVerificationLevel = 1, isActive = true }
+
+            [platforms.myspace]
+            isEnabled = true
+            minimumAccountAge = 0
+            minimumFollowers = 0
+            "#,
+        )
+        .unwrap();
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("prover.remote_url"));
+        assert!(error.contains("rate_limit.burst"));
+        assert!(error.contains("chain.network"));
+        assert!(error.contains("risk.version"));
+        assert!(error.contains("minimum_verification_level = 0"));
+        assert!(error.contains("more gas per transaction"));
+        assert!(error.contains("relayer.verifier_contract"));
+        assert!(error.contains("relayer.max_attempts"));
+        assert!(error.contains("oracle.max_block_range"));
+        assert!(error.contains("oracle.token_url"));
+        assert!(error.contains("platforms.myspace"));
+        assert!(error.contains("at least one platform"));
+
+        // Typos are errors rather than silently ignored keys
+        assert!(HostConfig::from_toml("[server]\nlisten_addr = \"127.0.0.1:1\"").is_err());
+    }
+
+    #[test]
+    fn test_policy_source_settings() {
+        let mut config = HostConfig::default();
+        config.apply_env(env(&[("POLICY_SOURCE", "registry")])).unwrap();
+        assert!(config.validate().unwrap_err().to_string().contains("rpc_url"));
+
+        config
+            .apply_env(env(&[("RPC_URL", "http://127.0.0.1:8545"), ("VERIFYING_CONTRACT", REGISTRY)]))
+            .unwrap();
+        config.validate().unwrap();
+        config.policy.registry_abi =
+            PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../exports/SocialAccountRegistry.json"));
+        assert!(config.policy_cache().is_ok());
+
+        let mut config = HostConfig::default();
+        config.policy.source = PolicySourceKind::Snapshot;
+        assert!(config.validate().unwrap_err().to_string().contains("policy.snapshot"));
+    }
+
+    #[test]
+    fn test_deployment_file_resolution() {
+        // The default does not depend on the working directory
+        let config = HostConfig::default();
+        assert!(config.deployment().unwrap().deployment_file.is_file());
+
+        // Relative to the config file, so the example works from anywhere
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("host.toml");
+        std::fs::write(
+            &path,
+            "[chain.deployments.local]\ndeployment_file = \"local.json\"\n\n[chain.deployments.sepolia]\ndeployment_file = \"/srv/sepolia-core.json\"\n",
+        )
+        .unwrap();
+        let config = HostConfig::from_file(&path).unwrap();
+        assert_eq!(config.chain.deployments["local"].deployment_file, dir.path().join("local.json"));
+        assert_eq!(config.chain.deployments["sepolia"].deployment_file, PathBuf::from("/srv/sepolia-core.json"));
+
+        let example = concat!(env!("CARGO_MANIFEST_DIR"), "/../config.example.toml");
+        let config = HostConfig::from_file(Path::new(example)).unwrap();
+        assert!(config.deployment().unwrap().deployment_file.is_file());
+    }
+
+    #[test]
+    fn test_missing_deployment_is_an_error() {
+        let mut config = HostConfig::default();
+        config.chain.network = "mainnet".to_string();
+        config.policy.source = PolicySourceKind::Registry;
+        assert!(config.deployment().is_err());
+        assert!(config.domain().is_err());
+        assert!(config.policy_cache().is_err());
+        assert!(config.relayer(None).is_err());
+    }
+
+    #[test]
+    fn test_example_config() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../config.example.toml");
+        let config = HostConfig::from_toml(&std::fs::read_to_string(path).unwrap()).unwrap();
+        config.validate().unwrap();
+    }
+}
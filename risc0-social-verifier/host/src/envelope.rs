@@ -0,0 +1,244 @@
+// Proof envelope
+// Self-describing container for a receipt: format version, receipt kind,
+// image ID, journal and the encoded seal. Everything that hands a proof to
+// someone else (web responses, CLI files, the receipt store, the remote
+// prover) moves `ProofEnvelope`s rather than bare receipt bytes.
+
+use anyhow::{anyhow, Result};
+use risc0_zkvm::{sha::Digest, InnerReceipt, Receipt};
+use serde::{Deserialize, Serialize};
+
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// Which kind of seal the receipt carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptKind {
+    /// Segment receipts, as produced by the prover
+    Composite,
+    /// Recursion-compressed STARK
+    Succinct,
+    /// SNARK suitable for on-chain verification
+    Groth16,
+    /// Dev-mode receipt, only valid with `RISC0_DEV_MODE`
+    Fake,
+}
+
+/// How `seal` is serialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SealEncoding {
+    Bincode,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    pub version: u32,
+    pub kind: ReceiptKind,
+    /// `0x`-prefixed image ID the receipt claims
+    pub image_id: String,
+    #[serde(with = "hex_bytes")]
+    pub journal: Vec<u8>,
+    pub encoding: SealEncoding,
+    /// The receipt's `InnerReceipt`, encoded as `encoding` says
+    #[serde(with = "hex_bytes")]
+    pub seal: Vec<u8>,
+}
+
+impl ProofEnvelope {
+    pub fn from_receipt(receipt: &Receipt, image_id: Digest, encoding: SealEncoding) -> Result<Self> {
+        let kind = match &receipt.inner {
+            InnerReceipt::Composite(_) => ReceiptKind::Composite,
+            InnerReceipt::Succinct(_) => ReceiptKind::Succinct,
+            InnerReceipt::Groth16(_) => ReceiptKind::Groth16,
+            InnerReceipt::Fake(_) => ReceiptKind::Fake,
+            _ => return Err(anyhow!("Unsupported receipt kind")),
+        };
+        let seal = match encoding {
+            SealEncoding::Bincode => bincode::serialize(&receipt.inner)?,
+            SealEncoding::Json => serde_json::to_vec(&receipt.inner)?,
+        };
+
+        Ok(Self {
+            version: ENVELOPE_VERSION,
+            kind,
+            image_id: format!("0x{}", hex::encode(image_id.as_bytes())),
+            journal: receipt.journal.bytes.clone(),
+            encoding,
+            seal,
+        })
+    }
+
+    /// Rebuild the receipt. This does not verify it.
+    pub fn to_receipt(&self) -> Result<Receipt> {
+        self.check_version()?;
+        let inner: InnerReceipt = match self.encoding {
+            SealEncoding::Bincode => bincode::deserialize(&self.seal)?,
+            SealEncoding::Json => serde_json::from_slice(&self.seal)?,
+        };
+        Ok(Receipt::new(inner, self.journal.clone()))
+    }
+
+    pub fn image_id(&self) -> Result<Digest> {
+        let bytes = hex::decode(self.image_id.trim_start_matches("0x"))?;
+        Digest::try_from(bytes.as_slice()).map_err(|_| anyhow!("Invalid image ID {}", self.image_id))
+    }
+
+    pub fn to_bincode(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self> {
+        let envelope: Self = bincode::deserialize(bytes)?;
+        envelope.check_version()?;
+        Ok(envelope)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let envelope: Self = serde_json::from_str(json)?;
+        envelope.check_version()?;
+        Ok(envelope)
+    }
+
+    /// Base64 of the bincode envelope, for JSON APIs
+    pub fn to_base64(&self) -> Result<String> {
+        Ok(base64::encode(self.to_bincode()?))
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        Self::from_bincode(&base64::decode(encoded.trim())?)
+    }
+
+    /// `0x`-prefixed hex of the bincode envelope, for calldata-style transport
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(format!("0x{}", hex::encode(self.to_bincode()?)))
+    }
+
+    pub fn from_hex(encoded: &str) -> Result<Self> {
+        Self::from_bincode(&hex::decode(encoded.trim().trim_start_matches("0x"))?)
+    }
+
+    /// Decode any of the string transports: JSON, `0x` hex or base64
+    pub fn decode_str(encoded: &str) -> Result<Self> {
+        let encoded = encoded.trim();
+        if encoded.starts_with('{') {
+            Self::from_json(encoded)
+        } else if encoded.starts_with("0x") {
+            Self::from_hex(encoded)
+        } else {
+            Self::from_base64(encoded)
+        }
+    }
+
+    fn check_version(&self) -> Result<()> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(anyhow!("Unsupported proof envelope version {}", self.version));
+        }
+        Ok(())
+    }
+}
+
+/// Hex strings in human-readable formats, raw bytes otherwise
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            let encoded = String::deserialize(deserializer)?;
+            hex::decode(encoded.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+        } else {
+            Vec::<u8>::deserialize(deserializer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prover::dev_mode::DevModeGuard;
+
+    fn envelope() -> ProofEnvelope {
+        ProofEnvelope {
+            version: ENVELOPE_VERSION,
+            kind: ReceiptKind::Succinct,
+            image_id: format!("0x{}", hex::encode(Digest::from([7u32; 8]).as_bytes())),
+            journal: vec![1, 2, 3, 4],
+            encoding: SealEncoding::Bincode,
+            seal: vec![0xde, 0xad, 0xbe, 0xef],
+        }
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let envelope = envelope();
+        assert_eq!(ProofEnvelope::from_bincode(&envelope.to_bincode().unwrap()).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let envelope = envelope();
+        let json = envelope.to_json().unwrap();
+        assert!(json.contains("\"kind\": \"succinct\""));
+        assert!(json.contains("\"journal\": \"0x01020304\""));
+        assert_eq!(ProofEnvelope::from_json(&json).unwrap(), envelope);
+        assert_eq!(ProofEnvelope::decode_str(&json).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_string_transport_round_trip() {
+        let envelope = envelope();
+
+        let base64 = envelope.to_base64().unwrap();
+        assert_eq!(ProofEnvelope::from_base64(&base64).unwrap(), envelope);
+        assert_eq!(ProofEnvelope::decode_str(&base64).unwrap(), envelope);
+
+        let hex = envelope.to_hex().unwrap();
+        assert_eq!(ProofEnvelope::from_hex(&hex).unwrap(), envelope);
+        assert_eq!(ProofEnvelope::decode_str(&hex).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_image_id_and_version() {
+        let mut envelope = envelope();
+        assert_eq!(envelope.image_id().unwrap(), Digest::from([7u32; 8]));
+
+        envelope.version = ENVELOPE_VERSION + 1;
+        assert!(ProofEnvelope::from_bincode(&envelope.to_bincode().unwrap()).is_err());
+        assert!(ProofEnvelope::from_json(&envelope.to_json().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_receipt_round_trip() {
+        let _dev_mode = DevModeGuard::enable_blocking();
+        let receipt = Receipt::new(
+            InnerReceipt::Fake(risc0_zkvm::FakeReceipt::new(risc0_zkvm::ReceiptClaim::ok(
+                Digest::from([7u32; 8]),
+                vec![1u8, 2, 3],
+            ))),
+            vec![1u8, 2, 3],
+        );
+
+        for encoding in [SealEncoding::Bincode, SealEncoding::Json] {
+            let envelope = ProofEnvelope::from_receipt(&receipt, Digest::from([7u32; 8]), encoding).unwrap();
+            assert_eq!(envelope.kind, ReceiptKind::Fake);
+
+            let decoded = ProofEnvelope::decode_str(&envelope.to_base64().unwrap()).unwrap();
+            let rebuilt = decoded.to_receipt().unwrap();
+            assert_eq!(rebuilt.journal.bytes, receipt.journal.bytes);
+            rebuilt.verify(Digest::from([7u32; 8])).unwrap();
+        }
+    }
+}
@@ -0,0 +1,172 @@
+// Image ID lockfile
+// On-chain verifiers pin the guest image ID, so a rebuild that changes it has
+// to be caught before deployment rather than by reverted transactions.
+
+use anyhow::{anyhow, Result};
+use clap::Args;
+use risc0_zkvm::{compute_image_id, sha::Digest};
+use serde::{Deserialize, Serialize};
+use social_verifier_methods::SOCIAL_VERIFIER_ID;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::cli::CommandError;
+
+pub const DEFAULT_LOCKFILE: &str = "../deployments/image-id.lock";
+
+/// Contents of `deployments/image-id.lock`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageIdLock {
+    pub guest: String,
+    /// ID of the committed reproducible build
+    pub image_id: Option<String>,
+    /// ID pinned by the verifier deployed on each network
+    #[serde(default)]
+    pub networks: BTreeMap<String, Option<String>>,
+}
+
+impl ImageIdLock {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        std::fs::write(path.as_ref(), contents)?;
+        Ok(())
+    }
+
+    /// Fail if `image_id` differs from the locked ID or, when `network` is
+    /// given, from the ID recorded for that network
+    pub fn check(&self, image_id: &Digest, network: Option<&str>) -> Result<()> {
+        let computed = format_image_id(image_id);
+
+        let locked = self
+            .image_id
+            .as_deref()
+            .ok_or_else(|| anyhow!("Lockfile has no image ID; run `image-id --update` on a reproducible build"))?;
+        if !locked.eq_ignore_ascii_case(&computed) {
+            return Err(anyhow!("Image ID {} does not match lockfile {}", computed, locked));
+        }
+
+        if let Some(network) = network {
+            let recorded = self
+                .networks
+                .get(network)
+                .ok_or_else(|| anyhow!("Network {} is not in the lockfile", network))?
+                .as_deref()
+                .ok_or_else(|| anyhow!("No image ID recorded for network {}", network))?;
+            if !recorded.eq_ignore_ascii_case(&computed) {
+                return Err(anyhow!(
+                    "Image ID {} does not match {} deployment {}",
+                    computed,
+                    network,
+                    recorded
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// `0x`-prefixed hex, the way the ID is passed to the on-chain verifier
+pub fn format_image_id(image_id: &Digest) -> String {
+    format!("0x{}", hex::encode(image_id.as_bytes()))
+}
+
+/// Recompute the image ID from the embedded ELF (or `--elf`, e.g. the output
+/// of a reproducible build) and check it against the lockfile
+#[derive(Debug, Args)]
+pub struct ImageIdArgs {
+    /// Guest ELF to hash instead of the one embedded at build time
+    #[arg(long, value_name = "PATH")]
+    pub elf: Option<PathBuf>,
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_LOCKFILE)]
+    pub lockfile: PathBuf,
+    /// Also compare with the ID recorded for this network
+    #[arg(long)]
+    pub network: Option<String>,
+    /// Write the computed ID to the lockfile instead of checking it
+    #[arg(long)]
+    pub update: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageIdReport {
+    pub image_id: String,
+    pub lockfile: PathBuf,
+    pub network: Option<String>,
+    pub updated: bool,
+}
+
+/// A mismatch is reported as `CommandError::Rejected`
+pub fn run(args: &ImageIdArgs) -> std::result::Result<ImageIdReport, CommandError> {
+    let image_id = match &args.elf {
+        Some(path) => compute_image_id(&std::fs::read(path)?)?,
+        None => Digest::from(SOCIAL_VERIFIER_ID),
+    };
+    let report = ImageIdReport {
+        image_id: format_image_id(&image_id),
+        lockfile: args.lockfile.clone(),
+        network: args.network.clone(),
+        updated: args.update,
+    };
+
+    let mut lock = ImageIdLock::load(&args.lockfile)?;
+    if args.update {
+        lock.image_id = Some(report.image_id.clone());
+        if let Some(network) = &args.network {
+            lock.networks.insert(network.clone(), Some(report.image_id.clone()));
+        }
+        lock.save(&args.lockfile)?;
+        return Ok(report);
+    }
+
+    lock.check(&image_id, args.network.as_deref()).map_err(CommandError::Rejected)?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock(image_id: Option<&str>, sepolia: Option<&str>) -> ImageIdLock {
+        ImageIdLock {
+            guest: "social-verifier".to_string(),
+            image_id: image_id.map(str::to_string),
+            networks: BTreeMap::from([("sepolia".to_string(), sepolia.map(str::to_string))]),
+        }
+    }
+
+    #[test]
+    fn test_lockfile_check() {
+        let image_id = Digest::from([7u32; 8]);
+        let current = format_image_id(&image_id);
+        let previous = format_image_id(&Digest::from([8u32; 8]));
+
+        assert!(lock(Some(&current), Some(&current)).check(&image_id, Some("sepolia")).is_ok());
+        assert!(lock(Some(&current.to_uppercase().replace("0X", "0x")), None).check(&image_id, None).is_ok());
+
+        // Rebuild changed the ID
+        assert!(lock(Some(&previous), None).check(&image_id, None).is_err());
+
+        // Lockfile updated but the deployed verifier still pins the old ID
+        assert!(lock(Some(&current), Some(&previous)).check(&image_id, Some("sepolia")).is_err());
+
+        // Nothing pinned yet
+        assert!(lock(None, None).check(&image_id, None).is_err());
+        assert!(lock(Some(&current), None).check(&image_id, Some("sepolia")).is_err());
+        assert!(lock(Some(&current), None).check(&image_id, Some("mainnet")).is_err());
+    }
+
+    #[test]
+    fn test_committed_lockfile_parses() {
+        let lock = ImageIdLock::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../../deployments/image-id.lock")).unwrap();
+        assert_eq!(lock.guest, "social-verifier");
+        assert!(lock.networks.contains_key("sepolia"));
+    }
+}
@@ -1,292 +1,718 @@
 // RISC Zero Host Program for Social Account Verification
 // This program runs on the host and coordinates with the guest program
 
-use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
+use risc0_zkvm::{sha::Digest, Receipt};
 use serde::{Deserialize, Serialize};
-use std::env;
+use social_verifier_methods::SOCIAL_VERIFIER_ID;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
 use anyhow::Result;
+use clap::Parser;
+use std::process::ExitCode;
+
+mod aggregation;
+mod audit;
+mod bundle;
+mod cli;
+mod config;
+mod envelope;
+mod image_id;
+mod oracle;
+mod policy;
+mod policy_source;
+mod preflight;
+mod prover;
+mod relayer;
+mod signer;
+mod sponsorship;
+mod store;
+mod web_service;
+
+pub use aggregation::{AggregatedProof, Aggregator};
+pub use bundle::BundleProofResult;
+pub use config::HostConfig;
+pub use envelope::{ProofEnvelope, ReceiptKind, SealEncoding};
+pub use oracle::{
+    token_delivery_digest, Oracle, OracleRequest, OracleRequestStatus, PollReport, TokenDelivery, TokenDeliveryError,
+    TokenSource,
+};
+pub use policy::{PlatformPolicy, RegistryPolicies};
+pub use policy_source::{PolicyCache, PolicySource, RegistryReader};
+pub use preflight::{CycleBudget, PreflightReport};
+pub use prover::{ProverBackend, ProverKind, RemoteProver};
+pub use relayer::{RelayJob, RelayStatus, RelayStep, Relayer};
+pub use sponsorship::{
+    set_account_policy_calldata, SponsorshipDecision, SponsorshipPolicy, SponsorshipTier, SponsorshipTiers,
+};
+pub use store::{ReceiptBundle, ReceiptStore, RetentionPolicy, StoredProof};
+pub use signer::VerifierSigner;
+pub use web_service::{
+    SponsorshipRequest, SponsorshipSimulation, VerificationRequest, VerificationResponse, VerificationServer,
+};
+
+pub use risc0_social_verifier::{
+    link_leaf, merkle_proof, oauth_token_hash, policy_hash, verify_merkle_proof, AggregationInput, AggregationOutput,
+    IdentityBundleInput, IdentityBundleOutput, PlatformRequirements, PolicyOutcome, ProfileSnapshot, ReputationTier,
+    RiskModel, SocialPlatform, VerificationInput, VerificationOutput, VerificationType,
+};
+
+/// Parse a platform name as used on the command line and in the web API
+pub fn parse_platform(name: &str) -> Option<SocialPlatform> {
+    match name.to_lowercase().as_str() {
+        "twitter" => Some(SocialPlatform::Twitter),
+        "discord" => Some(SocialPlatform::Discord),
+        "github" => Some(SocialPlatform::Github),
+        "telegram" => Some(SocialPlatform::Telegram),
+        "linkedin" => Some(SocialPlatform::LinkedIn),
+        _ => None,
+    }
+}
 
-// Include the guest binary
-const GUEST_BINARY: &[u8] = include_bytes!("../../guest/target/riscv32im-risc0-zkvm-elf/release/social-verifier-guest");
+/// The deployment a proof is bound to. The guest commits `chain_id` and
+/// `verifying_contract` to the journal, so a proof produced for one
+/// deployment is rejected by every other one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentDomain {
+    pub chain_id: u64,
+    pub verifying_contract: String,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum SocialPlatform {
-    Twitter,
-    Discord,
-    Github,
-    Telegram,
-    LinkedIn,
+impl DeploymentDomain {
+    pub fn new(chain_id: u64, verifying_contract: impl Into<String>) -> Self {
+        Self {
+            chain_id,
+            verifying_contract: verifying_contract.into(),
+        }
+    }
+
+    /// Take the chain ID from a file in `deployments/` (e.g. `sepolia-core.json`)
+    pub fn from_deployment_file(path: impl AsRef<Path>, verifying_contract: impl Into<String>) -> Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct DeploymentFile {
+            chain_id: u64,
+        }
+
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        let deployment: DeploymentFile = serde_json::from_str(&contents)?;
+        Ok(Self::new(deployment.chain_id, verifying_contract))
+    }
+
+    /// Reject journals committed for a different chain or contract
+    pub fn check(&self, output: &VerificationOutput) -> std::result::Result<(), ProofVerificationError> {
+        self.check_binding(output.chain_id, &output.verifying_contract)
+    }
+
+    /// `check` for any journal that commits a chain and contract
+    pub fn check_binding(&self, chain_id: u64, verifying_contract: &str) -> std::result::Result<(), ProofVerificationError> {
+        if chain_id != self.chain_id {
+            return Err(ProofVerificationError::ChainMismatch {
+                expected: self.chain_id,
+                actual: chain_id,
+            });
+        }
+        if !verifying_contract.eq_ignore_ascii_case(&self.verifying_contract) {
+            return Err(ProofVerificationError::ContractMismatch {
+                expected: self.verifying_contract.clone(),
+                actual: verifying_contract.to_string(),
+            });
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VerificationInput {
-    pub platform: SocialPlatform,
-    pub oauth_token: String,
-    pub wallet_address: String,
-    pub timestamp: u64,
+/// How long a proof stays usable, mirroring `RiscZeroSocialVerifier.proofValidityPeriod`
+/// and `SocialAccountRegistry.verificationValidityPeriod`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryPolicy {
+    /// Seconds between proof creation and `expires_at`
+    pub proof_validity_period: u64,
+    /// Seconds a successful verification counts before re-verification is needed
+    pub verification_validity_period: u64,
+    /// Tolerated clock skew for journals timestamped in the future
+    pub max_clock_skew: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VerificationOutput {
-    pub social_account_hash: [u8; 32],
+impl Default for ExpiryPolicy {
+    fn default() -> Self {
+        Self {
+            proof_validity_period: 60 * 60,
+            verification_validity_period: 30 * 24 * 60 * 60,
+            max_clock_skew: 5 * 60,
+        }
+    }
+}
+
+impl ExpiryPolicy {
+    pub fn expires_at(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_add(self.proof_validity_period)
+    }
+
+    /// Last second a verification proven at `timestamp` still counts, as
+    /// `SocialAccountRegistry._isVerificationValid` has it
+    pub fn verification_valid_until(&self, timestamp: u64) -> u64 {
+        timestamp.saturating_add(self.verification_validity_period)
+    }
+}
+
+/// What the caller expects a proof to say, checked by `verify_with_policy`
+#[derive(Debug, Clone)]
+pub struct ProofExpectations {
     pub wallet_address: String,
-    pub platform: SocialPlatform,
-    pub account_age: u64,
-    pub follower_count: u64,
-    pub timestamp: u64,
-    pub social_account_id: String,
-    pub verification_success: bool,
+    pub nonce: u64,
+}
+
+impl ProofExpectations {
+    pub fn new(wallet_address: impl Into<String>, nonce: u64) -> Self {
+        Self {
+            wallet_address: wallet_address.into(),
+            nonce,
+        }
+    }
+
+    /// Check freshness, nonce and wallet of a decoded journal at time `now`
+    pub fn check(
+        &self,
+        output: &VerificationOutput,
+        policy: &ExpiryPolicy,
+        now: u64,
+    ) -> std::result::Result<(), ProofVerificationError> {
+        if output.timestamp > now.saturating_add(policy.max_clock_skew) {
+            return Err(ProofVerificationError::TimestampInFuture(output.timestamp));
+        }
+        if output.expires_at > policy.expires_at(output.timestamp) {
+            return Err(ProofVerificationError::ExpiryTooLong {
+                expires_at: output.expires_at,
+                max_validity: policy.proof_validity_period,
+            });
+        }
+        if now >= output.expires_at {
+            return Err(ProofVerificationError::Expired(output.expires_at));
+        }
+        if output.nonce != self.nonce {
+            return Err(ProofVerificationError::NonceMismatch {
+                expected: self.nonce,
+                actual: output.nonce,
+            });
+        }
+        if !output.wallet_address.eq_ignore_ascii_case(&self.wallet_address) {
+            return Err(ProofVerificationError::WalletMismatch {
+                expected: self.wallet_address.clone(),
+                actual: output.wallet_address.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Why a receipt was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofVerificationError {
+    /// Envelope does not hold a decodable receipt
+    MalformedReceipt(String),
+    /// Seal does not verify against any accepted image ID
+    UnknownImageId,
+    /// Journal is not a `VerificationOutput`
+    MalformedJournal(String),
+    /// Guest ran but the social account did not verify
+    VerificationFailed,
+    /// Platform is not enabled on this service
+    PlatformDisabled(SocialPlatform),
+    /// Account verified but does not meet the platform policy
+    PolicyNotMet(PolicyOutcome),
+    /// Journal claims a different image ID than the one that proved it
+    SelfImageIdMismatch,
+    ChainMismatch { expected: u64, actual: u64 },
+    ContractMismatch { expected: String, actual: String },
+    TimestampInFuture(u64),
+    ExpiryTooLong { expires_at: u64, max_validity: u64 },
+    Expired(u64),
+    NonceMismatch { expected: u64, actual: u64 },
+    WalletMismatch { expected: String, actual: String },
+}
+
+impl fmt::Display for ProofVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedReceipt(e) => write!(f, "Malformed receipt: {}", e),
+            Self::UnknownImageId => write!(f, "Receipt does not match any accepted image ID"),
+            Self::MalformedJournal(e) => write!(f, "Malformed journal: {}", e),
+            Self::VerificationFailed => write!(f, "Social account verification failed"),
+            Self::PlatformDisabled(platform) => write!(f, "Platform {:?} is disabled", platform),
+            Self::PolicyNotMet(outcome) => write!(f, "Platform policy not met: {:?}", outcome),
+            Self::SelfImageIdMismatch => write!(f, "Journal image ID does not match the receipt"),
+            Self::ChainMismatch { expected, actual } => {
+                write!(f, "Proof bound to chain {}, expected {}", actual, expected)
+            }
+            Self::ContractMismatch { expected, actual } => {
+                write!(f, "Proof bound to contract {}, expected {}", actual, expected)
+            }
+            Self::TimestampInFuture(timestamp) => {
+                write!(f, "Proof timestamp {} is in the future", timestamp)
+            }
+            Self::ExpiryTooLong { expires_at, max_validity } => write!(
+                f,
+                "Proof expiry {} exceeds the {}s validity period",
+                expires_at, max_validity
+            ),
+            Self::Expired(expires_at) => write!(f, "Proof expired at {}", expires_at),
+            Self::NonceMismatch { expected, actual } => {
+                write!(f, "Proof nonce {}, expected {}", actual, expected)
+            }
+            Self::WalletMismatch { expected, actual } => {
+                write!(f, "Proof issued for wallet {}, expected {}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProofVerificationError {}
+
+/// Image IDs and platforms a verifier accepts. Keeping more than one image ID
+/// lets receipts from the previous guest keep verifying during an upgrade.
+#[derive(Debug, Clone)]
+pub struct ReceiptVerifier {
+    pub accepted_image_ids: Vec<Digest>,
+    pub enabled_platforms: Vec<SocialPlatform>,
+}
+
+impl ReceiptVerifier {
+    pub fn new(accepted_image_ids: Vec<Digest>) -> Self {
+        Self {
+            accepted_image_ids,
+            enabled_platforms: vec![
+                SocialPlatform::Twitter,
+                SocialPlatform::Discord,
+                SocialPlatform::Github,
+            ],
+        }
+    }
+
+    pub fn with_enabled_platforms(mut self, enabled_platforms: Vec<SocialPlatform>) -> Self {
+        self.enabled_platforms = enabled_platforms;
+        self
+    }
+
+    /// Verify the seal against the accepted image IDs and decode the journal
+    pub fn verify(&self, receipt: &Receipt) -> std::result::Result<VerificationOutput, ProofVerificationError> {
+        let image_id = self
+            .accepted_image_ids
+            .iter()
+            .find(|image_id| receipt.verify(**image_id).is_ok())
+            .ok_or(ProofVerificationError::UnknownImageId)?;
+
+        let verification_output: VerificationOutput = receipt
+            .journal
+            .decode()
+            .map_err(|e| ProofVerificationError::MalformedJournal(e.to_string()))?;
+        // A composed previous receipt was only checked against the committed ID
+        if Digest::from(verification_output.self_image_id) != *image_id {
+            return Err(ProofVerificationError::SelfImageIdMismatch);
+        }
+        self.check_journal(&verification_output)?;
+        Ok(verification_output)
+    }
+
+    /// Semantic checks on a decoded journal
+    pub fn check_journal(&self, output: &VerificationOutput) -> std::result::Result<(), ProofVerificationError> {
+        if !output.verification_success {
+            return Err(ProofVerificationError::VerificationFailed);
+        }
+        if !self.enabled_platforms.contains(&output.platform) {
+            return Err(ProofVerificationError::PlatformDisabled(output.platform));
+        }
+        if output.policy_outcome != PolicyOutcome::Passed {
+            return Err(ProofVerificationError::PolicyNotMet(output.policy_outcome));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProofResult {
     pub verification_output: VerificationOutput,
-    pub receipt: Vec<u8>, // Serialized receipt
+    pub receipt: ProofEnvelope,
     pub proof_hash: [u8; 32],
+    pub prover: ProverKind,
+    /// Snapshot behind `profile_commitment`, the input to the next re-verification
+    pub profile: Option<ProfileSnapshot>,
 }
 
 pub struct SocialVerificationService {
-    prover: risc0_zkvm::Prover,
+    prover: ProverBackend,
+    domain: DeploymentDomain,
+    expiry_policy: ExpiryPolicy,
+    receipt_verifier: ReceiptVerifier,
+    policies: Arc<PolicyCache>,
+    cycle_budget: Option<CycleBudget>,
+    store: Option<Arc<ReceiptStore>>,
+    risk_model: RiskModel,
+    sponsorship_tiers: SponsorshipTiers,
 }
 
 impl SocialVerificationService {
-    pub fn new() -> Self {
+    /// Create a service that accepts receipts from the embedded guest only
+    pub fn new(domain: DeploymentDomain) -> Self {
         Self {
-            prover: default_prover(),
+            prover: ProverBackend::Local,
+            domain,
+            expiry_policy: ExpiryPolicy::default(),
+            receipt_verifier: ReceiptVerifier::new(vec![Digest::from(SOCIAL_VERIFIER_ID)]),
+            policies: Arc::new(PolicyCache::fixed(RegistryPolicies::registry_defaults())),
+            cycle_budget: None,
+            store: None,
+            risk_model: RiskModel::default(),
+            sponsorship_tiers: SponsorshipTiers::default(),
         }
     }
 
-    /// Verify a social account and generate a ZK proof
-    pub async fn verify_social_account(
+    /// Keep every generated proof in `store`
+    pub fn with_store(mut self, store: Arc<ReceiptStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Requirements the guest enforces; should match the registry's `platformConfigs`
+    pub fn with_platform_policies(self, platform_policies: RegistryPolicies) -> Self {
+        self.with_policy_cache(Arc::new(PolicyCache::fixed(platform_policies)))
+    }
+
+    /// Look policies up through `policies`, e.g. live from the registry
+    pub fn with_policy_cache(mut self, policies: Arc<PolicyCache>) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    pub fn with_prover(mut self, prover: ProverBackend) -> Self {
+        self.prover = prover;
+        self
+    }
+
+    pub fn with_cycle_budget(mut self, cycle_budget: CycleBudget) -> Self {
+        self.cycle_budget = Some(cycle_budget);
+        self
+    }
+
+    pub fn with_receipt_verifier(mut self, receipt_verifier: ReceiptVerifier) -> Self {
+        self.receipt_verifier = receipt_verifier;
+        self
+    }
+
+    pub fn with_expiry_policy(mut self, expiry_policy: ExpiryPolicy) -> Self {
+        self.expiry_policy = expiry_policy;
+        self
+    }
+
+    /// Sybil-risk model the guest scores accounts with
+    pub fn with_risk_model(mut self, risk_model: RiskModel) -> Self {
+        self.risk_model = risk_model;
+        self
+    }
+
+    /// Paymaster tiers `sponsorship` places wallets in
+    pub fn with_sponsorship_tiers(mut self, sponsorship_tiers: SponsorshipTiers) -> Self {
+        self.sponsorship_tiers = sponsorship_tiers;
+        self
+    }
+
+    /// Build the guest input for a verification bound to this deployment. With
+    /// a store, the wallet's last profile for `platform` makes it a
+    /// re-verification that composes the receipt it came from.
+    pub async fn build_input(
         &self,
         platform: SocialPlatform,
         oauth_token: String,
         wallet_address: String,
-    ) -> Result<ProofResult> {
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs();
+        nonce: u64,
+    ) -> Result<VerificationInput> {
+        let (previous_profile, previous_receipt) = match &self.store {
+            Some(store) => (
+                store.latest_profile(&wallet_address, platform)?,
+                store.latest_profile_receipt(&wallet_address, platform)?,
+            ),
+            None => (None, None),
+        };
+        self.build_reverification_input(
+            platform,
+            oauth_token,
+            wallet_address,
+            nonce,
+            previous_profile,
+            previous_receipt.as_ref(),
+        )
+        .await
+    }
+
+    /// Build the guest input, scoring consistency against `previous_profile`.
+    /// `previous_receipt` is composed if it comes from this guest image; one
+    /// from an older image only contributes its profile.
+    pub async fn build_reverification_input(
+        &self,
+        platform: SocialPlatform,
+        oauth_token: String,
+        wallet_address: String,
+        nonce: u64,
+        previous_profile: Option<ProfileSnapshot>,
+        previous_receipt: Option<&ProofEnvelope>,
+    ) -> Result<VerificationInput> {
+        // Refuse before spending cycles on a proof the verifier would reject
+        if !self.receipt_verifier.enabled_platforms.contains(&platform) {
+            return Err(ProofVerificationError::PlatformDisabled(platform).into());
+        }
+        let policy = self
+            .policies
+            .get(platform)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No platform policy for {:?}", platform))?;
+        if !policy.is_enabled {
+            return Err(ProofVerificationError::PlatformDisabled(platform).into());
+        }
+        let timestamp = unix_now()?;
+
+        let image_id = Digest::from(SOCIAL_VERIFIER_ID);
+        let previous_journal = match (&previous_profile, previous_receipt) {
+            (Some(_), Some(receipt)) if receipt.image_id()? == image_id => Some(receipt.journal.clone()),
+            _ => None,
+        };
 
-        let input = VerificationInput {
+        Ok(VerificationInput {
             platform,
             oauth_token,
             wallet_address,
             timestamp,
+            expires_at: self.expiry_policy.expires_at(timestamp),
+            nonce,
+            expected_account_id: None,
+            previous_profile,
+            previous_journal,
+            self_image_id: SOCIAL_VERIFIER_ID,
+            chain_id: self.domain.chain_id,
+            verifying_contract: self.domain.verifying_contract.clone(),
+            policy: policy.requirements(),
+            risk_model: self.risk_model,
+        })
+    }
+
+    /// Verify a social account and generate a ZK proof
+    pub async fn verify_social_account(
+        &self,
+        platform: SocialPlatform,
+        oauth_token: String,
+        wallet_address: String,
+        nonce: u64,
+    ) -> Result<ProofResult> {
+        let input = self.build_input(platform, oauth_token, wallet_address, nonce).await?;
+        let assumptions = self.assumptions_for(&input)?;
+        self.prove_input(&input, assumptions).await
+    }
+
+    /// Re-verify against an explicit snapshot and, optionally, the receipt it
+    /// came from, e.g. ones kept outside the store
+    pub async fn reverify_social_account(
+        &self,
+        platform: SocialPlatform,
+        oauth_token: String,
+        wallet_address: String,
+        nonce: u64,
+        previous_profile: ProfileSnapshot,
+        previous_receipt: Option<&ProofEnvelope>,
+    ) -> Result<ProofResult> {
+        let input = self
+            .build_reverification_input(
+                platform,
+                oauth_token,
+                wallet_address,
+                nonce,
+                Some(previous_profile),
+                previous_receipt,
+            )
+            .await?;
+        let assumptions = match (&input.previous_journal, previous_receipt) {
+            (Some(_), Some(receipt)) => vec![receipt.to_receipt()?],
+            _ => Vec::new(),
         };
+        self.prove_input(&input, assumptions).await
+    }
 
-        // Create the executor environment
-        let env = ExecutorEnv::builder()
-            .write(&input)?
-            .build()?;
+    /// Receipts the guest composes for `input`: the stored receipt whose
+    /// journal is `previous_journal`
+    pub fn assumptions_for(&self, input: &VerificationInput) -> Result<Vec<Receipt>> {
+        let Some(journal) = &input.previous_journal else {
+            return Ok(Vec::new());
+        };
+        let proof_hash = hex::encode(proof_hash(journal));
+        let receipt = self
+            .store
+            .as_ref()
+            .map(|store| store.receipt(&proof_hash))
+            .transpose()?
+            .flatten()
+            .ok_or_else(|| anyhow::anyhow!("Previous receipt {} is not in the receipt store", proof_hash))?;
+        Ok(vec![receipt.to_receipt()?])
+    }
+
+    async fn prove_input(&self, input: &VerificationInput, assumptions: Vec<Receipt>) -> Result<ProofResult> {
+        // Run the executor first so failures, policy misses and over-budget
+        // inputs are cheap; the registry would revert on a policy miss anyway
+        let preflight = self.preflight_with(input, assumptions.clone())?;
+        let outcome = preflight.verification_output.policy_outcome;
+        if preflight.verification_output.verification_success && outcome != PolicyOutcome::Passed {
+            return Err(ProofVerificationError::PolicyNotMet(outcome).into());
+        }
 
         // Execute the guest program and generate proof
-        let receipt = self.prover.prove(env, GUEST_BINARY)?;
+        let receipt = self.prover.prove(input, &assumptions).await?;
 
         // Extract the verification output from the receipt
         let verification_output: VerificationOutput = receipt.journal.decode()?;
 
+        // The journal only has the commitment; recompute the snapshot natively
+        // and keep it only if it is the one the guest committed to
+        let previous: Option<VerificationOutput> = input
+            .previous_journal
+            .as_ref()
+            .map(|journal| risc0_zkvm::serde::from_slice(journal))
+            .transpose()?;
+        let profile = risc0_social_verifier::verify_social_account_composed(input, previous.as_ref())
+            .1
+            .filter(|profile| profile.commitment() == verification_output.profile_commitment);
+
         // Generate proof hash
         let proof_hash = self.calculate_proof_hash(&receipt);
 
-        Ok(ProofResult {
+        let result = ProofResult {
             verification_output,
-            receipt: bincode::serialize(&receipt)?,
+            receipt: ProofEnvelope::from_receipt(&receipt, Digest::from(SOCIAL_VERIFIER_ID), SealEncoding::Bincode)?,
             proof_hash,
-        })
-    }
+            prover: self.prover.kind(),
+            profile,
+        };
 
-    /// Verify an existing proof
-    pub fn verify_proof(&self, receipt_bytes: &[u8]) -> Result<bool> {
-        let receipt: Receipt = bincode::deserialize(receipt_bytes)?;
-        
-        // Verify the receipt
-        receipt.verify(GUEST_BINARY)?;
-        
-        Ok(true)
-    }
+        if let Some(store) = &self.store {
+            store.put(&result)?;
+        }
 
-    /// Calculate a hash of the proof for on-chain storage
-    fn calculate_proof_hash(&self, receipt: &Receipt) -> [u8; 32] {
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(&receipt.journal.bytes);
-        hasher.finalize().into()
+        Ok(result)
     }
-}
 
-/// Web service endpoints for social verification
-pub mod web_service {
-    use super::*;
-    use serde_json;
-    use std::sync::Arc;
+    /// Verify an existing proof against the accepted image IDs and the
+    /// configured deployment, returning the decoded journal
+    pub fn verify_proof(&self, envelope: &ProofEnvelope) -> std::result::Result<VerificationOutput, ProofVerificationError> {
+        let receipt = envelope
+            .to_receipt()
+            .map_err(|e| ProofVerificationError::MalformedReceipt(e.to_string()))?;
 
-    #[derive(Debug, Serialize, Deserialize)]
-    pub struct VerificationRequest {
-        pub platform: String,
-        pub oauth_token: String,
-        pub wallet_address: String,
-    }
+        let verification_output = self.receipt_verifier.verify(&receipt)?;
+
+        // Make sure the proof was produced for this deployment
+        self.domain.check(&verification_output)?;
 
-    #[derive(Debug, Serialize, Deserialize)]
-    pub struct VerificationResponse {
-        pub success: bool,
-        pub social_account_hash: Option<String>,
-        pub account_age: Option<u64>,
-        pub follower_count: Option<u64>,
-        pub proof_hash: Option<String>,
-        pub receipt: Option<String>, // Base64 encoded
-        pub error: Option<String>,
+        Ok(verification_output)
     }
 
-    pub struct VerificationServer {
-        service: Arc<SocialVerificationService>,
+    /// Verify a proof and check it is fresh and was issued for the expected
+    /// wallet and nonce. Returns the validated journal.
+    pub fn verify_with_policy(
+        &self,
+        envelope: &ProofEnvelope,
+        expectations: &ProofExpectations,
+    ) -> std::result::Result<VerificationOutput, ProofVerificationError> {
+        let verification_output = self.verify_proof(envelope)?;
+        expectations.check(&verification_output, &self.expiry_policy, unix_now_secs())?;
+        Ok(verification_output)
     }
 
-    impl VerificationServer {
-        pub fn new() -> Self {
-            Self {
-                service: Arc::new(SocialVerificationService::new()),
-            }
-        }
+    /// Verify a proof and check it is still fresh, for callers such as `sign`
+    /// that expect no wallet or nonce beyond what the journal commits
+    pub fn verify_unexpired(&self, envelope: &ProofEnvelope) -> std::result::Result<VerificationOutput, ProofVerificationError> {
+        let verification_output = self.verify_proof(envelope)?;
+        ProofExpectations::new(verification_output.wallet_address.as_str(), verification_output.nonce).check(
+            &verification_output,
+            &self.expiry_policy,
+            unix_now_secs(),
+        )?;
+        Ok(verification_output)
+    }
 
-        pub async fn handle_verification_request(
-            &self,
-            request: VerificationRequest,
-        ) -> VerificationResponse {
-            let platform = match request.platform.to_lowercase().as_str() {
-                "twitter" => SocialPlatform::Twitter,
-                "discord" => SocialPlatform::Discord,
-                "github" => SocialPlatform::Github,
-                "telegram" => SocialPlatform::Telegram,
-                "linkedin" => SocialPlatform::LinkedIn,
-                _ => {
-                    return VerificationResponse {
-                        success: false,
-                        social_account_hash: None,
-                        account_age: None,
-                        follower_count: None,
-                        proof_hash: None,
-                        receipt: None,
-                        error: Some("Unsupported platform".to_string()),
-                    };
-                }
-            };
-
-            match self.service.verify_social_account(
-                platform,
-                request.oauth_token,
-                request.wallet_address,
-            ).await {
-                Ok(result) => {
-                    if result.verification_output.verification_success {
-                        VerificationResponse {
-                            success: true,
-                            social_account_hash: Some(hex::encode(result.verification_output.social_account_hash)),
-                            account_age: Some(result.verification_output.account_age),
-                            follower_count: Some(result.verification_output.follower_count),
-                            proof_hash: Some(hex::encode(result.proof_hash)),
-                            receipt: Some(base64::encode(result.receipt)),
-                            error: None,
-                        }
-                    } else {
-                        VerificationResponse {
-                            success: false,
-                            social_account_hash: None,
-                            account_age: None,
-                            follower_count: None,
-                            proof_hash: None,
-                            receipt: None,
-                            error: Some("Social account verification failed".to_string()),
-                        }
-                    }
-                }
-                Err(e) => VerificationResponse {
-                    success: false,
-                    social_account_hash: None,
-                    account_age: None,
-                    follower_count: None,
-                    proof_hash: None,
-                    receipt: None,
-                    error: Some(format!("Verification error: {}", e)),
-                },
-            }
-        }
+    /// Calculate a hash of the proof for on-chain storage
+    fn calculate_proof_hash(&self, receipt: &Receipt) -> [u8; 32] {
+        proof_hash(&receipt.journal.bytes)
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Parse command line arguments
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() < 4 {
-        println!("Usage: {} <platform> <oauth_token> <wallet_address>", args[0]);
-        println!("Platforms: twitter, discord, github, telegram, linkedin");
-        return Ok(());
-    }
-
-    let platform_str = &args[1];
-    let oauth_token = &args[2];
-    let wallet_address = &args[3];
-
-    let platform = match platform_str.to_lowercase().as_str() {
-        "twitter" => SocialPlatform::Twitter,
-        "discord" => SocialPlatform::Discord,
-        "github" => SocialPlatform::Github,
-        "telegram" => SocialPlatform::Telegram,
-        "linkedin" => SocialPlatform::LinkedIn,
-        _ => {
-            println!("Unsupported platform: {}", platform_str);
-            return Ok(());
-        }
-    };
-
-    println!("Starting social account verification...");
-    println!("Platform: {:?}", platform);
-    println!("Wallet Address: {}", wallet_address);
-
-    let service = SocialVerificationService::new();
-    
-    match service.verify_social_account(
-        platform,
-        oauth_token.to_string(),
-        wallet_address.to_string(),
-    ).await {
-        Ok(result) => {
-            println!("\n=== Verification Result ===");
-            println!("Success: {}", result.verification_output.verification_success);
-            println!("Social Account Hash: {}", hex::encode(result.verification_output.social_account_hash));
-            println!("Account Age: {} seconds", result.verification_output.account_age);
-            println!("Follower Count: {}", result.verification_output.follower_count);
-            println!("Proof Hash: {}", hex::encode(result.proof_hash));
-            println!("Receipt Size: {} bytes", result.receipt.len());
-            
-            // Verify the proof
-            match service.verify_proof(&result.receipt) {
-                Ok(valid) => println!("Proof Verification: {}", if valid { "VALID" } else { "INVALID" }),
-                Err(e) => println!("Proof Verification Error: {}", e),
-            }
-        }
-        Err(e) => {
-            println!("Verification failed: {}", e);
-        }
-    }
+/// Proof hash recorded on-chain: SHA-256 of the journal
+pub fn proof_hash(journal: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(journal).into()
+}
+
+fn unix_now() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
 
-    Ok(())
+fn unix_now_secs() -> u64 {
+    // A clock before 1970 makes every proof look expired, which is the safe side
+    unix_now().unwrap_or(u64::MAX)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    cli::run(cli::Cli::parse()).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prover::dev_mode::DevModeGuard;
+
+    const REGISTRY: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+
+    fn sepolia() -> DeploymentDomain {
+        DeploymentDomain::new(11155111, REGISTRY)
+    }
+
+    fn output_for(domain: &DeploymentDomain) -> VerificationOutput {
+        VerificationOutput {
+            social_account_hash: [1u8; 32],
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            platform: SocialPlatform::Twitter,
+            account_age: 0,
+            follower_count: 150,
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            social_account_id: "123456789".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: domain.chain_id,
+            verifying_contract: domain.verifying_contract.clone(),
+            verification_success: true,
+            policy_hash: [0u8; 32],
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score: 0,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
 
     #[tokio::test]
     async fn test_twitter_verification() {
-        let service = SocialVerificationService::new();
+        let _dev_mode = DevModeGuard::disable().await;
+        let service = SocialVerificationService::new(sepolia());
         
         let result = service.verify_social_account(
             SocialPlatform::Twitter,
             "mock_twitter_token_12345".to_string(),
             "0x1234567890123456789012345678901234567890".to_string(),
+            1,
         ).await;
 
         assert!(result.is_ok());
@@ -297,12 +723,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_invalid_token() {
-        let service = SocialVerificationService::new();
+        let _dev_mode = DevModeGuard::disable().await;
+        let service = SocialVerificationService::new(sepolia());
         
         let result = service.verify_social_account(
             SocialPlatform::Twitter,
             "short".to_string(), // Invalid token
             "0x1234567890123456789012345678901234567890".to_string(),
+            1,
         ).await;
 
         assert!(result.is_ok());
@@ -310,6 +738,215 @@ mod tests {
         assert!(!proof_result.verification_output.verification_success);
     }
 
+    #[tokio::test]
+    async fn test_stored_profile_makes_reverification() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(ReceiptStore::open(dir.path()).unwrap());
+        let service = SocialVerificationService::new(sepolia()).with_store(store.clone());
+        let wallet = "0x1234567890123456789012345678901234567890".to_string();
+
+        let first = service
+            .build_input(SocialPlatform::Twitter, "mock_twitter_token_12345".to_string(), wallet.clone(), 1)
+            .await
+            .unwrap();
+        assert_eq!(first.previous_profile, None);
+
+        // What `verify_social_account` stores after proving `first`, a second earlier
+        let (mut verification_output, profile) = risc0_social_verifier::verify_social_account_with_profile(&first);
+        verification_output.timestamp -= 1;
+        let journal: Vec<u8> = risc0_zkvm::serde::to_vec(&verification_output)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        store
+            .put(&ProofResult {
+                verification_output,
+                receipt: ProofEnvelope {
+                    version: envelope::ENVELOPE_VERSION,
+                    kind: ReceiptKind::Succinct,
+                    image_id: format!("0x{}", hex::encode(Digest::from(SOCIAL_VERIFIER_ID))),
+                    journal: journal.clone(),
+                    encoding: SealEncoding::Bincode,
+                    seal: Vec::new(),
+                },
+                proof_hash: proof_hash(&journal),
+                prover: ProverKind::DevMode,
+                profile: profile.clone(),
+            })
+            .unwrap();
+
+        let second = service
+            .build_input(SocialPlatform::Twitter, "mock_twitter_token_67890".to_string(), wallet, 2)
+            .await
+            .unwrap();
+        assert_eq!(second.previous_profile, profile);
+        // The stored receipt is composed and found again by its proof hash;
+        // only decoding the stub seal fails
+        assert_eq!(second.previous_journal, Some(journal.clone()));
+        assert!(service.assumptions_for(&second).is_err_and(|e| !e.to_string().contains("not in the receipt store")));
+
+        let previous: VerificationOutput = risc0_zkvm::serde::from_slice(&journal).unwrap();
+        let (output, _) = risc0_social_verifier::verify_social_account_composed(&second, Some(&previous));
+        assert_eq!(output.verification_type, VerificationType::ReVerification);
+        assert_eq!(output.account_consistency_score, 100);
+        assert_eq!(output.previous_profile_commitment, profile.map(|p| p.commitment()));
+        assert_eq!(output.previous_proof_hash, Some(proof_hash(&journal)));
+    }
+
+    #[test]
+    fn test_deployment_domain_binding() {
+        let domain = sepolia();
+        assert!(domain.check(&output_for(&domain)).is_ok());
+
+        // Address comparison ignores checksum casing
+        let lowercase = DeploymentDomain::new(11155111, REGISTRY.to_lowercase());
+        assert!(domain.check(&output_for(&lowercase)).is_ok());
+
+        // Same contract on another chain is a replay
+        let mainnet = DeploymentDomain::new(1, REGISTRY);
+        assert_eq!(
+            domain.check(&output_for(&mainnet)),
+            Err(ProofVerificationError::ChainMismatch {
+                expected: 11155111,
+                actual: 1,
+            })
+        );
+
+        // Another deployment on the same chain
+        let other = DeploymentDomain::new(11155111, "0x1111222233334444555566667777888899990000");
+        assert!(domain.check(&output_for(&other)).is_err());
+    }
+
+    #[test]
+    fn test_deployment_file_chain_id() {
+        let domain = DeploymentDomain::from_deployment_file(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/../../deployments/sepolia-core.json"),
+            REGISTRY,
+        )
+        .unwrap();
+        assert_eq!(domain, sepolia());
+    }
+
+    #[test]
+    fn test_expiry_policy() {
+        let policy = ExpiryPolicy::default();
+        let domain = sepolia();
+        let output = output_for(&domain);
+        let expectations = ProofExpectations::new(output.wallet_address.clone(), 1);
+
+        // Fresh proof for the right wallet and nonce
+        assert!(expectations.check(&output, &policy, output.timestamp + 60).is_ok());
+
+        // Expired
+        assert_eq!(
+            expectations.check(&output, &policy, output.expires_at),
+            Err(ProofVerificationError::Expired(output.expires_at))
+        );
+
+        // Timestamped beyond the tolerated clock skew
+        assert!(expectations.check(&output, &policy, output.timestamp - 3600).is_err());
+
+        // Expiry further out than the policy allows
+        let mut long_lived = output_for(&domain);
+        long_lived.expires_at = policy.verification_valid_until(long_lived.timestamp);
+        assert!(expectations.check(&long_lived, &policy, long_lived.timestamp + 60).is_err());
+        // The proof is spent within the hour, the verification counts for 30 days
+        assert_eq!(policy.verification_valid_until(output.timestamp), output.timestamp + 30 * 24 * 60 * 60);
+
+        // Wrong nonce or wallet
+        let stale_nonce = ProofExpectations::new(output.wallet_address.clone(), 2);
+        assert!(stale_nonce.check(&output, &policy, output.timestamp + 60).is_err());
+        let other_wallet = ProofExpectations::new("0x1111222233334444555566667777888899990000", 1);
+        assert!(other_wallet.check(&output, &policy, output.timestamp + 60).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_unexpired() {
+        let _dev_mode = DevModeGuard::enable().await;
+        let service = SocialVerificationService::new(sepolia());
+        let envelope = |output: &VerificationOutput| {
+            let image_id = Digest::from(SOCIAL_VERIFIER_ID);
+            let journal: Vec<u8> = risc0_zkvm::serde::to_vec(output).unwrap().iter().flat_map(|w| w.to_le_bytes()).collect();
+            let claim = risc0_zkvm::ReceiptClaim::ok(image_id, journal.clone());
+            let receipt = Receipt::new(risc0_zkvm::InnerReceipt::Fake(risc0_zkvm::FakeReceipt::new(claim)), journal);
+            ProofEnvelope::from_receipt(&receipt, image_id, SealEncoding::Bincode).unwrap()
+        };
+        let fresh = |domain: &DeploymentDomain| {
+            let now = unix_now_secs();
+            VerificationOutput {
+                timestamp: now,
+                expires_at: now + 3600,
+                self_image_id: SOCIAL_VERIFIER_ID,
+                ..output_for(domain)
+            }
+        };
+
+        assert!(service.verify_unexpired(&envelope(&fresh(&sepolia()))).is_ok());
+        let expired = VerificationOutput {
+            self_image_id: SOCIAL_VERIFIER_ID,
+            ..output_for(&sepolia())
+        };
+        assert_eq!(
+            service.verify_unexpired(&envelope(&expired)),
+            Err(ProofVerificationError::Expired(expired.expires_at))
+        );
+        let other_deployment = fresh(&DeploymentDomain::new(1, REGISTRY));
+        assert!(matches!(
+            service.verify_unexpired(&envelope(&other_deployment)),
+            Err(ProofVerificationError::ChainMismatch { .. })
+        ));
+        let disabled = VerificationOutput {
+            platform: SocialPlatform::Telegram,
+            ..fresh(&sepolia())
+        };
+        assert_eq!(
+            service.verify_unexpired(&envelope(&disabled)),
+            Err(ProofVerificationError::PlatformDisabled(SocialPlatform::Telegram))
+        );
+    }
+
+    #[test]
+    fn test_journal_semantics() {
+        let domain = sepolia();
+        let verifier = ReceiptVerifier::new(vec![Digest::ZERO]);
+        assert!(verifier.check_journal(&output_for(&domain)).is_ok());
+
+        let mut failed = output_for(&domain);
+        failed.verification_success = false;
+        assert_eq!(
+            verifier.check_journal(&failed),
+            Err(ProofVerificationError::VerificationFailed)
+        );
+
+        let mut telegram = output_for(&domain);
+        telegram.platform = SocialPlatform::Telegram;
+        assert_eq!(
+            verifier.check_journal(&telegram),
+            Err(ProofVerificationError::PlatformDisabled(SocialPlatform::Telegram))
+        );
+
+        let mut too_young = output_for(&domain);
+        too_young.policy_outcome = PolicyOutcome::AccountTooYoung;
+        assert_eq!(
+            verifier.check_journal(&too_young),
+            Err(ProofVerificationError::PolicyNotMet(PolicyOutcome::AccountTooYoung))
+        );
+
+        // Twitter switched off by the operator
+        let github_only = verifier.with_enabled_platforms(vec![SocialPlatform::Github]);
+        assert_eq!(
+            github_only.check_journal(&output_for(&domain)),
+            Err(ProofVerificationError::PlatformDisabled(SocialPlatform::Twitter))
+        );
+    }
+
+    #[test]
+    fn test_embedded_image_id() {
+        let image_id = risc0_zkvm::compute_image_id(social_verifier_methods::SOCIAL_VERIFIER_ELF).unwrap();
+        assert_eq!(image_id, Digest::from(SOCIAL_VERIFIER_ID));
+    }
+
     #[test]
     fn test_proof_verification() {
         // This would test the proof verification functionality
@@ -0,0 +1,564 @@
+// Oracle mode
+// Watches `RiscZeroSocialVerifier` for `VerificationRequested` logs and
+// fulfils each request: the OAuth token arrives over a side channel, is checked
+// against the request's `oauthTokenHash` (`sha256(token)`), proven and relayed with
+// `submitProof`. Requests and scanned blocks are kept in the receipt store, so
+// a restart neither skips nor repeats a request.
+
+use alloy::dyn_abi::DynSolValue;
+use alloy::primitives::{keccak256, Address, Signature, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::OracleConfig;
+use crate::{
+    oauth_token_hash, proof_hash, unix_now_secs, ProofVerificationError, ReceiptStore, RelayStatus, RelayStep, Relayer,
+    SocialPlatform, SocialVerificationService,
+};
+
+/// `VerificationRequested(bytes32,address,address,uint8,bytes32,uint256)`
+pub fn verification_requested_topic() -> B256 {
+    keccak256("VerificationRequested(bytes32,address,address,uint8,bytes32,uint256)")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleRequestStatus {
+    /// Waiting for its token, or for a transient error to clear
+    Pending,
+    /// `submitProof` confirmed
+    Fulfilled,
+    /// `requestTimeout` passed, or would before a proof could land
+    Expired,
+    /// The token, the account or the contract said no
+    Rejected,
+}
+
+/// A `VerificationRequested` log and what the oracle did with it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OracleRequest {
+    pub request_id: String,
+    pub requester: String,
+    pub wallet_address: String,
+    pub platform: SocialPlatform,
+    pub oauth_token_hash: String,
+    pub requested_at: u64,
+    pub block_number: u64,
+    pub status: OracleRequestStatus,
+    pub proof_hash: Option<String>,
+    pub error: Option<String>,
+    pub updated_at: u64,
+}
+
+/// Where the oracle gets a request's OAuth token, which never goes on-chain
+pub enum TokenSource {
+    /// `<dir>/<requestId>` files dropped by the app, removed once used
+    Directory(PathBuf),
+    /// `GET <url>/<requestId>` on the app backend; 404 means not yet
+    Http {
+        url: String,
+        api_key: Option<String>,
+        client: reqwest::Client,
+    },
+}
+
+impl TokenSource {
+    pub fn http(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self::Http {
+            url: url.into(),
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// The token for `request_id`, or `None` if it has not arrived
+    pub async fn fetch(&self, request_id: &str) -> Result<Option<String>> {
+        let token = match self {
+            Self::Directory(dir) => match std::fs::read_to_string(dir.join(request_file(request_id)?)) {
+                Ok(token) => token,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e.into()),
+            },
+            Self::Http { url, api_key, client } => {
+                let mut request = client.get(format!("{}/0x{}", url.trim_end_matches('/'), request_id));
+                if let Some(api_key) = api_key {
+                    request = request.bearer_auth(api_key);
+                }
+                let response = request.send().await?;
+                if response.status() == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                response.error_for_status()?.text().await?
+            }
+        };
+        let token = token.trim().to_string();
+        Ok(if token.is_empty() { None } else { Some(token) })
+    }
+
+    /// Forget a token once its request is settled
+    pub fn discard(&self, request_id: &str) -> Result<()> {
+        match self {
+            Self::Directory(dir) => match std::fs::remove_file(dir.join(request_file(request_id)?)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            },
+            Self::Http { .. } => Ok(()),
+        }
+    }
+}
+
+fn request_file(request_id: &str) -> Result<String> {
+    // The ID becomes a file name, so only accept what it should look like
+    let request_id = request_id.trim_start_matches("0x").to_lowercase();
+    if request_id.len() != 64 || !request_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("Invalid request ID: {}", request_id));
+    }
+    Ok(format!("0x{}", request_id))
+}
+
+/// Write `token` to `<dir>/<requestId>` for `TokenSource::Directory`, via a
+/// rename so the oracle never reads half a token
+pub fn deliver_token(dir: &Path, request_id: &str, token: &str) -> Result<()> {
+    let file = request_file(request_id)?;
+    std::fs::create_dir_all(dir)?;
+    let partial = dir.join(format!(".{}.partial", file));
+    std::fs::write(&partial, token)?;
+    std::fs::rename(&partial, dir.join(file))?;
+    Ok(())
+}
+
+/// `keccak256(abi.encodePacked(requestId, oauthTokenHash))`, EIP-191 signed by
+/// the requester or the wallet to hand a token to the oracle
+pub fn token_delivery_digest(request_id: B256, oauth_token_hash: B256) -> B256 {
+    keccak256([request_id.as_slice(), oauth_token_hash.as_slice()].concat())
+}
+
+/// Body of `POST /oracle/tokens`: the token a request committed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenDelivery {
+    pub request_id: String,
+    pub oauth_token: String,
+    /// Hex EIP-191 signature over `token_delivery_digest`
+    pub signature: String,
+}
+
+/// Why a token delivery was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenDeliveryError {
+    /// The oracle has not seen this request
+    UnknownRequest(String),
+    /// The request no longer needs a token
+    Settled(OracleRequestStatus),
+    /// `sha256(token)` is not the request's `oauthTokenHash`
+    CommitmentMismatch,
+    InvalidSignature(String),
+    /// Signed by neither the requester nor the wallet
+    UnauthorizedSigner(Address),
+}
+
+impl fmt::Display for TokenDeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownRequest(id) => write!(f, "Unknown verification request: {}", id),
+            Self::Settled(status) => write!(f, "Verification request is already {:?}", status),
+            Self::CommitmentMismatch => write!(f, "OAuth token does not match the request's oauthTokenHash"),
+            Self::InvalidSignature(e) => write!(f, "Invalid delivery signature: {}", e),
+            Self::UnauthorizedSigner(signer) => write!(f, "{} is neither the requester nor the wallet", signer),
+        }
+    }
+}
+
+impl std::error::Error for TokenDeliveryError {}
+
+impl TokenDelivery {
+    /// Accept the delivery only for a pending `request` it matches, signed by
+    /// someone the request names
+    pub fn check(&self, request: Option<&OracleRequest>) -> Result<(), TokenDeliveryError> {
+        let request = request.ok_or_else(|| TokenDeliveryError::UnknownRequest(self.request_id.clone()))?;
+        if request.status != OracleRequestStatus::Pending {
+            return Err(TokenDeliveryError::Settled(request.status));
+        }
+        let commitment = B256::from(oauth_token_hash(&self.oauth_token));
+        if hex::encode(commitment) != request.oauth_token_hash {
+            return Err(TokenDeliveryError::CommitmentMismatch);
+        }
+
+        let request_id: B256 = request
+            .request_id
+            .parse()
+            .map_err(|e| TokenDeliveryError::UnknownRequest(format!("{}: {}", request.request_id, e)))?;
+        let signature = hex::decode(self.signature.trim_start_matches("0x"))
+            .map_err(|e| TokenDeliveryError::InvalidSignature(e.to_string()))
+            .and_then(|bytes| {
+                Signature::try_from(bytes.as_slice()).map_err(|e| TokenDeliveryError::InvalidSignature(e.to_string()))
+            })?;
+        let signer = signature
+            .recover_address_from_msg(token_delivery_digest(request_id, commitment).as_slice())
+            .map_err(|e| TokenDeliveryError::InvalidSignature(e.to_string()))?;
+        let named = [&request.requester, &request.wallet_address];
+        if !named.iter().any(|address| address.parse::<Address>().ok() == Some(signer)) {
+            return Err(TokenDeliveryError::UnauthorizedSigner(signer));
+        }
+        Ok(())
+    }
+}
+
+/// What one `poll` saw and did
+#[derive(Debug, Default, Serialize)]
+pub struct PollReport {
+    /// Blocks scanned, if any were far enough behind the head
+    pub scanned: Option<(u64, u64)>,
+    pub new_requests: usize,
+    /// Every pending request that was worked on, in its new state
+    pub requests: Vec<OracleRequest>,
+}
+
+enum Attempt {
+    Fulfilled { proof_hash: String, note: Option<String> },
+    AwaitingToken,
+    Expired,
+    Rejected(anyhow::Error),
+}
+
+/// Fulfils verification requests with one relayer
+pub struct Oracle {
+    relayer: Relayer,
+    service: Arc<SocialVerificationService>,
+    store: Arc<ReceiptStore>,
+    verifier: Address,
+    tokens: TokenSource,
+    config: OracleConfig,
+}
+
+impl Oracle {
+    pub fn new(relayer: Relayer, tokens: TokenSource, config: OracleConfig) -> Result<Self> {
+        let verifier = *relayer
+            .verifier()
+            .ok_or_else(|| anyhow!("relayer.verifier_contract must be set to watch for requests"))?
+            .address();
+        Ok(Self {
+            service: relayer.service().clone(),
+            store: relayer.store().clone(),
+            relayer,
+            verifier,
+            tokens,
+            config,
+        })
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.config.poll_interval_secs)
+    }
+
+    /// Scan the next range of confirmed blocks for requests, then try every
+    /// pending request
+    pub async fn poll(&self) -> Result<PollReport> {
+        let provider = self.relayer.provider();
+        let contract = self.verifier.to_string();
+        let mut report = PollReport::default();
+
+        // Logs this close to the head could still be reorged away
+        let safe_block = provider.get_block_number().await?.saturating_sub(self.config.confirmations);
+        let from = match self.store.oracle_checkpoint(&contract)? {
+            Some(checkpoint) => checkpoint + 1,
+            None => self.config.start_block,
+        };
+        if from <= safe_block {
+            let to = safe_block.min(from.saturating_add(self.config.max_block_range - 1));
+            let filter = Filter::new()
+                .address(self.verifier)
+                .event_signature(verification_requested_topic())
+                .from_block(from)
+                .to_block(to);
+            for log in provider.get_logs(&filter).await? {
+                if self.store.add_oracle_request(&request_from_log(&log)?)? {
+                    report.new_requests += 1;
+                }
+            }
+            // Only after every request in the range is recorded
+            self.store.set_oracle_checkpoint(&contract, to)?;
+            report.scanned = Some((from, to));
+        }
+
+        let pending = self.store.oracle_requests_with_status(OracleRequestStatus::Pending)?;
+        if !pending.is_empty() {
+            let timeout = self.request_timeout().await?;
+            for request in pending {
+                report.requests.push(self.fulfil(request, timeout).await?);
+            }
+        }
+        Ok(report)
+    }
+
+    async fn fulfil(&self, mut request: OracleRequest, timeout: u64) -> Result<OracleRequest> {
+        match self.attempt(&request, timeout).await {
+            Ok(Attempt::Fulfilled { proof_hash, note }) => {
+                request.status = OracleRequestStatus::Fulfilled;
+                request.proof_hash = Some(proof_hash);
+                request.error = note;
+            }
+            Ok(Attempt::AwaitingToken) => request.error = Some("Waiting for the OAuth token".to_string()),
+            Ok(Attempt::Expired) => {
+                request.status = OracleRequestStatus::Expired;
+                request.error = None;
+            }
+            Ok(Attempt::Rejected(e)) => {
+                request.status = OracleRequestStatus::Rejected;
+                request.error = Some(format!("{:#}", e));
+            }
+            // Stays pending and is tried again on the next poll
+            Err(e) => request.error = Some(format!("{:#}", e)),
+        }
+        if request.status != OracleRequestStatus::Pending {
+            self.tokens.discard(&request.request_id)?;
+        }
+        request.updated_at = unix_now_secs();
+        self.store.put_oracle_request(&request)?;
+        Ok(request)
+    }
+
+    async fn attempt(&self, request: &OracleRequest, timeout: u64) -> Result<Attempt> {
+        // Proving takes a while; only start if the submission can still land
+        let deadline = request.requested_at.saturating_add(timeout);
+        if unix_now_secs().saturating_add(self.config.timeout_margin_secs) > deadline {
+            return Ok(Attempt::Expired);
+        }
+
+        let Some(token) = self.tokens.fetch(&request.request_id).await? else {
+            return Ok(Attempt::AwaitingToken);
+        };
+        let commitment: B256 = request.oauth_token_hash.parse()?;
+        if B256::from(oauth_token_hash(&token)) != commitment {
+            return Ok(Attempt::Rejected(anyhow!("OAuth token does not match the request's oauthTokenHash")));
+        }
+
+        let result = match self
+            .service
+            .verify_social_account(request.platform, token, request.wallet_address.clone(), 0)
+            .await
+        {
+            Ok(result) => result,
+            // Policy misses and disabled platforms will not change on retry
+            Err(e) if e.downcast_ref::<ProofVerificationError>().is_some() => return Ok(Attempt::Rejected(e)),
+            Err(e) => return Err(e),
+        };
+        if !result.verification_output.verification_success {
+            return Ok(Attempt::Rejected(anyhow!("Social account verification failed")));
+        }
+        if unix_now_secs() > deadline {
+            return Ok(Attempt::Expired);
+        }
+
+        let jobs = self.relayer.relay(&result.receipt, Some(request.request_id.parse()?)).await?;
+        let submission = jobs
+            .iter()
+            .find(|job| job.step == RelayStep::SubmitProof)
+            .ok_or_else(|| anyhow!("Relayer skipped submitProof"))?;
+        let error = || submission.error.clone().unwrap_or_default();
+        match submission.status {
+            RelayStatus::Confirmed => Ok(Attempt::Fulfilled {
+                proof_hash: hex::encode(proof_hash(&result.receipt.journal)),
+                // The request is answered even if linking did not go through
+                note: jobs
+                    .iter()
+                    .find(|job| job.status != RelayStatus::Confirmed)
+                    .map(|job| format!("{:?}: {}", job.step, job.error.clone().unwrap_or_default())),
+            }),
+            RelayStatus::Reverted => Ok(Attempt::Rejected(anyhow!("submitProof reverted: {}", error()))),
+            _ => Err(anyhow!("submitProof did not confirm: {}", error())),
+        }
+    }
+
+    /// `requestTimeout()`, in seconds
+    async fn request_timeout(&self) -> Result<u64> {
+        let verifier = self.relayer.verifier().ok_or_else(|| anyhow!("No verifier contract"))?;
+        let values = verifier.function("requestTimeout", &[])?.call().await?;
+        match values.as_slice() {
+            [DynSolValue::Uint(timeout, _)] => {
+                u64::try_from(*timeout).map_err(|_| anyhow!("requestTimeout does not fit in u64: {}", timeout))
+            }
+            _ => Err(anyhow!("Unexpected requestTimeout output: {:?}", values)),
+        }
+    }
+}
+
+/// Decode a `VerificationRequested` log into a new pending request
+pub fn request_from_log(log: &Log) -> Result<OracleRequest> {
+    let data = &log.data().data;
+    let ([_, request_id, requester, wallet], 96) = (log.topics(), data.len()) else {
+        return Err(anyhow!("Not a VerificationRequested log: {:?}", log));
+    };
+    let platform = match U256::from_be_slice(&data[..32]) {
+        index if index <= U256::from(u8::MAX) => platform_from_abi(index.to::<u8>()),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow!("Unknown platform in request {}", request_id))?;
+    let requested_at = u64::try_from(U256::from_be_slice(&data[64..96]))
+        .map_err(|_| anyhow!("requestedAt does not fit in u64 in request {}", request_id))?;
+
+    Ok(OracleRequest {
+        request_id: hex::encode(request_id),
+        requester: Address::from_word(*requester).to_string(),
+        wallet_address: Address::from_word(*wallet).to_string(),
+        platform,
+        oauth_token_hash: hex::encode(&data[32..64]),
+        requested_at,
+        block_number: log
+            .block_number
+            .ok_or_else(|| anyhow!("Request {} is not in a block yet", request_id))?,
+        status: OracleRequestStatus::Pending,
+        proof_hash: None,
+        error: None,
+        updated_at: unix_now_secs(),
+    })
+}
+
+/// Solidity enums are uint8 in the ABI, in declaration order
+fn platform_from_abi(index: u8) -> Option<SocialPlatform> {
+    match index {
+        0 => Some(SocialPlatform::Twitter),
+        1 => Some(SocialPlatform::Discord),
+        2 => Some(SocialPlatform::Github),
+        3 => Some(SocialPlatform::Telegram),
+        4 => Some(SocialPlatform::LinkedIn),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Bytes, LogData};
+    use alloy::signers::local::PrivateKeySigner;
+    use alloy::signers::SignerSync;
+
+    #[test]
+    fn test_request_from_log() {
+        let request_id = B256::repeat_byte(1);
+        let requester = Address::repeat_byte(2);
+        let wallet = Address::repeat_byte(3);
+        let token_hash = oauth_token_hash("mock_twitter_token_12345");
+        let data = [
+            U256::from(2u8).to_be_bytes::<32>().to_vec(),
+            token_hash.to_vec(),
+            U256::from(1_700_000_000u64).to_be_bytes::<32>().to_vec(),
+        ]
+        .concat();
+        let topics = vec![verification_requested_topic(), request_id, requester.into_word(), wallet.into_word()];
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(4),
+                data: LogData::new_unchecked(topics.clone(), Bytes::from(data.clone())),
+            },
+            block_number: Some(42),
+            ..Default::default()
+        };
+
+        let request = request_from_log(&log).unwrap();
+        assert_eq!(request.request_id, hex::encode(request_id));
+        assert_eq!(request.wallet_address, wallet.to_string());
+        assert_eq!(request.platform, SocialPlatform::Github);
+        assert_eq!(request.oauth_token_hash, hex::encode(token_hash));
+        assert_eq!(request.requested_at, 1_700_000_000);
+        assert_eq!(request.block_number, 42);
+        assert_eq!(request.status, OracleRequestStatus::Pending);
+
+        // An unknown platform or a log of another shape is refused
+        let mut unknown = data.clone();
+        unknown[31] = 9;
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(4),
+                data: LogData::new_unchecked(topics[..3].to_vec(), Bytes::from(data)),
+            },
+            block_number: Some(42),
+            ..Default::default()
+        };
+        assert!(request_from_log(&log).is_err());
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(4),
+                data: LogData::new_unchecked(topics, Bytes::from(unknown)),
+            },
+            block_number: Some(42),
+            ..Default::default()
+        };
+        assert!(request_from_log(&log).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_directory_token_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let tokens = TokenSource::Directory(dir.path().to_path_buf());
+        let request_id = hex::encode([5u8; 32]);
+
+        assert_eq!(tokens.fetch(&request_id).await.unwrap(), None);
+        std::fs::write(dir.path().join(format!("0x{}", request_id)), "mock_twitter_token_12345\n").unwrap();
+        assert_eq!(tokens.fetch(&request_id).await.unwrap().as_deref(), Some("mock_twitter_token_12345"));
+        tokens.discard(&request_id).unwrap();
+        assert_eq!(tokens.fetch(&request_id).await.unwrap(), None);
+        // Discarding twice is fine; a path for an ID is not
+        tokens.discard(&request_id).unwrap();
+        assert!(tokens.fetch("../secrets").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_delivery() {
+        let wallet = PrivateKeySigner::random();
+        let stranger = PrivateKeySigner::random();
+        let token = "mock_twitter_token_12345";
+        let request = OracleRequest {
+            request_id: hex::encode([5u8; 32]),
+            requester: Address::repeat_byte(2).to_string(),
+            wallet_address: wallet.address().to_string(),
+            platform: SocialPlatform::Twitter,
+            oauth_token_hash: hex::encode(oauth_token_hash(token)),
+            requested_at: 1_700_000_000,
+            block_number: 42,
+            status: OracleRequestStatus::Pending,
+            proof_hash: None,
+            error: None,
+            updated_at: 1_700_000_000,
+        };
+        let delivery = |signer: &PrivateKeySigner, token: &str| {
+            let digest = token_delivery_digest(B256::repeat_byte(5), oauth_token_hash(token).into());
+            TokenDelivery {
+                request_id: format!("0x{}", request.request_id),
+                oauth_token: token.to_string(),
+                signature: hex::encode(signer.sign_message_sync(digest.as_slice()).unwrap().as_bytes()),
+            }
+        };
+
+        assert_eq!(delivery(&wallet, token).check(Some(&request)), Ok(()));
+        assert_eq!(
+            delivery(&wallet, token).check(None),
+            Err(TokenDeliveryError::UnknownRequest(format!("0x{}", request.request_id)))
+        );
+        assert_eq!(delivery(&wallet, "another_token").check(Some(&request)), Err(TokenDeliveryError::CommitmentMismatch));
+        assert_eq!(
+            delivery(&stranger, token).check(Some(&request)),
+            Err(TokenDeliveryError::UnauthorizedSigner(stranger.address()))
+        );
+        let settled = OracleRequest {
+            status: OracleRequestStatus::Fulfilled,
+            ..request.clone()
+        };
+        assert_eq!(
+            delivery(&wallet, token).check(Some(&settled)),
+            Err(TokenDeliveryError::Settled(OracleRequestStatus::Fulfilled))
+        );
+
+        // Delivered tokens are what the directory source reads
+        let dir = tempfile::tempdir().unwrap();
+        deliver_token(dir.path(), &request.request_id, token).unwrap();
+        let tokens = TokenSource::Directory(dir.path().to_path_buf());
+        assert_eq!(tokens.fetch(&request.request_id).await.unwrap().as_deref(), Some(token));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
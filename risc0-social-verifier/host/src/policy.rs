@@ -0,0 +1,192 @@
+// Registry policy
+// Mirrors `SocialAccountRegistry.PlatformConfig`, so a proof can be checked
+// against the registry's requirements off-chain.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::{parse_platform, policy_hash, PlatformRequirements, PolicyOutcome, SocialPlatform, VerificationOutput};
+
+/// One platform's entry in `SocialAccountRegistry.platformConfigs`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformPolicy {
+    pub is_enabled: bool,
+    /// Seconds
+    pub minimum_account_age: u64,
+    pub minimum_followers: u64,
+    /// Seconds between re-verifications
+    #[serde(default)]
+    pub verification_cooldown: u64,
+    #[serde(default)]
+    pub requires_additional_verification: bool,
+}
+
+impl PlatformPolicy {
+    /// What the guest is asked to enforce
+    pub fn requirements(&self) -> PlatformRequirements {
+        PlatformRequirements {
+            minimum_account_age: self.minimum_account_age,
+            minimum_followers: self.minimum_followers,
+            requires_additional_verification: self.requires_additional_verification,
+        }
+    }
+}
+
+/// Policies keyed by platform, loaded from JSON such as
+/// `{ "twitter": { "isEnabled": true, "minimumAccountAge": 2592000, "minimumFollowers": 10 } }`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryPolicies {
+    pub platforms: BTreeMap<String, PlatformPolicy>,
+}
+
+impl RegistryPolicies {
+    /// The configs `SocialAccountRegistry._initializePlatformConfigs` deploys with
+    pub fn registry_defaults() -> Self {
+        const DAY: u64 = 24 * 60 * 60;
+        let policy = |minimum_account_age_days: u64, minimum_followers, cooldown_days: u64, additional| PlatformPolicy {
+            is_enabled: true,
+            minimum_account_age: minimum_account_age_days * DAY,
+            minimum_followers,
+            verification_cooldown: cooldown_days * DAY,
+            requires_additional_verification: additional,
+        };
+
+        let mut platforms = BTreeMap::new();
+        platforms.insert("twitter".to_string(), policy(30, 10, 7, false));
+        platforms.insert("discord".to_string(), policy(14, 0, 7, false));
+        platforms.insert("github".to_string(), policy(90, 5, 14, true));
+        Self { platforms }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        Self::from_json(&contents)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let platforms: BTreeMap<String, PlatformPolicy> = serde_json::from_str(json)?;
+        for name in platforms.keys() {
+            parse_platform(name).ok_or_else(|| anyhow!("Unknown platform in policy: {}", name))?;
+        }
+        Ok(Self { platforms })
+    }
+
+    pub fn get(&self, platform: SocialPlatform) -> Option<&PlatformPolicy> {
+        self.platforms
+            .iter()
+            .find(|(name, _)| parse_platform(name) == Some(platform))
+            .map(|(_, policy)| policy)
+    }
+
+    /// Would the registry accept this verification?
+    pub fn check(&self, output: &VerificationOutput) -> Result<()> {
+        let policy = self
+            .get(output.platform)
+            .ok_or_else(|| anyhow!("No policy for {:?}", output.platform))?;
+
+        if !policy.is_enabled {
+            return Err(anyhow!("{:?} is disabled in the registry", output.platform));
+        }
+        if output.policy_hash != policy_hash(&output.platform, &policy.requirements()) {
+            return Err(anyhow!("Proof was evaluated against a different {:?} policy", output.platform));
+        }
+        if output.policy_outcome != PolicyOutcome::Passed {
+            return Err(anyhow!("Guest policy check failed: {:?}", output.policy_outcome));
+        }
+        if output.account_age < policy.minimum_account_age {
+            return Err(anyhow!(
+                "Account age {}s is below the {}s minimum",
+                output.account_age,
+                policy.minimum_account_age
+            ));
+        }
+        if output.follower_count < policy.minimum_followers {
+            return Err(anyhow!(
+                "{} followers is below the minimum of {}",
+                output.follower_count,
+                policy.minimum_followers
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RiskModel, VerificationType};
+
+    const POLICIES: &str = r#"{
+        "twitter": { "isEnabled": true, "minimumAccountAge": 2592000, "minimumFollowers": 10 },
+        "telegram": { "isEnabled": false, "minimumAccountAge": 2592000, "minimumFollowers": 0 }
+    }"#;
+
+    fn output(platform: SocialPlatform, account_age: u64, follower_count: u64) -> VerificationOutput {
+        let requirements = RegistryPolicies::from_json(POLICIES)
+            .unwrap()
+            .get(platform)
+            .map(PlatformPolicy::requirements)
+            .unwrap_or_default();
+        VerificationOutput {
+            social_account_hash: [1u8; 32],
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            platform,
+            account_age,
+            follower_count,
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            social_account_id: "123456789".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: 11155111,
+            verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            verification_success: true,
+            policy_hash: policy_hash(&platform, &requirements),
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score: 0,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_registry_policy_check() {
+        let policies = RegistryPolicies::from_json(POLICIES).unwrap();
+        let thirty_days = 30 * 24 * 60 * 60;
+
+        assert!(policies.check(&output(SocialPlatform::Twitter, thirty_days, 10)).is_ok());
+        assert!(policies.check(&output(SocialPlatform::Twitter, 24 * 60 * 60, 150)).is_err());
+        assert!(policies.check(&output(SocialPlatform::Twitter, thirty_days, 9)).is_err());
+        assert!(policies.check(&output(SocialPlatform::Telegram, thirty_days, 0)).is_err());
+        assert!(policies.check(&output(SocialPlatform::Github, thirty_days, 100)).is_err());
+    }
+
+    #[test]
+    fn test_committed_policy_must_match() {
+        let policies = RegistryPolicies::from_json(POLICIES).unwrap();
+        let thirty_days = 30 * 24 * 60 * 60;
+
+        // Evaluated against a laxer policy than the registry's
+        let mut lax = output(SocialPlatform::Twitter, thirty_days, 10);
+        lax.policy_hash = policy_hash(&SocialPlatform::Twitter, &PlatformRequirements::default());
+        assert!(policies.check(&lax).is_err());
+
+        let mut failed = output(SocialPlatform::Twitter, thirty_days, 10);
+        failed.policy_outcome = PolicyOutcome::AdditionalVerificationMissing;
+        assert!(policies.check(&failed).is_err());
+    }
+
+    #[test]
+    fn test_unknown_platform_rejected() {
+        assert!(RegistryPolicies::from_json(r#"{ "myspace": { "isEnabled": true, "minimumAccountAge": 0, "minimumFollowers": 0 } }"#).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+// Registry policy sources
+// Platform requirements read live from `SocialAccountRegistry.platformConfigs`
+// over JSON-RPC, or from a JSON snapshot for offline use, behind a TTL cache.
+
+use alloy::contract::{ContractInstance, Interface};
+use alloy::dyn_abi::DynSolValue;
+use alloy::json_abi::JsonAbi;
+use alloy::primitives::{Address, U256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{PlatformPolicy, RegistryPolicies, SocialPlatform};
+
+/// ABI export for the registry, relative to the `risc0-social-verifier` directory
+pub const DEFAULT_REGISTRY_ABI: &str = "../exports/SocialAccountRegistry.json";
+
+/// Shape of the files in `exports/`
+#[derive(Debug, Deserialize)]
+pub struct ContractExport {
+    pub abi: JsonAbi,
+}
+
+impl ContractExport {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| anyhow!("Cannot read ABI {}: {}", path.display(), e))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// Reads platform configs from a deployed `SocialAccountRegistry`
+pub struct RegistryReader {
+    contract: ContractInstance<DynProvider>,
+}
+
+impl RegistryReader {
+    pub fn new(rpc_url: &str, registry: &str, abi_path: impl AsRef<Path>) -> Result<Self> {
+        let abi = ContractExport::load(abi_path)?.abi;
+        let registry: Address = registry.parse().map_err(|e| anyhow!("Invalid registry address {}: {}", registry, e))?;
+        let provider = ProviderBuilder::new()
+            .connect_http(rpc_url.parse().map_err(|e| anyhow!("Invalid RPC URL {}: {}", rpc_url, e))?)
+            .erased();
+        Ok(Self {
+            contract: ContractInstance::new(registry, provider, Interface::new(abi)),
+        })
+    }
+
+    /// `platformConfigs(platform)`
+    pub async fn platform_config(&self, platform: SocialPlatform) -> Result<PlatformPolicy> {
+        let values = self
+            .contract
+            .function("platformConfigs", &[platform_arg(platform)])?
+            .call()
+            .await?;
+        policy_from_values(&values)
+    }
+
+    /// `platformPolicyHash(platform)`, the hash the guest commits
+    pub async fn platform_policy_hash(&self, platform: SocialPlatform) -> Result<[u8; 32]> {
+        let values = self
+            .contract
+            .function("platformPolicyHash", &[platform_arg(platform)])?
+            .call()
+            .await?;
+        match values.as_slice() {
+            [DynSolValue::FixedBytes(hash, 32)] => Ok(hash.0),
+            _ => Err(anyhow!("Unexpected platformPolicyHash output: {:?}", values)),
+        }
+    }
+}
+
+pub(crate) fn platform_arg(platform: SocialPlatform) -> DynSolValue {
+    // Solidity enums are uint8 in the ABI, in declaration order
+    DynSolValue::Uint(U256::from(platform as u8), 8)
+}
+
+/// Decode the flattened `PlatformConfig` struct returned by the public getter
+fn policy_from_values(values: &[DynSolValue]) -> Result<PlatformPolicy> {
+    let to_u64 = |value: &U256, field: &str| {
+        u64::try_from(*value).map_err(|_| anyhow!("{} does not fit in u64: {}", field, value))
+    };
+    match values {
+        [
+            DynSolValue::Bool(is_enabled),
+            DynSolValue::Uint(minimum_account_age, _),
+            DynSolValue::Uint(minimum_followers, _),
+            DynSolValue::Uint(verification_cooldown, _),
+            DynSolValue::Bool(requires_additional_verification),
+        ] => Ok(PlatformPolicy {
+            is_enabled: *is_enabled,
+            minimum_account_age: to_u64(minimum_account_age, "minimumAccountAge")?,
+            minimum_followers: to_u64(minimum_followers, "minimumFollowers")?,
+            verification_cooldown: to_u64(verification_cooldown, "verificationCooldown")?,
+            requires_additional_verification: *requires_additional_verification,
+        }),
+        _ => Err(anyhow!("Unexpected platformConfigs output: {:?}", values)),
+    }
+}
+
+pub enum PolicySource {
+    /// Fixed policies, e.g. the `[platforms]` config table
+    Static(RegistryPolicies),
+    /// A JSON snapshot in the `verify --policy` format, re-read on expiry
+    Snapshot(PathBuf),
+    /// Live reads from the registry
+    Registry(RegistryReader),
+}
+
+impl PolicySource {
+    /// `None` means the source has no policy for `platform`
+    pub async fn fetch(&self, platform: SocialPlatform) -> Result<Option<PlatformPolicy>> {
+        match self {
+            Self::Static(policies) => Ok(policies.get(platform).cloned()),
+            Self::Snapshot(path) => Ok(RegistryPolicies::load(path)?.get(platform).cloned()),
+            Self::Registry(reader) => Ok(Some(reader.platform_config(platform).await?)),
+        }
+    }
+}
+
+/// Caches each platform's policy for `ttl`, so a registry update is picked up
+/// without an RPC round trip per verification
+pub struct PolicyCache {
+    source: PolicySource,
+    ttl: Duration,
+    entries: Mutex<HashMap<SocialPlatform, (Instant, Option<PlatformPolicy>)>>,
+}
+
+impl PolicyCache {
+    pub fn new(source: PolicySource, ttl: Duration) -> Self {
+        Self {
+            source,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A cache over fixed policies; they never go stale
+    pub fn fixed(policies: RegistryPolicies) -> Self {
+        Self::new(PolicySource::Static(policies), Duration::MAX)
+    }
+
+    pub async fn get(&self, platform: SocialPlatform) -> Result<Option<PlatformPolicy>> {
+        if let Some((fetched_at, policy)) = self.lock().get(&platform) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(policy.clone());
+            }
+        }
+
+        let policy = self.source.fetch(platform).await?;
+        self.lock().insert(platform, (Instant::now(), policy.clone()));
+        Ok(policy)
+    }
+
+    /// Drop cached entries, e.g. after a `PlatformConfigUpdated` event
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<SocialPlatform, (Instant, Option<PlatformPolicy>)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy_hash;
+
+    const SNAPSHOT: &str = r#"{ "twitter": { "isEnabled": true, "minimumAccountAge": 2592000, "minimumFollowers": 10 } }"#;
+
+    #[test]
+    fn test_decode_platform_config() {
+        let values = [
+            DynSolValue::Bool(true),
+            DynSolValue::Uint(U256::from(7776000u64), 256),
+            DynSolValue::Uint(U256::from(5u64), 256),
+            DynSolValue::Uint(U256::from(1209600u64), 256),
+            DynSolValue::Bool(true),
+        ];
+        assert_eq!(
+            policy_from_values(&values).unwrap(),
+            RegistryPolicies::registry_defaults().get(SocialPlatform::Github).cloned().unwrap()
+        );
+
+        assert!(policy_from_values(&values[..4]).is_err());
+        let mut overflow = values.clone();
+        overflow[1] = DynSolValue::Uint(U256::MAX, 256);
+        assert!(policy_from_values(&overflow).is_err());
+    }
+
+    #[test]
+    fn test_exported_abi_has_getters() {
+        let abi = ContractExport::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../../exports/SocialAccountRegistry.json"))
+            .unwrap()
+            .abi;
+        assert_eq!(abi.function("platformConfigs").unwrap()[0].outputs.len(), 5);
+        assert!(abi.function("platformPolicyHash").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_cache_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policies.json");
+        std::fs::write(&path, SNAPSHOT).unwrap();
+
+        let cache = PolicyCache::new(PolicySource::Snapshot(path.clone()), Duration::from_secs(3600));
+        assert_eq!(cache.get(SocialPlatform::Twitter).await.unwrap().unwrap().minimum_followers, 10);
+        assert!(cache.get(SocialPlatform::Github).await.unwrap().is_none());
+
+        // Served from the cache until it expires or is invalidated
+        std::fs::write(&path, SNAPSHOT.replace("\"minimumFollowers\": 10", "\"minimumFollowers\": 50")).unwrap();
+        assert_eq!(cache.get(SocialPlatform::Twitter).await.unwrap().unwrap().minimum_followers, 10);
+        cache.invalidate();
+        assert_eq!(cache.get(SocialPlatform::Twitter).await.unwrap().unwrap().minimum_followers, 50);
+
+        let uncached = PolicyCache::new(PolicySource::Snapshot(path.clone()), Duration::ZERO);
+        uncached.get(SocialPlatform::Twitter).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(uncached.get(SocialPlatform::Twitter).await.is_err());
+    }
+
+    /// Start `anvil`, deploy with `script/DeploySocialVerification.s.sol`, then
+    /// run with DEV_CHAIN_RPC_URL and DEV_CHAIN_REGISTRY set.
+    #[tokio::test]
+    #[ignore = "needs a local dev chain with the contracts deployed"]
+    async fn test_registry_reader_against_dev_chain() {
+        let rpc_url = std::env::var("DEV_CHAIN_RPC_URL").unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
+        let registry = std::env::var("DEV_CHAIN_REGISTRY").expect("DEV_CHAIN_REGISTRY");
+        let abi = concat!(env!("CARGO_MANIFEST_DIR"), "/../../exports/SocialAccountRegistry.json");
+        let reader = RegistryReader::new(&rpc_url, &registry, abi).unwrap();
+
+        for platform in [SocialPlatform::Twitter, SocialPlatform::Discord, SocialPlatform::Github] {
+            let policy = reader.platform_config(platform).await.unwrap();
+            assert!(policy.is_enabled);
+            // The guest's policy hash must agree with the registry's
+            assert_eq!(
+                reader.platform_policy_hash(platform).await.unwrap(),
+                policy_hash(&platform, &policy.requirements())
+            );
+        }
+
+        let cache = PolicyCache::new(PolicySource::Registry(reader), Duration::from_secs(60));
+        assert!(cache.get(SocialPlatform::Twitter).await.unwrap().is_some());
+    }
+}
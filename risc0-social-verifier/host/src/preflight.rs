@@ -0,0 +1,133 @@
+// Executor preflight
+// Runs the guest without proving to get the journal and cycle counts cheaply,
+// so bad inputs and cost regressions show up before minutes of proving.
+
+use anyhow::{anyhow, Result};
+use risc0_zkvm::{default_executor, Receipt, SegmentInfo};
+use serde::{Deserialize, Serialize};
+use social_verifier_methods::SOCIAL_VERIFIER_ELF;
+
+use crate::prover::executor_env;
+use crate::{SocialPlatform, SocialVerificationService, VerificationInput, VerificationOutput};
+
+/// Cost of one guest execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub platform: SocialPlatform,
+    /// Cycles proven, including padding of each segment to a power of two
+    pub total_cycles: u64,
+    /// Cycles spent executing guest instructions
+    pub user_cycles: u64,
+    pub segment_count: usize,
+    pub verification_output: VerificationOutput,
+}
+
+impl PreflightReport {
+    pub fn from_segments(
+        platform: SocialPlatform,
+        segments: &[SegmentInfo],
+        verification_output: VerificationOutput,
+    ) -> Self {
+        Self {
+            platform,
+            total_cycles: segments.iter().map(|segment| 1u64 << segment.po2).sum(),
+            user_cycles: segments.iter().map(|segment| segment.cycles as u64).sum(),
+            segment_count: segments.len(),
+            verification_output,
+        }
+    }
+}
+
+/// Upper bound on the cycles a single verification may cost
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CycleBudget {
+    pub max_total_cycles: u64,
+}
+
+impl CycleBudget {
+    pub fn check(&self, report: &PreflightReport) -> Result<()> {
+        if report.total_cycles > self.max_total_cycles {
+            return Err(anyhow!(
+                "{:?} verification needs {} cycles, budget is {}",
+                report.platform,
+                report.total_cycles,
+                self.max_total_cycles
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl SocialVerificationService {
+    /// Execute the guest without proving and report its journal and cost.
+    /// Fails if the configured cycle budget would be exceeded.
+    pub fn preflight(&self, input: &VerificationInput) -> Result<PreflightReport> {
+        self.preflight_with(input, self.assumptions_for(input)?)
+    }
+
+    /// `preflight` with the previous receipt given explicitly
+    pub fn preflight_with(&self, input: &VerificationInput, assumptions: Vec<Receipt>) -> Result<PreflightReport> {
+        let env = executor_env(input, assumptions)?;
+        let session = default_executor().execute(env, SOCIAL_VERIFIER_ELF)?;
+
+        let verification_output: VerificationOutput = session.journal.decode()?;
+        let report = PreflightReport::from_segments(input.platform, &session.segments, verification_output);
+
+        if let Some(budget) = &self.cycle_budget {
+            budget.check(&report)?;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_social_verifier::{PolicyOutcome, RiskModel, VerificationType};
+
+    fn output() -> VerificationOutput {
+        VerificationOutput {
+            social_account_hash: [1u8; 32],
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            platform: SocialPlatform::Github,
+            account_age: 0,
+            follower_count: 25,
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            social_account_id: "12345".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: 11155111,
+            verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            verification_success: true,
+            policy_hash: [0u8; 32],
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score: 0,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_cycle_accounting_and_budget() {
+        let segments = vec![
+            SegmentInfo { po2: 20, cycles: 1_000_000 },
+            SegmentInfo { po2: 16, cycles: 40_000 },
+        ];
+        let report = PreflightReport::from_segments(SocialPlatform::Github, &segments, output());
+
+        assert_eq!(report.segment_count, 2);
+        assert_eq!(report.user_cycles, 1_040_000);
+        assert_eq!(report.total_cycles, (1 << 20) + (1 << 16));
+
+        assert!(CycleBudget { max_total_cycles: 1 << 21 }.check(&report).is_ok());
+        assert!(CycleBudget { max_total_cycles: 1 << 20 }.check(&report).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+// Prover backends
+// Local CPU proving, RISC0_DEV_MODE fake receipts for fast tests, and a client
+// for a remote proving service. The backend used is recorded with each proof.
+
+use anyhow::{anyhow, Result};
+use risc0_zkvm::{default_prover, is_dev_mode, sha::Digest, ExecutorEnv, ProverOpts, Receipt};
+use serde::{Deserialize, Serialize};
+use social_verifier_methods::{AGGREGATOR_ELF, IDENTITY_BUNDLE_ELF, SOCIAL_VERIFIER_ELF, SOCIAL_VERIFIER_ID};
+
+use crate::{AggregationInput, IdentityBundleInput, ProofEnvelope, SealEncoding, VerificationInput};
+
+/// Which backend produced a proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProverKind {
+    Local,
+    DevMode,
+    Remote,
+}
+
+#[derive(Debug, Clone)]
+pub enum ProverBackend {
+    /// Prove on this machine's CPU
+    Local,
+    /// Fake receipts; they only verify while `RISC0_DEV_MODE` is set
+    DevMode,
+    /// Delegate to a remote proving service
+    Remote(RemoteProver),
+}
+
+impl ProverBackend {
+    pub fn kind(&self) -> ProverKind {
+        match self {
+            Self::Local => ProverKind::Local,
+            Self::DevMode => ProverKind::DevMode,
+            Self::Remote(_) => ProverKind::Remote,
+        }
+    }
+
+    /// Prove the guest for `input`. `assumptions` are receipts the guest
+    /// verifies by composition, i.e. the previous verification's receipt.
+    pub async fn prove(&self, input: &VerificationInput, assumptions: &[Receipt]) -> Result<Receipt> {
+        match self {
+            Self::Remote(remote) => remote.prove(input, assumptions).await,
+            _ => {
+                self.check_in_process()?;
+                let opts = ProverOpts::default();
+                prove_in_process(SOCIAL_VERIFIER_ELF, input.clone(), assumptions.to_vec(), opts).await
+            }
+        }
+    }
+
+    /// Prove the identity bundle guest for `input`
+    pub async fn prove_bundle(&self, input: &IdentityBundleInput, assumptions: &[Receipt]) -> Result<Receipt> {
+        match self {
+            // The remote protocol only carries single verifications
+            Self::Remote(_) => Err(anyhow!("The remote prover backend does not support identity bundles")),
+            _ => {
+                self.check_in_process()?;
+                let opts = ProverOpts::default();
+                prove_in_process(IDENTITY_BUNDLE_ELF, input.clone(), assumptions.to_vec(), opts).await
+            }
+        }
+    }
+
+    /// Prove the aggregation guest over the receipts in `assumptions`,
+    /// compressed to a Groth16 receipt for on-chain verification
+    pub async fn prove_aggregation(&self, input: &AggregationInput, assumptions: &[Receipt]) -> Result<Receipt> {
+        match self {
+            Self::Remote(_) => Err(anyhow!("The remote prover backend does not support aggregation")),
+            _ => {
+                self.check_in_process()?;
+                prove_in_process(AGGREGATOR_ELF, input.clone(), assumptions.to_vec(), ProverOpts::groth16()).await
+            }
+        }
+    }
+
+    fn check_in_process(&self) -> Result<()> {
+        match self {
+            // default_prover() silently switches to fake receipts in dev mode
+            Self::Local if is_dev_mode() => Err(anyhow!("RISC0_DEV_MODE is set; use the dev-mode prover backend")),
+            Self::DevMode if !is_dev_mode() => {
+                Err(anyhow!("The dev-mode prover backend requires RISC0_DEV_MODE=1"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn prove_in_process<T>(
+    elf: &'static [u8],
+    input: T,
+    assumptions: Vec<Receipt>,
+    opts: ProverOpts,
+) -> Result<Receipt>
+where
+    T: Serialize + Send + 'static,
+{
+    // Proving is CPU-bound and the prover is not Send, so it gets its own thread
+    tokio::task::spawn_blocking(move || {
+        let env = executor_env(&input, assumptions)?;
+        Ok(default_prover().prove_with_opts(env, elf, &opts)?.receipt)
+    })
+    .await?
+}
+
+/// Guest environment for `input`, with `assumptions` available to `env::verify`
+pub(crate) fn executor_env(input: &impl Serialize, assumptions: Vec<Receipt>) -> Result<ExecutorEnv<'static>> {
+    let mut builder = ExecutorEnv::builder();
+    for receipt in assumptions {
+        builder.add_assumption(receipt);
+    }
+    builder.write(input)?.build()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteProveRequest {
+    /// Hex image ID the client expects the service to prove
+    pub image_id: String,
+    pub input: VerificationInput,
+    /// Receipts the guest composes, in envelope form
+    #[serde(default)]
+    pub assumptions: Vec<ProofEnvelope>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteProveResponse {
+    pub receipt: ProofEnvelope,
+}
+
+/// Client for a remote proving service (`POST {url}/v1/prove`)
+#[derive(Debug, Clone)]
+pub struct RemoteProver {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl RemoteProver {
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+
+    pub async fn prove(&self, input: &VerificationInput, assumptions: &[Receipt]) -> Result<Receipt> {
+        let image_id = Digest::from(SOCIAL_VERIFIER_ID);
+        let request = RemoteProveRequest {
+            image_id: hex::encode(image_id.as_bytes()),
+            input: input.clone(),
+            assumptions: assumptions
+                .iter()
+                .map(|receipt| ProofEnvelope::from_receipt(receipt, image_id, SealEncoding::Bincode))
+                .collect::<Result<_>>()?,
+        };
+
+        let mut builder = self.client.post(format!("{}/v1/prove", self.url)).json(&request);
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let response = builder.send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Remote prover returned {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ));
+        }
+
+        let response: RemoteProveResponse = response.json().await?;
+        response.receipt.to_receipt()
+    }
+}
+
+/// In-process stand-in for the remote proving service. It speaks the same
+/// protocol as the real service and proves with the dev-mode backend.
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server, StatusCode};
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use tokio::sync::oneshot;
+
+    pub struct MockProvingService {
+        addr: SocketAddr,
+        shutdown: Option<oneshot::Sender<()>>,
+    }
+
+    impl MockProvingService {
+        /// Bind to an ephemeral local port and serve in the background
+        pub async fn start() -> Result<Self> {
+            let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+            let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?.serve(make_service);
+            let addr = server.local_addr();
+
+            let (shutdown, stopped) = oneshot::channel::<()>();
+            tokio::spawn(server.with_graceful_shutdown(async {
+                stopped.await.ok();
+            }));
+
+            Ok(Self {
+                addr,
+                shutdown: Some(shutdown),
+            })
+        }
+
+        pub fn url(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+    }
+
+    impl Drop for MockProvingService {
+        fn drop(&mut self) {
+            if let Some(shutdown) = self.shutdown.take() {
+                shutdown.send(()).ok();
+            }
+        }
+    }
+
+    async fn handle(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        if request.method() != Method::POST || request.uri().path() != "/v1/prove" {
+            return Ok(reply(StatusCode::NOT_FOUND, "Not found".to_string()));
+        }
+
+        match prove(request).await {
+            Ok(response) => Ok(reply(StatusCode::OK, response)),
+            Err(e) => Ok(reply(StatusCode::BAD_REQUEST, e.to_string())),
+        }
+    }
+
+    async fn prove(request: Request<Body>) -> Result<String> {
+        let body = hyper::body::to_bytes(request.into_body()).await?;
+        let request: RemoteProveRequest = serde_json::from_slice(&body)?;
+
+        let expected = hex::encode(Digest::from(SOCIAL_VERIFIER_ID).as_bytes());
+        if request.image_id != expected {
+            return Err(anyhow!("Unknown image ID {}", request.image_id));
+        }
+
+        let assumptions = request
+            .assumptions
+            .iter()
+            .map(ProofEnvelope::to_receipt)
+            .collect::<Result<Vec<_>>>()?;
+        let receipt = ProverBackend::DevMode.prove(&request.input, &assumptions).await?;
+        Ok(serde_json::to_string(&RemoteProveResponse {
+            receipt: ProofEnvelope::from_receipt(&receipt, Digest::from(SOCIAL_VERIFIER_ID), SealEncoding::Bincode)?,
+        })?)
+    }
+
+    fn reply(status: StatusCode, body: String) -> Response<Body> {
+        let mut response = Response::new(Body::from(body));
+        *response.status_mut() = status;
+        response
+    }
+}
+
+/// `RISC0_DEV_MODE` is process-wide: it decides which receipts verify and
+/// what `default_prover()` returns. Tests that prove or verify receipts hold
+/// this guard so they never see another test's setting.
+#[cfg(test)]
+pub(crate) mod dev_mode {
+    use std::ffi::OsString;
+    use tokio::sync::{Mutex, MutexGuard};
+
+    static LOCK: Mutex<()> = Mutex::const_new(());
+
+    /// `RISC0_DEV_MODE` set or unset until dropped, then restored
+    pub(crate) struct DevModeGuard {
+        previous: Option<OsString>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl DevModeGuard {
+        pub(crate) async fn enable() -> Self {
+            Self::new(LOCK.lock().await, true)
+        }
+
+        pub(crate) async fn disable() -> Self {
+            Self::new(LOCK.lock().await, false)
+        }
+
+        /// `enable` for tests outside a runtime
+        pub(crate) fn enable_blocking() -> Self {
+            Self::new(LOCK.blocking_lock(), true)
+        }
+
+        fn new(lock: MutexGuard<'static, ()>, enabled: bool) -> Self {
+            let previous = std::env::var_os("RISC0_DEV_MODE");
+            if enabled {
+                std::env::set_var("RISC0_DEV_MODE", "1");
+            } else {
+                std::env::remove_var("RISC0_DEV_MODE");
+            }
+            Self { previous, _lock: lock }
+        }
+    }
+
+    impl Drop for DevModeGuard {
+        fn drop(&mut self) {
+            match &self.previous {
+                Some(value) => std::env::set_var("RISC0_DEV_MODE", value),
+                None => std::env::remove_var("RISC0_DEV_MODE"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dev_mode::DevModeGuard;
+    use super::mock::MockProvingService;
+    use super::*;
+    use risc0_social_verifier::{PlatformRequirements, RiskModel, SocialPlatform};
+
+    fn input() -> VerificationInput {
+        VerificationInput {
+            platform: SocialPlatform::Github,
+            oauth_token: "ghp_github_token_1234567890".to_string(),
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: SOCIAL_VERIFIER_ID,
+            chain_id: 11155111,
+            verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_prover_against_mock_service() {
+        let _dev_mode = DevModeGuard::enable().await;
+
+        let service = MockProvingService::start().await.unwrap();
+        let backend = ProverBackend::Remote(RemoteProver::new(service.url(), Some("test-key".to_string())));
+        assert_eq!(backend.kind(), ProverKind::Remote);
+
+        let receipt = backend.prove(&input(), &[]).await.unwrap();
+        receipt.verify(SOCIAL_VERIFIER_ID).unwrap();
+
+        let output: risc0_social_verifier::VerificationOutput = receipt.journal.decode().unwrap();
+        assert!(output.verification_success);
+        assert_eq!(output.nonce, 1);
+    }
+
+    #[tokio::test]
+    async fn test_reverification_composes_previous_receipt() {
+        let _dev_mode = DevModeGuard::enable().await;
+
+        let first = ProverBackend::DevMode.prove(&input(), &[]).await.unwrap();
+        let reverification = VerificationInput {
+            timestamp: 1640995200 + 86400,
+            expires_at: 1640995200 + 86400 + 3600,
+            nonce: 2,
+            previous_profile: risc0_social_verifier::verify_social_account_with_profile(&input()).1,
+            previous_journal: Some(first.journal.bytes.clone()),
+            ..input()
+        };
+
+        // Without the previous receipt as an assumption the guest cannot prove
+        assert!(ProverBackend::DevMode.prove(&reverification, &[]).await.is_err());
+
+        let second = ProverBackend::DevMode.prove(&reverification, std::slice::from_ref(&first)).await.unwrap();
+        let output: risc0_social_verifier::VerificationOutput = second.journal.decode().unwrap();
+        assert!(output.verification_success);
+        assert_eq!(output.previous_proof_hash, Some(crate::proof_hash(&first.journal.bytes)));
+    }
+}
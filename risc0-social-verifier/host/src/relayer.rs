@@ -0,0 +1,708 @@
+// On-chain relayer
+// Sends verified proofs to `RiscZeroSocialVerifier.submitProof` and then to
+// `SocialAccountRegistry.linkSocialAccount` or `reverifyAccount`. Every step is
+// a job in the receipt store, so a restarted relay picks up a sent transaction
+// instead of sending it twice.
+
+use alloy::contract::{ContractInstance, Interface};
+use alloy::dyn_abi::DynSolValue;
+use alloy::network::{EthereumWallet, TransactionBuilder};
+use alloy::primitives::{Address, Bytes, B256, U256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::transports::TransportError;
+use anyhow::{anyhow, Result};
+use risc0_zkvm::{default_prover, InnerReceipt, ProverOpts};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::aggregation::onchain_seal;
+use crate::config::RelayerConfig;
+use crate::policy_source::{platform_arg, ContractExport};
+use crate::{
+    proof_hash, unix_now_secs, ProofEnvelope, ReceiptStore, SocialVerificationService, VerificationOutput,
+    VerifierSigner,
+};
+
+/// ABI export for the verifier, relative to the `risc0-social-verifier` directory
+pub const DEFAULT_VERIFIER_ABI: &str = "../exports/RiscZeroSocialVerifier.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStep {
+    /// `RiscZeroSocialVerifier.submitProof`, answering a verification request
+    SubmitProof,
+    LinkSocialAccount,
+    ReverifyAccount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayStatus {
+    Pending,
+    /// Broadcast, receipt not seen yet
+    Sent,
+    Confirmed,
+    /// Rejected by the contract, in gas estimation or on-chain
+    Reverted,
+    /// Gave up after `relayer.max_attempts` transient errors
+    Failed,
+}
+
+/// One transaction of a relay, as tracked in the receipt store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelayJob {
+    pub proof_hash: String,
+    pub step: RelayStep,
+    pub status: RelayStatus,
+    pub request_id: Option<String>,
+    /// The mined transaction once there is one, else the latest sent
+    pub tx_hash: Option<String>,
+    /// Every transaction sent under `nonce`, oldest first. A replacement does
+    /// not cancel the original, so any of them may be the one mined
+    pub tx_hashes: Vec<String>,
+    pub nonce: Option<u64>,
+    /// Sends in the latest run
+    pub attempts: u32,
+    pub block_number: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub error: Option<String>,
+    pub updated_at: u64,
+}
+
+impl RelayJob {
+    pub fn new(proof_hash: &str, step: RelayStep, request_id: Option<String>, now: u64) -> Self {
+        Self {
+            proof_hash: proof_hash.trim_start_matches("0x").to_lowercase(),
+            step,
+            status: RelayStatus::Pending,
+            request_id,
+            tx_hash: None,
+            tx_hashes: Vec::new(),
+            nonce: None,
+            attempts: 0,
+            block_number: None,
+            gas_used: None,
+            error: None,
+            updated_at: now,
+        }
+    }
+}
+
+/// Submits proofs from one sender account
+pub struct Relayer {
+    service: Arc<SocialVerificationService>,
+    store: Arc<ReceiptStore>,
+    signer: VerifierSigner,
+    provider: DynProvider,
+    sender: Address,
+    verifier: Option<ContractInstance<DynProvider>>,
+    registry: ContractInstance<DynProvider>,
+    config: RelayerConfig,
+    /// Next nonce to use, read from the node's pending count when unset
+    next_nonce: tokio::sync::Mutex<Option<u64>>,
+}
+
+impl Relayer {
+    /// Relay to the service's registry through `rpc_url`, sending from `sender`
+    pub fn connect(
+        service: Arc<SocialVerificationService>,
+        signer: VerifierSigner,
+        sender: PrivateKeySigner,
+        rpc_url: &str,
+        registry_abi: impl AsRef<Path>,
+        config: RelayerConfig,
+    ) -> Result<Self> {
+        let store = service
+            .store
+            .clone()
+            .ok_or_else(|| anyhow!("Relaying needs storage.receipt_store to track transactions"))?;
+        let sender_address = sender.address();
+        // Nonce and gas are set explicitly; the wallet only signs
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(sender))
+            .connect_http(rpc_url.parse().map_err(|e| anyhow!("Invalid RPC URL {}: {}", rpc_url, e))?)
+            .erased();
+
+        let contract = |address: &str, abi_path: &Path| -> Result<ContractInstance<DynProvider>> {
+            let address: Address = address.parse().map_err(|e| anyhow!("Invalid contract address {}: {}", address, e))?;
+            let abi = ContractExport::load(abi_path)?.abi;
+            Ok(ContractInstance::new(address, provider.clone(), Interface::new(abi)))
+        };
+        let registry = contract(&service.domain.verifying_contract, registry_abi.as_ref())?;
+        let verifier = config
+            .verifier_contract
+            .as_deref()
+            .map(|address| contract(address, &config.verifier_abi))
+            .transpose()?;
+
+        Ok(Self {
+            service,
+            store,
+            signer,
+            provider,
+            sender: sender_address,
+            verifier,
+            registry,
+            config,
+            next_nonce: tokio::sync::Mutex::new(None),
+        })
+    }
+
+    pub fn sender(&self) -> Address {
+        self.sender
+    }
+
+    pub(crate) fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+
+    pub(crate) fn verifier(&self) -> Option<&ContractInstance<DynProvider>> {
+        self.verifier.as_ref()
+    }
+
+    pub(crate) fn service(&self) -> &Arc<SocialVerificationService> {
+        &self.service
+    }
+
+    pub(crate) fn store(&self) -> &Arc<ReceiptStore> {
+        &self.store
+    }
+
+    /// Relay a proof: `submitProof` for `request_id` if given, then link the
+    /// account or re-verify it if it is already linked. Steps confirmed by an
+    /// earlier run are skipped; the jobs are returned in order.
+    pub async fn relay(&self, envelope: &ProofEnvelope, request_id: Option<B256>) -> Result<Vec<RelayJob>> {
+        let output = self.service.verify_proof(envelope)?;
+        if !output.verification_success {
+            return Err(anyhow!("Only successful verifications can be relayed"));
+        }
+        let proof_hash = B256::from(proof_hash(&envelope.journal));
+        let key = hex::encode(proof_hash);
+        let mut jobs = Vec::new();
+
+        if let Some(request_id) = request_id {
+            let verifier = self
+                .verifier
+                .as_ref()
+                .ok_or_else(|| anyhow!("relayer.verifier_contract must be set to answer a verification request"))?;
+            let job = match self.store.relay_job(&key, RelayStep::SubmitProof)? {
+                // Skip reading the request and compressing the receipt again
+                Some(job) if job.status == RelayStatus::Confirmed => job,
+                _ => {
+                    let calldata = self.submit_proof_calldata(verifier, request_id, envelope, &output).await?;
+                    self.run_step(&key, RelayStep::SubmitProof, Some(request_id), *verifier.address(), calldata)
+                        .await?
+                }
+            };
+            let confirmed = job.status == RelayStatus::Confirmed;
+            jobs.push(job);
+            if !confirmed {
+                return Ok(jobs);
+            }
+        }
+
+        let step = self.registry_step(&key, &output).await?;
+        let signature = self.signer.sign_registry_proof(&output, proof_hash)?;
+        let calldata = self
+            .registry
+            .function(step.function(), &[verification_proof(&output, proof_hash, signature)?])?
+            .calldata()
+            .clone();
+        jobs.push(self.run_step(&key, step, None, *self.registry.address(), calldata).await?);
+        Ok(jobs)
+    }
+
+    /// Link or re-verify. A step already sent for this proof is resumed, since
+    /// once it is mined the registry would point the other way.
+    async fn registry_step(&self, proof_hash: &str, output: &VerificationOutput) -> Result<RelayStep> {
+        for step in [RelayStep::LinkSocialAccount, RelayStep::ReverifyAccount] {
+            if let Some(job) = self.store.relay_job(proof_hash, step)? {
+                if matches!(job.status, RelayStatus::Sent | RelayStatus::Confirmed) {
+                    return Ok(step);
+                }
+            }
+        }
+
+        let hash = DynSolValue::FixedBytes(B256::from(output.social_account_hash), 32);
+        let values = self.registry.function("isSocialAccountLinked", &[hash])?.call().await?;
+        match values.as_slice() {
+            [DynSolValue::Bool(true), _] => Ok(RelayStep::ReverifyAccount),
+            [DynSolValue::Bool(false), _] => Ok(RelayStep::LinkSocialAccount),
+            _ => Err(anyhow!("Unexpected isSocialAccountLinked output: {:?}", values)),
+        }
+    }
+
+    async fn submit_proof_calldata(
+        &self,
+        verifier: &ContractInstance<DynProvider>,
+        request_id: B256,
+        envelope: &ProofEnvelope,
+        output: &VerificationOutput,
+    ) -> Result<Bytes> {
+        // The request commits to a token; the journal must commit the same one
+        let values = verifier
+            .function("getVerificationResult", &[DynSolValue::FixedBytes(request_id, 32)])?
+            .call()
+            .await?;
+        match values.as_slice() {
+            [DynSolValue::Bool(_), DynSolValue::Bool(_), DynSolValue::Tuple(data)] => match data.as_slice() {
+                [_, DynSolValue::Address(wallet), .., DynSolValue::FixedBytes(oauth_token_hash, 32)] => {
+                    if *wallet != wallet_address(output)? {
+                        return Err(anyhow!("Request {} is for wallet {}, not {}", request_id, wallet, output.wallet_address));
+                    }
+                    if *oauth_token_hash != B256::from(output.oauth_token_hash) {
+                        return Err(anyhow!(
+                            "Request {} commits to OAuth token hash {}, the proof to {}",
+                            request_id,
+                            oauth_token_hash,
+                            B256::from(output.oauth_token_hash)
+                        ));
+                    }
+                }
+                _ => return Err(anyhow!("Unexpected ProofData in getVerificationResult: {:?}", data)),
+            },
+            _ => return Err(anyhow!("Unexpected getVerificationResult output: {:?}", values)),
+        }
+
+        let envelope = envelope.clone();
+        let seal = tokio::task::spawn_blocking(move || risc_zero_proof(&envelope)).await??;
+        let signature = self.signer.sign_submission(request_id, output)?;
+        let args = [
+            DynSolValue::FixedBytes(request_id, 32),
+            proof_data(output)?,
+            DynSolValue::Bytes(seal),
+            DynSolValue::Bytes(signature),
+        ];
+        Ok(verifier.function("submitProof", &args)?.calldata().clone())
+    }
+
+    /// Drive one step to a verdict, retrying transient errors
+    async fn run_step(
+        &self,
+        proof_hash: &str,
+        step: RelayStep,
+        request_id: Option<B256>,
+        to: Address,
+        calldata: Bytes,
+    ) -> Result<RelayJob> {
+        let mut job = match self.store.relay_job(proof_hash, step)? {
+            Some(job) if job.status == RelayStatus::Confirmed => return Ok(job),
+            Some(job) => RelayJob {
+                attempts: 0,
+                ..job
+            },
+            None => RelayJob::new(proof_hash, step, request_id.map(|id| id.to_string()), unix_now_secs()),
+        };
+
+        while job.attempts < self.config.max_attempts {
+            if job.attempts > 0 {
+                tokio::time::sleep(self.retry_delay(job.attempts)).await;
+            }
+            job.attempts += 1;
+            match self.attempt(&mut job, to, &calldata).await {
+                Ok(receipt) => return self.finish(job, &receipt),
+                Err(e) if is_revert(&e) => {
+                    job.status = RelayStatus::Reverted;
+                    job.error = Some(format!("{:#}", e));
+                    return self.save(job);
+                }
+                Err(e) => {
+                    job.error = Some(format!("{:#}", e));
+                    job = self.save(job)?;
+                }
+            }
+        }
+        job.status = RelayStatus::Failed;
+        self.save(job)
+    }
+
+    /// Send the step's transaction, or find the one an earlier attempt sent,
+    /// and wait for it to confirm
+    async fn attempt(&self, job: &mut RelayJob, to: Address, calldata: &Bytes) -> Result<TransactionReceipt> {
+        // Read before looking for receipts: once the nonce is used, whichever
+        // transaction used it has a receipt
+        let nonce_used = match (job.status, job.nonce) {
+            (RelayStatus::Sent, Some(nonce)) => self.provider.get_transaction_count(self.sender).latest().await? > nonce,
+            _ => false,
+        };
+        if let Some(receipt) = self.sent_receipt(job).await? {
+            return Ok(receipt);
+        }
+
+        let tx = TransactionRequest::default()
+            .with_from(self.sender)
+            .with_to(to)
+            .with_input(calldata.clone());
+        let gas = self.provider.estimate_gas(tx.clone()).await?;
+        let fees = self.provider.estimate_eip1559_fees().await?;
+
+        // A sent transaction that never showed up is replaced under its nonce,
+        // which needs higher fees than the original. A nonce some other
+        // transaction used is given up for a fresh one.
+        let (nonce, fee_percent) = match (job.status, job.nonce) {
+            (RelayStatus::Sent, Some(nonce)) if !nonce_used => (nonce, 125),
+            _ => {
+                job.tx_hashes.clear();
+                (self.take_nonce().await?, 100)
+            }
+        };
+        let tx = tx
+            .with_nonce(nonce)
+            .with_gas_limit(gas.saturating_mul(self.config.gas_multiplier_percent) / 100)
+            .with_max_fee_per_gas(fees.max_fee_per_gas * fee_percent / 100)
+            .with_max_priority_fee_per_gas(fees.max_priority_fee_per_gas * fee_percent / 100);
+
+        let pending = match self.provider.send_transaction(tx).await {
+            Ok(pending) => pending,
+            Err(e) => {
+                // The node may not have counted the nonce; read it again next time
+                *self.next_nonce.lock().await = None;
+                // A replacement is refused once an earlier send under its nonce is mined
+                if let Some(receipt) = self.sent_receipt(job).await? {
+                    return Ok(receipt);
+                }
+                return Err(e.into());
+            }
+        };
+        let tx_hash = pending.tx_hash().to_string();
+        job.status = RelayStatus::Sent;
+        job.tx_hash = Some(tx_hash.clone());
+        job.tx_hashes.push(tx_hash);
+        job.nonce = Some(nonce);
+        *job = self.save(job.clone())?;
+
+        Ok(pending
+            .with_required_confirmations(self.config.confirmations)
+            .with_timeout(Some(Duration::from_secs(self.config.receipt_timeout_secs)))
+            .get_receipt()
+            .await?)
+    }
+
+    /// Receipt of whichever transaction sent under the job's nonce was mined
+    async fn sent_receipt(&self, job: &RelayJob) -> Result<Option<TransactionReceipt>> {
+        if job.status != RelayStatus::Sent {
+            return Ok(None);
+        }
+        for tx_hash in &job.tx_hashes {
+            let tx_hash: B256 = tx_hash.parse().map_err(|e| anyhow!("Invalid transaction hash {}: {}", tx_hash, e))?;
+            if let Some(receipt) = self.provider.get_transaction_receipt(tx_hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn take_nonce(&self) -> Result<u64> {
+        let mut next_nonce = self.next_nonce.lock().await;
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => self.provider.get_transaction_count(self.sender).pending().await?,
+        };
+        *next_nonce = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        Duration::from_millis(self.config.retry_delay_ms.saturating_mul(1 << (attempts - 1).min(16)))
+    }
+
+    fn finish(&self, mut job: RelayJob, receipt: &TransactionReceipt) -> Result<RelayJob> {
+        job.tx_hash = Some(receipt.transaction_hash.to_string());
+        job.block_number = receipt.block_number;
+        job.gas_used = Some(receipt.gas_used);
+        if receipt.status() {
+            job.status = RelayStatus::Confirmed;
+            job.error = None;
+        } else {
+            job.status = RelayStatus::Reverted;
+            job.error = Some("Transaction reverted".to_string());
+        }
+        self.save(job)
+    }
+
+    fn save(&self, mut job: RelayJob) -> Result<RelayJob> {
+        job.updated_at = unix_now_secs();
+        self.store.put_relay_job(&job)?;
+        Ok(job)
+    }
+}
+
+impl RelayStep {
+    fn function(&self) -> &'static str {
+        match self {
+            Self::SubmitProof => "submitProof",
+            Self::LinkSocialAccount => "linkSocialAccount",
+            Self::ReverifyAccount => "reverifyAccount",
+        }
+    }
+}
+
+/// `riscZeroProof` for `submitProof`: the Groth16 seal in the verifier
+/// router's format, compressing the receipt first if needed
+fn risc_zero_proof(envelope: &ProofEnvelope) -> Result<Vec<u8>> {
+    let receipt = envelope.to_receipt()?;
+    let receipt = match receipt.inner {
+        InnerReceipt::Groth16(_) | InnerReceipt::Fake(_) => receipt,
+        _ => default_prover().compress(&ProverOpts::groth16(), &receipt)?,
+    };
+    onchain_seal(&receipt)
+}
+
+/// `RiscZeroSocialVerifier.ProofData`
+pub fn proof_data(output: &VerificationOutput) -> Result<DynSolValue> {
+    Ok(DynSolValue::Tuple(vec![
+        DynSolValue::FixedBytes(B256::from(output.social_account_hash), 32),
+        DynSolValue::Address(wallet_address(output)?),
+        platform_arg(output.platform),
+        DynSolValue::Uint(U256::from(output.account_age), 256),
+        DynSolValue::Uint(U256::from(output.follower_count), 256),
+        DynSolValue::Uint(U256::from(output.timestamp), 256),
+        DynSolValue::String(output.social_account_id.clone()),
+        DynSolValue::FixedBytes(B256::from(output.oauth_token_hash), 32),
+    ]))
+}
+
+/// `SocialAccountRegistry.VerificationProof`
+pub fn verification_proof(output: &VerificationOutput, proof_hash: B256, signature: Vec<u8>) -> Result<DynSolValue> {
+    Ok(DynSolValue::Tuple(vec![
+        DynSolValue::FixedBytes(B256::from(output.social_account_hash), 32),
+        DynSolValue::Address(wallet_address(output)?),
+        platform_arg(output.platform),
+        DynSolValue::Uint(U256::from(output.account_age), 256),
+        DynSolValue::Uint(U256::from(output.follower_count), 256),
+        DynSolValue::Uint(U256::from(output.timestamp), 256),
+        DynSolValue::FixedBytes(proof_hash, 32),
+        DynSolValue::Bytes(signature),
+    ]))
+}
+
+fn wallet_address(output: &VerificationOutput) -> Result<Address> {
+    output
+        .wallet_address
+        .parse()
+        .map_err(|_| anyhow!("Invalid wallet address {}", output.wallet_address))
+}
+
+/// Whether the contract refused the call, so sending it again cannot help.
+/// Nodes answer a reverting `eth_estimateGas` with error code 3 (EIP-1474's
+/// execution error) or with the revert payload in the error's data.
+fn is_revert(e: &anyhow::Error) -> bool {
+    const EXECUTION_ERROR: i64 = 3;
+    e.chain()
+        .filter_map(|cause| cause.downcast_ref::<TransportError>())
+        .filter_map(|e| e.as_error_resp())
+        .any(|payload| payload.code == EXECUTION_ERROR || payload.as_revert_data().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prover::dev_mode::DevModeGuard;
+    use crate::{DeploymentDomain, PolicyOutcome, ProverBackend, RiskModel, SocialPlatform, VerificationType};
+    use alloy::primitives::keccak256;
+
+    const REGISTRY: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+    const REGISTRY_ABI: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../exports/SocialAccountRegistry.json");
+    const VERIFIER_ABI: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../exports/RiscZeroSocialVerifier.json");
+
+    fn output() -> VerificationOutput {
+        VerificationOutput {
+            social_account_hash: [1u8; 32],
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            platform: SocialPlatform::Github,
+            account_age: 90 * 24 * 60 * 60,
+            follower_count: 25,
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            social_account_id: "12345".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: 11155111,
+            verifying_contract: REGISTRY.to_string(),
+            verification_success: true,
+            policy_hash: [0u8; 32],
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score: 0,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_calldata_matches_exported_abis() {
+        let abi = |path: &str| Interface::new(ContractExport::load(path).unwrap().abi);
+        let registry = abi(REGISTRY_ABI);
+        let verifier = abi(VERIFIER_ABI);
+
+        let proof = verification_proof(&output(), B256::repeat_byte(2), vec![3u8; 65]).unwrap();
+        for (function, signature) in [
+            ("linkSocialAccount", "linkSocialAccount((bytes32,address,uint8,uint256,uint256,uint256,bytes32,bytes))"),
+            ("reverifyAccount", "reverifyAccount((bytes32,address,uint8,uint256,uint256,uint256,bytes32,bytes))"),
+        ] {
+            let calldata = registry.encode_input(function, std::slice::from_ref(&proof)).unwrap();
+            assert_eq!(calldata[..4], keccak256(signature)[..4]);
+        }
+
+        let args = [
+            DynSolValue::FixedBytes(B256::repeat_byte(4), 32),
+            proof_data(&output()).unwrap(),
+            DynSolValue::Bytes(vec![6u8; 36]),
+            DynSolValue::Bytes(vec![3u8; 65]),
+        ];
+        let calldata = verifier.encode_input("submitProof", &args).unwrap();
+        assert_eq!(
+            calldata[..4],
+            keccak256("submitProof(bytes32,(bytes32,address,uint8,uint256,uint256,uint256,string,bytes32),bytes,bytes)")[..4]
+        );
+    }
+
+    #[test]
+    fn test_is_revert() {
+        // The payload type comes from the variant, so it need not be named here
+        fn rpc_error<E: serde::de::DeserializeOwned>(variant: fn(E) -> TransportError, json: &str) -> anyhow::Error {
+            variant(serde_json::from_str(json).unwrap()).into()
+        }
+
+        // Error(string) "Already linked", as geth and anvil report it
+        let reverted = rpc_error(
+            TransportError::ErrorResp,
+            r#"{"code": 3, "message": "execution reverted: Already linked",
+                "data": "0x08c379a00000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000e416c7265616479206c696e6b6564000000000000000000000000000000000000"}"#,
+        );
+        assert!(is_revert(&reverted));
+        assert!(is_revert(&reverted.context("Relaying link_social_account")));
+        // Revert data under another code
+        assert!(is_revert(&rpc_error(TransportError::ErrorResp, r#"{"code": -32015, "message": "VM execution error: reverted", "data": "0xdeadbeef"}"#)));
+
+        // Transient, even when the message mentions a revert
+        assert!(!is_revert(&rpc_error(TransportError::ErrorResp, r#"{"code": -32000, "message": "nonce too low"}"#)));
+        assert!(!is_revert(&rpc_error(TransportError::ErrorResp, r#"{"code": -32000, "message": "replacement transaction underpriced, would revert"}"#)));
+        assert!(!is_revert(&anyhow!("Timed out waiting for a receipt; will it revert?")));
+    }
+
+    // Anvil's default accounts 0 (deployer), 1 (verifier key) and 2 (relayer)
+    const DEPLOYER_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const VERIFIER_KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    const RELAYER_KEY: &str = "0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a";
+
+    /// Needs `anvil` and `forge` on PATH:
+    /// `cargo test -- --ignored test_relay_against_anvil`
+    #[tokio::test]
+    #[ignore]
+    async fn test_relay_against_anvil() {
+        let _dev_mode = DevModeGuard::enable().await;
+        let port = 18545;
+        let rpc_url = format!("http://127.0.0.1:{}", port);
+        let mut anvil = std::process::Command::new("anvil")
+            .args(["--port", &port.to_string(), "--silent"])
+            .spawn()
+            .expect("anvil on PATH");
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let deployer: PrivateKeySigner = DEPLOYER_KEY.parse().unwrap();
+        let verifier_key: PrivateKeySigner = VERIFIER_KEY.parse().unwrap();
+        let relayer_key: PrivateKeySigner = RELAYER_KEY.parse().unwrap();
+        let status = std::process::Command::new("forge")
+            .args(["script", "script/DeploySocialVerificationLocal.s.sol", "--rpc-url", &rpc_url, "--broadcast"])
+            .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/../.."))
+            .env("PRIVATE_KEY", DEPLOYER_KEY)
+            .env("VERIFIER_KEY", verifier_key.address().to_string())
+            .env("RELAYER", relayer_key.address().to_string())
+            .status()
+            .expect("forge on PATH");
+        assert!(status.success());
+        // The script deploys the registry, then the verifier, from a fresh account
+        let registry = deployer.address().create(0);
+        let verifier = deployer.address().create(1);
+
+        let store = tempfile::tempdir().unwrap();
+        let service = Arc::new(
+            SocialVerificationService::new(DeploymentDomain::new(31337, registry.to_string()))
+                .with_prover(ProverBackend::DevMode)
+                .with_store(Arc::new(ReceiptStore::open(store.path()).unwrap())),
+        );
+        let wallet = "0x1234567890123456789012345678901234567890";
+        let token = "mock_twitter_token_12345";
+        let result = service
+            .verify_social_account(SocialPlatform::Twitter, token.to_string(), wallet.to_string(), 1)
+            .await
+            .unwrap();
+
+        // Open a request as a user would, committing to the token without revealing
+        // it; its ID hashes the block it lands in
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(deployer.clone()))
+            .connect_http(rpc_url.parse().unwrap())
+            .erased();
+        let abi = Interface::new(ContractExport::load(VERIFIER_ABI).unwrap().abi);
+        let contract = ContractInstance::new(verifier, provider.clone(), abi);
+        let args = [
+            platform_arg(SocialPlatform::Twitter),
+            DynSolValue::FixedBytes(B256::from(crate::oauth_token_hash(token)), 32),
+            DynSolValue::Address(wallet.parse().unwrap()),
+        ];
+        let receipt = contract
+            .function("requestVerificationWithCommitment", &args)
+            .unwrap()
+            .send()
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+        let block_number = receipt.block_number.unwrap();
+        let block = provider.get_block_by_number(block_number.into()).await.unwrap().unwrap();
+        let mut packed = deployer.address().to_vec();
+        packed.push(SocialPlatform::Twitter as u8);
+        packed.extend_from_slice(wallet.parse::<Address>().unwrap().as_slice());
+        packed.extend_from_slice(&U256::from(block.header.timestamp).to_be_bytes::<32>());
+        packed.extend_from_slice(&U256::from(block_number).to_be_bytes::<32>());
+        let request_id = keccak256(packed);
+
+        let config = RelayerConfig {
+            verifier_contract: Some(verifier.to_string()),
+            verifier_abi: VERIFIER_ABI.into(),
+            retry_delay_ms: 100,
+            ..RelayerConfig::default()
+        };
+        let relayer = Relayer::connect(
+            service.clone(),
+            VerifierSigner::from_private_key(VERIFIER_KEY).unwrap(),
+            relayer_key,
+            &rpc_url,
+            REGISTRY_ABI,
+            config,
+        )
+        .unwrap();
+
+        let jobs = relayer.relay(&result.receipt, Some(request_id)).await.unwrap();
+        let steps: Vec<_> = jobs.iter().map(|job| (job.step, job.status)).collect();
+        assert_eq!(
+            steps,
+            vec![
+                (RelayStep::SubmitProof, RelayStatus::Confirmed),
+                (RelayStep::LinkSocialAccount, RelayStatus::Confirmed),
+            ]
+        );
+        let hash = DynSolValue::FixedBytes(B256::from(result.verification_output.social_account_hash), 32);
+        let linked = relayer.registry.function("isSocialAccountLinked", &[hash]).unwrap().call().await.unwrap();
+        assert_eq!(linked[0], DynSolValue::Bool(true));
+
+        // Relaying again finds both steps confirmed and sends nothing
+        assert_eq!(relayer.relay(&result.receipt, Some(request_id)).await.unwrap(), jobs);
+        assert!(relayer.store.relay_jobs_with_status(RelayStatus::Sent).unwrap().is_empty());
+
+        anvil.kill().unwrap();
+        anvil.wait().unwrap();
+    }
+}
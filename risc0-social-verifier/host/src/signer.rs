@@ -0,0 +1,163 @@
+// Verifier signatures
+// `RiscZeroSocialVerifier.submitProof` and `SocialAccountRegistry.linkSocialAccount`
+// both recover an EIP-191 signature over a packed digest of the proof data and
+// compare it with the configured verifier key.
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use crate::VerificationOutput;
+
+/// Signs proof data with the verifier key
+pub struct VerifierSigner {
+    signer: PrivateKeySigner,
+}
+
+impl VerifierSigner {
+    pub fn from_private_key(private_key: &str) -> Result<Self> {
+        Ok(Self {
+            signer: private_key.trim().parse()?,
+        })
+    }
+
+    /// Decrypt a JSON keystore (as written by `cast wallet import`)
+    pub fn from_keystore(path: impl AsRef<Path>, password: &str) -> Result<Self> {
+        Ok(Self {
+            signer: PrivateKeySigner::decrypt_keystore(path, password)?,
+        })
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// Signature for `RiscZeroSocialVerifier.submitProof(requestId, ...)`
+    pub fn sign_submission(&self, request_id: B256, output: &VerificationOutput) -> Result<Vec<u8>> {
+        self.sign_digest(submission_digest(request_id, output)?)
+    }
+
+    /// Signature for `SocialAccountRegistry.VerificationProof`
+    pub fn sign_registry_proof(&self, output: &VerificationOutput, proof_hash: B256) -> Result<Vec<u8>> {
+        self.sign_digest(registry_proof_digest(output, proof_hash)?)
+    }
+
+    fn sign_digest(&self, digest: B256) -> Result<Vec<u8>> {
+        // sign_message applies the "\x19Ethereum Signed Message:\n32" prefix,
+        // matching `toEthSignedMessageHash()` on-chain
+        Ok(self.signer.sign_message_sync(digest.as_slice())?.as_bytes().to_vec())
+    }
+}
+
+/// `keccak256(abi.encodePacked(requestId, socialAccountHash, walletAddress,
+/// uint256(platform), accountAge, followerCount, timestamp))`
+pub fn submission_digest(request_id: B256, output: &VerificationOutput) -> Result<B256> {
+    let mut packed = Vec::with_capacity(32 * 6 + 20);
+    packed.extend_from_slice(request_id.as_slice());
+    pack_proof_data(&mut packed, output)?;
+    Ok(keccak256(packed))
+}
+
+/// `keccak256(abi.encodePacked(socialAccountHash, walletAddress,
+/// uint256(platform), accountAge, followerCount, timestamp, proofHash))`
+pub fn registry_proof_digest(output: &VerificationOutput, proof_hash: B256) -> Result<B256> {
+    let mut packed = Vec::with_capacity(32 * 6 + 20);
+    pack_proof_data(&mut packed, output)?;
+    packed.extend_from_slice(proof_hash.as_slice());
+    Ok(keccak256(packed))
+}
+
+fn pack_proof_data(packed: &mut Vec<u8>, output: &VerificationOutput) -> Result<()> {
+    let wallet: Address = output
+        .wallet_address
+        .parse()
+        .map_err(|_| anyhow!("Invalid wallet address {}", output.wallet_address))?;
+
+    packed.extend_from_slice(&output.social_account_hash);
+    packed.extend_from_slice(wallet.as_slice());
+    // Rust and Solidity declare the platforms in the same order
+    packed.extend_from_slice(&U256::from(output.platform as u8).to_be_bytes::<32>());
+    packed.extend_from_slice(&U256::from(output.account_age).to_be_bytes::<32>());
+    packed.extend_from_slice(&U256::from(output.follower_count).to_be_bytes::<32>());
+    packed.extend_from_slice(&U256::from(output.timestamp).to_be_bytes::<32>());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PolicyOutcome, RiskModel, SocialPlatform, VerificationType};
+    use alloy::primitives::Signature;
+
+    // Anvil's first default account
+    const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn output() -> VerificationOutput {
+        VerificationOutput {
+            social_account_hash: [1u8; 32],
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            platform: SocialPlatform::Github,
+            account_age: 90 * 24 * 60 * 60,
+            follower_count: 25,
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            social_account_id: "12345".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: 11155111,
+            verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            verification_success: true,
+            policy_hash: [0u8; 32],
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score: 0,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_packed_layout() {
+        let output = output();
+        let request_id = B256::repeat_byte(9);
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(request_id.as_slice());
+        expected.extend_from_slice(&[1u8; 32]);
+        expected.extend_from_slice(&hex::decode("742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8").unwrap());
+        for value in [2u64, output.account_age, 25, 1640995200] {
+            expected.extend_from_slice(&U256::from(value).to_be_bytes::<32>());
+        }
+
+        assert_eq!(submission_digest(request_id, &output).unwrap(), keccak256(expected));
+    }
+
+    #[test]
+    fn test_signature_recovers_verifier() {
+        let signer = VerifierSigner::from_private_key(ANVIL_KEY).unwrap();
+        let output = output();
+        let request_id = B256::repeat_byte(9);
+
+        let signature = signer.sign_submission(request_id, &output).unwrap();
+        assert_eq!(signature.len(), 65);
+
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        let digest = submission_digest(request_id, &output).unwrap();
+        let recovered = signature.recover_address_from_msg(digest.as_slice()).unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+
+    #[test]
+    fn test_invalid_wallet_rejected() {
+        let mut output = output();
+        output.wallet_address = "not-an-address".to_string();
+        assert!(registry_proof_digest(&output, B256::ZERO).is_err());
+    }
+}
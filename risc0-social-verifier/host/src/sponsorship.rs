@@ -0,0 +1,396 @@
+// Paymaster sponsorship tiers
+// Derives a wallet's verification level from its verification journals and
+// maps it to an `EIP7702Paymaster.SponsorshipPolicy`, ready for
+// `setAccountPolicy`.
+
+use alloy::dyn_abi::DynSolValue;
+use alloy::primitives::{keccak256, Address, U256};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{ExpiryPolicy, PolicyOutcome, ProofEnvelope, SocialPlatform, SocialVerificationService, VerificationOutput};
+
+const DAY: u64 = 24 * 60 * 60;
+const ETHER: u64 = 1_000_000_000_000_000_000;
+
+const SET_ACCOUNT_POLICY: &str = "setAccountPolicy(address,(uint256,uint256,uint256,bool,bool,uint256,bool))";
+
+/// `EIP7702Paymaster.SponsorshipPolicy`, field names as on-chain. Gas limits
+/// are in wei.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SponsorshipPolicy {
+    pub daily_gas_limit: u64,
+    pub per_tx_gas_limit: u64,
+    pub daily_tx_limit: u64,
+    pub requires_whitelist: bool,
+    pub requires_social_verification: bool,
+    pub minimum_verification_level: u64,
+    pub is_active: bool,
+}
+
+impl SponsorshipPolicy {
+    /// The struct as an ABI tuple, in declaration order
+    fn abi_value(&self) -> DynSolValue {
+        DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(self.daily_gas_limit), 256),
+            DynSolValue::Uint(U256::from(self.per_tx_gas_limit), 256),
+            DynSolValue::Uint(U256::from(self.daily_tx_limit), 256),
+            DynSolValue::Bool(self.requires_whitelist),
+            DynSolValue::Bool(self.requires_social_verification),
+            DynSolValue::Uint(U256::from(self.minimum_verification_level), 256),
+            DynSolValue::Bool(self.is_active),
+        ])
+    }
+}
+
+/// Calldata for `EIP7702Paymaster.setAccountPolicy(account, policy)`
+pub fn set_account_policy_calldata(account: &str, policy: &SponsorshipPolicy) -> Result<Vec<u8>> {
+    let account: Address = account.parse().map_err(|_| anyhow!("Invalid account address {}", account))?;
+    let params = DynSolValue::Tuple(vec![DynSolValue::Address(account), policy.abi_value()]);
+    Ok([&keccak256(SET_ACCOUNT_POLICY)[..4], params.abi_encode_params().as_slice()].concat())
+}
+
+/// A tier is reached with `minimum_verification_level` distinct platforms
+/// whose accounts each score at most `maximum_risk_score` and are at least
+/// `minimum_account_age` seconds old
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SponsorshipTier {
+    pub id: u8,
+    pub name: String,
+    pub minimum_verification_level: u32,
+    pub maximum_risk_score: u8,
+    pub minimum_account_age: u64,
+    pub policy: SponsorshipPolicy,
+}
+
+/// Tiers in ascending order; a wallet gets the last one it qualifies for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SponsorshipTiers {
+    pub tiers: Vec<SponsorshipTier>,
+}
+
+impl Default for SponsorshipTiers {
+    fn default() -> Self {
+        let tier = |id, name: &str, level, maximum_risk_score, age_days: u64, daily_milliether: u64, daily_tx_limit| {
+            SponsorshipTier {
+                id,
+                name: name.to_string(),
+                minimum_verification_level: level,
+                maximum_risk_score,
+                minimum_account_age: age_days * DAY,
+                policy: SponsorshipPolicy {
+                    daily_gas_limit: daily_milliether * (ETHER / 1000),
+                    per_tx_gas_limit: daily_milliether * (ETHER / 10_000),
+                    daily_tx_limit,
+                    requires_whitelist: false,
+                    requires_social_verification: level > 0,
+                    minimum_verification_level: u64::from(level),
+                    is_active: true,
+                },
+            }
+        };
+        // Tier 0 is the paymaster's default global policy
+        Self {
+            tiers: vec![
+                tier(0, "standard", 0, 100, 0, 100, 50),
+                tier(1, "verified", 1, 50, 30, 200, 100),
+                tier(2, "trusted", 2, 30, 90, 500, 200),
+                tier(3, "premium", 3, 15, 365, 1000, 500),
+            ],
+        }
+    }
+}
+
+/// Tier a wallet's verifications put it in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SponsorshipDecision {
+    pub wallet_address: String,
+    /// Distinct platforms with a live, passed verification, as the registry counts them
+    pub verification_level: u32,
+    pub tier_id: u8,
+    pub tier: String,
+    pub policy: SponsorshipPolicy,
+    /// Last second every verification backing the tier still counts, the
+    /// earliest `timestamp + verification_validity_period` among them; 0 for
+    /// a tier that needs none
+    pub valid_until: u64,
+    /// `setAccountPolicy(wallet, policy)`
+    #[serde(with = "crate::envelope::hex_bytes")]
+    pub calldata: Vec<u8>,
+}
+
+impl SponsorshipDecision {
+    /// Gas units the daily allowance buys at `max_fee_per_gas` wei
+    pub fn daily_gas_units(&self, max_fee_per_gas: u64) -> u64 {
+        self.policy.daily_gas_limit / max_fee_per_gas.max(1)
+    }
+
+    /// Transactions a day at `gas_per_tx` units and `max_fee_per_gas` wei,
+    /// capped by the policy's transaction limit
+    pub fn daily_transactions(&self, gas_per_tx: u64, max_fee_per_gas: u64) -> u64 {
+        let cost = gas_per_tx.saturating_mul(max_fee_per_gas).max(1);
+        if cost > self.policy.per_tx_gas_limit {
+            return 0;
+        }
+        (self.policy.daily_gas_limit / cost).min(self.policy.daily_tx_limit)
+    }
+}
+
+impl SponsorshipTiers {
+    /// Problems with the tier table, for `HostConfig::validate`
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.tiers.iter().any(|tier| tier.minimum_verification_level == 0) {
+            problems.push("sponsorship.tiers needs a tier with minimum_verification_level = 0".to_string());
+        }
+        for pair in self.tiers.windows(2) {
+            if pair[1].id <= pair[0].id || pair[1].minimum_verification_level < pair[0].minimum_verification_level {
+                problems.push(format!(
+                    "sponsorship.tiers must be in ascending order, but tier {} follows tier {}",
+                    pair[1].id, pair[0].id
+                ));
+            }
+        }
+        for tier in &self.tiers {
+            if tier.policy.per_tx_gas_limit > tier.policy.daily_gas_limit {
+                problems.push(format!("sponsorship tier {} allows more gas per transaction than per day", tier.id));
+            }
+            if tier.maximum_risk_score > 100 {
+                problems.push(format!("sponsorship tier {} maximum_risk_score is above 100", tier.id));
+            }
+        }
+        problems
+    }
+
+    /// Place `wallet_address` in a tier from `outputs`, which the caller has
+    /// verified. Only successful verifications of this wallet scored with the
+    /// model hashed to `risk_model_hash` count, and only while the registry
+    /// would still count them: until `verification_validity_period` after
+    /// their timestamp, not the proof's own `expires_at`.
+    pub fn decide(
+        &self,
+        wallet_address: &str,
+        outputs: &[VerificationOutput],
+        risk_model_hash: &[u8; 32],
+        expiry_policy: &ExpiryPolicy,
+        now: u64,
+    ) -> Result<SponsorshipDecision> {
+        let live: Vec<&VerificationOutput> = outputs
+            .iter()
+            .filter(|output| {
+                output.wallet_address.eq_ignore_ascii_case(wallet_address)
+                    && output.verification_success
+                    && output.policy_outcome == PolicyOutcome::Passed
+                    && expiry_policy.verification_valid_until(output.timestamp) >= now
+                    && output.risk_model_hash == *risk_model_hash
+            })
+            .collect();
+
+        let (tier, backing) = self
+            .tiers
+            .iter()
+            .rev()
+            .find_map(|tier| {
+                let backing = qualifying_platforms(tier, &live);
+                (backing.len() as u32 >= tier.minimum_verification_level).then_some((tier, backing))
+            })
+            .ok_or_else(|| anyhow!("No sponsorship tier applies to {}", wallet_address))?;
+
+        Ok(SponsorshipDecision {
+            wallet_address: wallet_address.to_string(),
+            verification_level: distinct_platforms(&live).len() as u32,
+            tier_id: tier.id,
+            tier: tier.name.clone(),
+            policy: tier.policy.clone(),
+            valid_until: backing
+                .iter()
+                .map(|output| expiry_policy.verification_valid_until(output.timestamp))
+                .min()
+                .unwrap_or(0),
+            calldata: set_account_policy_calldata(wallet_address, &tier.policy)?,
+        })
+    }
+}
+
+/// One output per platform meeting `tier`'s score and age, the most recent
+/// where there are several
+fn qualifying_platforms<'a>(tier: &SponsorshipTier, live: &[&'a VerificationOutput]) -> Vec<&'a VerificationOutput> {
+    let eligible: Vec<&VerificationOutput> = live
+        .iter()
+        .copied()
+        .filter(|output| {
+            output.sybil_risk_score <= tier.maximum_risk_score && output.account_age >= tier.minimum_account_age
+        })
+        .collect();
+    distinct_platforms(&eligible)
+}
+
+fn distinct_platforms<'a>(outputs: &[&'a VerificationOutput]) -> Vec<&'a VerificationOutput> {
+    let mut by_platform: Vec<(SocialPlatform, &VerificationOutput)> = Vec::new();
+    for &output in outputs {
+        match by_platform.iter_mut().find(|(platform, _)| *platform == output.platform) {
+            Some((_, best)) if output.timestamp > best.timestamp => *best = output,
+            Some(_) => {}
+            None => by_platform.push((output.platform, output)),
+        }
+    }
+    by_platform.into_iter().map(|(_, output)| output).collect()
+}
+
+impl SocialVerificationService {
+    /// Sponsorship tier for `wallet_address` from `envelopes`, or from the
+    /// store's latest receipt per platform when none are given
+    pub fn sponsorship(&self, wallet_address: &str, envelopes: &[ProofEnvelope]) -> Result<SponsorshipDecision> {
+        let mut outputs = Vec::new();
+        if envelopes.is_empty() {
+            if let Some(store) = &self.store {
+                for platform in &self.receipt_verifier.enabled_platforms {
+                    if let Some(envelope) = store.latest_profile_receipt(wallet_address, *platform)? {
+                        // A receipt this deployment no longer accepts just doesn't count
+                        if let Ok(output) = self.verify_proof(&envelope) {
+                            outputs.push(output);
+                        }
+                    }
+                }
+            }
+        }
+        for envelope in envelopes {
+            outputs.push(self.verify_proof(envelope)?);
+        }
+        self.sponsorship_tiers.decide(
+            wallet_address,
+            &outputs,
+            &self.risk_model.hash(),
+            &self.expiry_policy,
+            crate::unix_now_secs(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RiskModel, VerificationType};
+
+    const WALLET: &str = "0x1234567890123456789012345678901234567890";
+    const NOW: u64 = 1_700_000_000;
+
+    fn output(platform: SocialPlatform, sybil_risk_score: u8, account_age: u64) -> VerificationOutput {
+        VerificationOutput {
+            social_account_hash: [platform as u8; 32],
+            wallet_address: WALLET.to_string(),
+            platform,
+            account_age,
+            follower_count: 150,
+            timestamp: NOW - 60,
+            expires_at: NOW + 3600,
+            nonce: 1,
+            social_account_id: "123456789".to_string(),
+            verification_type: VerificationType::NewAccount,
+            account_consistency_score: 100,
+            chain_id: 11155111,
+            verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            verification_success: true,
+            policy_hash: [0u8; 32],
+            policy_outcome: PolicyOutcome::Passed,
+            profile_commitment: [0u8; 32],
+            previous_profile_commitment: None,
+            self_image_id: [0u32; 8],
+            previous_proof_hash: None,
+            sybil_risk_score,
+            risk_model_version: 1,
+            risk_model_hash: RiskModel::default().hash(),
+            oauth_token_hash: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn test_tier_from_platforms_score_and_age() {
+        let tiers = SponsorshipTiers::default();
+        assert!(tiers.problems().is_empty());
+        let model = RiskModel::default().hash();
+        let expiry = ExpiryPolicy::default();
+
+        let decision = tiers.decide(WALLET, &[], &model, &expiry, NOW).unwrap();
+        assert_eq!((decision.tier_id, decision.verification_level, decision.valid_until), (0, 0, 0));
+        assert!(!decision.policy.requires_social_verification);
+
+        let twitter = output(SocialPlatform::Twitter, 0, 400 * DAY);
+        let github = output(SocialPlatform::Github, 10, 400 * DAY);
+        let decision = tiers.decide(WALLET, &[twitter.clone(), github.clone()], &model, &expiry, NOW).unwrap();
+        assert_eq!((decision.tier_id, decision.verification_level), (2, 2));
+        assert_eq!(decision.policy.minimum_verification_level, 2);
+        assert_eq!(decision.valid_until, NOW - 60 + 30 * DAY);
+
+        // Three platforms, but Discord scores too risky for premium
+        let discord = output(SocialPlatform::Discord, 55, 0);
+        let decision = tiers.decide(WALLET, &[twitter.clone(), github.clone(), discord], &model, &expiry, NOW).unwrap();
+        assert_eq!((decision.tier_id, decision.verification_level), (2, 3));
+
+        // Young accounts stay in the verified tier
+        let young = output(SocialPlatform::Twitter, 0, 60 * DAY);
+        assert_eq!(tiers.decide(WALLET, &[young], &model, &expiry, NOW).unwrap().tier_id, 1);
+
+        // A verification counts for the registry's validity period, long after
+        // the proof itself expired, and the most recent one per platform is used
+        let older = VerificationOutput {
+            timestamp: NOW - 30 * DAY,
+            expires_at: NOW - 30 * DAY + 3600,
+            ..twitter.clone()
+        };
+        let decision = tiers.decide(WALLET, std::slice::from_ref(&older), &model, &expiry, NOW).unwrap();
+        assert_eq!((decision.tier_id, decision.valid_until), (1, NOW));
+        let decision = tiers.decide(WALLET, &[older, twitter.clone(), github.clone()], &model, &expiry, NOW).unwrap();
+        assert_eq!((decision.tier_id, decision.valid_until), (2, NOW - 60 + 30 * DAY));
+
+        // Lapsed verifications, other wallets and other risk models don't count
+        let expired = VerificationOutput {
+            timestamp: NOW - 30 * DAY - 1,
+            expires_at: NOW - 30 * DAY - 1 + 3600,
+            ..twitter.clone()
+        };
+        let other_wallet = VerificationOutput {
+            wallet_address: "0x1111222233334444555566667777888899990000".to_string(),
+            ..twitter.clone()
+        };
+        let other_model = VerificationOutput {
+            risk_model_hash: [9u8; 32],
+            oauth_token_hash: [0u8; 32],
+            ..twitter
+        };
+        let decision = tiers.decide(WALLET, &[expired, other_wallet, other_model], &model, &expiry, NOW).unwrap();
+        assert_eq!(decision.tier_id, 0);
+    }
+
+    #[test]
+    fn test_set_account_policy_calldata() {
+        let tiers = SponsorshipTiers::default();
+        let policy = &tiers.tiers[1].policy;
+        let calldata = set_account_policy_calldata(WALLET, policy).unwrap();
+
+        assert_eq!(calldata[..4], keccak256(SET_ACCOUNT_POLICY)[..4]);
+        // address, then the struct's seven static words inline
+        assert_eq!(calldata.len(), 4 + 32 * 8);
+        assert_eq!(hex::encode(&calldata[4 + 12..4 + 32]), WALLET.trim_start_matches("0x"));
+        assert_eq!(U256::from_be_slice(&calldata[36..68]), U256::from(policy.daily_gas_limit));
+        assert_eq!(calldata[4 + 32 * 6 - 1], 1); // requiresSocialVerification
+        assert_eq!(U256::from_be_slice(&calldata[4 + 32 * 6..4 + 32 * 7]), U256::from(1u64));
+        assert!(set_account_policy_calldata("not an address", policy).is_err());
+    }
+
+    #[test]
+    fn test_daily_allowance() {
+        let decision = SponsorshipTiers::default()
+            .decide(WALLET, &[], &RiskModel::default().hash(), &ExpiryPolicy::default(), NOW)
+            .unwrap();
+        // 0.1 ether at 10 gwei
+        assert_eq!(decision.daily_gas_units(10_000_000_000), 10_000_000);
+        // 100k gas at 10 gwei is 0.001 ether: 100 fit, capped at 50 a day
+        assert_eq!(decision.daily_transactions(100_000, 10_000_000_000), 50);
+        // Above the 0.01 ether per-transaction limit
+        assert_eq!(decision.daily_transactions(2_000_000, 10_000_000_000), 0);
+    }
+}
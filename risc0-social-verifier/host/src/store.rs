@@ -0,0 +1,862 @@
+// Receipt store
+// Receipts are kept as bincode `ProofEnvelope` files under `receipts/`,
+// indexed in SQLite by proof hash, wallet, social account hash and time, so
+// proofs can be re-served, audited and re-submitted to chain. The latest
+// profile snapshot per wallet and platform is kept for re-verification, and
+// the relayer records each transaction it sends so it can resume after a restart.
+// The oracle keeps the requests it has seen and how far it has scanned.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::{
+    OracleRequest, OracleRequestStatus, ProfileSnapshot, ProofEnvelope, ProofResult, ProverKind, RelayJob, RelayStatus,
+    RelayStep, SocialPlatform, VerificationOutput,
+};
+
+const BUNDLE_VERSION: u32 = 1;
+
+/// Index entry for a stored receipt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredProof {
+    pub proof_hash: String,
+    pub wallet_address: String,
+    pub social_account_hash: String,
+    pub platform: SocialPlatform,
+    pub timestamp: u64,
+    pub expires_at: u64,
+    pub verification_success: bool,
+    pub prover: ProverKind,
+}
+
+impl StoredProof {
+    /// Index entry for the receipt whose journal hashes to `proof_hash` and
+    /// decodes to `output`
+    fn new(proof_hash: &[u8; 32], output: &VerificationOutput, prover: ProverKind) -> Self {
+        Self {
+            proof_hash: hex::encode(proof_hash),
+            wallet_address: output.wallet_address.to_lowercase(),
+            social_account_hash: hex::encode(output.social_account_hash),
+            platform: output.platform,
+            timestamp: output.timestamp,
+            expires_at: output.expires_at,
+            verification_success: output.verification_success,
+            prover,
+        }
+    }
+}
+
+/// What `apply_retention` deletes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Drop receipts created more than this many seconds ago
+    pub max_age: Option<u64>,
+    /// Keep only the newest receipts for each wallet
+    pub max_per_wallet: Option<usize>,
+}
+
+/// Portable set of receipts for moving between stores
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptBundle {
+    pub version: u32,
+    pub entries: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub proof: StoredProof,
+    pub receipt: ProofEnvelope,
+}
+
+pub struct ReceiptStore {
+    root: PathBuf,
+    db: Mutex<Connection>,
+}
+
+impl ReceiptStore {
+    /// Open or create a store rooted at `root`
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(root.join("receipts"))?;
+
+        let db = Connection::open(root.join("index.sqlite3"))?;
+        db.execute_batch(
+            "CREATE TABLE IF NOT EXISTS proofs (
+                proof_hash TEXT PRIMARY KEY,
+                wallet_address TEXT NOT NULL,
+                social_account_hash TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                verification_success INTEGER NOT NULL,
+                prover TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS proofs_wallet ON proofs (wallet_address, timestamp);
+            CREATE INDEX IF NOT EXISTS proofs_social_account ON proofs (social_account_hash, timestamp);
+            CREATE INDEX IF NOT EXISTS proofs_timestamp ON proofs (timestamp);
+            CREATE TABLE IF NOT EXISTS profiles (
+                wallet_address TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                proof_hash TEXT NOT NULL,
+                verified_at INTEGER NOT NULL,
+                snapshot TEXT NOT NULL,
+                PRIMARY KEY (wallet_address, platform)
+            );
+            CREATE TABLE IF NOT EXISTS relay_jobs (
+                proof_hash TEXT NOT NULL,
+                step TEXT NOT NULL,
+                status TEXT NOT NULL,
+                request_id TEXT,
+                tx_hash TEXT,
+                tx_hashes TEXT NOT NULL,
+                nonce INTEGER,
+                attempts INTEGER NOT NULL,
+                block_number INTEGER,
+                gas_used INTEGER,
+                error TEXT,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (proof_hash, step)
+            );
+            CREATE INDEX IF NOT EXISTS relay_jobs_status ON relay_jobs (status, updated_at);
+            CREATE TABLE IF NOT EXISTS oracle_requests (
+                request_id TEXT PRIMARY KEY,
+                requester TEXT NOT NULL,
+                wallet_address TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                oauth_token_hash TEXT NOT NULL,
+                requested_at INTEGER NOT NULL,
+                block_number INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                proof_hash TEXT,
+                error TEXT,
+                updated_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS oracle_requests_status ON oracle_requests (status, requested_at);
+            CREATE TABLE IF NOT EXISTS oracle_checkpoints (
+                contract TEXT PRIMARY KEY,
+                block_number INTEGER NOT NULL
+            );",
+        )?;
+
+        Ok(Self {
+            root,
+            db: Mutex::new(db),
+        })
+    }
+
+    /// Store a freshly generated proof
+    pub fn put(&self, result: &ProofResult) -> Result<StoredProof> {
+        let proof = StoredProof::new(&result.proof_hash, &result.verification_output, result.prover);
+        self.insert(&proof, &result.receipt)?;
+        if let Some(profile) = &result.profile {
+            self.put_profile(&proof, profile)?;
+        }
+        Ok(proof)
+    }
+
+    fn put_profile(&self, proof: &StoredProof, profile: &ProfileSnapshot) -> Result<()> {
+        // Never let an older proof replace a newer snapshot
+        self.db()?.execute(
+            "INSERT INTO profiles VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (wallet_address, platform) DO UPDATE SET
+                proof_hash = excluded.proof_hash,
+                verified_at = excluded.verified_at,
+                snapshot = excluded.snapshot
+             WHERE excluded.verified_at >= profiles.verified_at",
+            params![
+                proof.wallet_address,
+                serde_json::to_string(&proof.platform)?,
+                proof.proof_hash,
+                profile.verified_at as i64,
+                serde_json::to_string(profile)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Snapshot from the wallet's latest verification on `platform`
+    pub fn latest_profile(&self, wallet_address: &str, platform: SocialPlatform) -> Result<Option<ProfileSnapshot>> {
+        let snapshot: Option<String> = self
+            .db()?
+            .query_row(
+                "SELECT snapshot FROM profiles WHERE wallet_address = ?1 AND platform = ?2",
+                params![wallet_address.to_lowercase(), serde_json::to_string(&platform)?],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(snapshot.map(|snapshot| serde_json::from_str(&snapshot)).transpose()?)
+    }
+
+    /// Receipt that committed to `latest_profile`, unless retention removed it
+    pub fn latest_profile_receipt(&self, wallet_address: &str, platform: SocialPlatform) -> Result<Option<ProofEnvelope>> {
+        let proof_hash: Option<String> = self
+            .db()?
+            .query_row(
+                "SELECT proof_hash FROM profiles WHERE wallet_address = ?1 AND platform = ?2",
+                params![wallet_address.to_lowercase(), serde_json::to_string(&platform)?],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match proof_hash {
+            Some(proof_hash) => self.receipt(&proof_hash),
+            None => Ok(None),
+        }
+    }
+
+    fn insert(&self, proof: &StoredProof, receipt: &ProofEnvelope) -> Result<()> {
+        // Blob first, so an index row never points at a missing file
+        std::fs::write(self.blob_path(&proof.proof_hash)?, receipt.to_bincode()?)?;
+
+        self.db()?.execute(
+            "INSERT OR REPLACE INTO proofs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                normalize_hash(&proof.proof_hash),
+                proof.wallet_address.to_lowercase(),
+                proof.social_account_hash,
+                serde_json::to_string(&proof.platform)?,
+                proof.timestamp as i64,
+                proof.expires_at as i64,
+                proof.verification_success,
+                serde_json::to_string(&proof.prover)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Receipt for a proof hash
+    pub fn receipt(&self, proof_hash: &str) -> Result<Option<ProofEnvelope>> {
+        if self.find_by_proof_hash(proof_hash)?.is_none() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(self.blob_path(proof_hash)?)?;
+        Ok(Some(ProofEnvelope::from_bincode(&bytes)?))
+    }
+
+    pub fn find_by_proof_hash(&self, proof_hash: &str) -> Result<Option<StoredProof>> {
+        let db = self.db()?;
+        let proof = db
+            .query_row(
+                "SELECT * FROM proofs WHERE proof_hash = ?1",
+                params![normalize_hash(proof_hash)],
+                stored_proof,
+            )
+            .optional()?;
+        Ok(proof)
+    }
+
+    pub fn find_by_wallet(&self, wallet_address: &str) -> Result<Vec<StoredProof>> {
+        self.query(
+            "SELECT * FROM proofs WHERE wallet_address = ?1 ORDER BY timestamp DESC",
+            params![wallet_address.to_lowercase()],
+        )
+    }
+
+    pub fn find_by_social_account(&self, social_account_hash: &str) -> Result<Vec<StoredProof>> {
+        self.query(
+            "SELECT * FROM proofs WHERE social_account_hash = ?1 ORDER BY timestamp DESC",
+            params![normalize_hash(social_account_hash)],
+        )
+    }
+
+    /// Proofs created in `[from, to)`
+    pub fn find_in_range(&self, from: u64, to: u64) -> Result<Vec<StoredProof>> {
+        self.query(
+            "SELECT * FROM proofs WHERE timestamp >= ?1 AND timestamp < ?2 ORDER BY timestamp DESC",
+            params![from as i64, to as i64],
+        )
+    }
+
+    /// Delete receipts the policy no longer keeps. Returns how many were removed.
+    pub fn apply_retention(&self, policy: &RetentionPolicy, now: u64) -> Result<usize> {
+        let mut doomed = Vec::new();
+
+        if let Some(max_age) = policy.max_age {
+            // Older than `max_age`; a receipt exactly `max_age` old is kept
+            let cutoff = now.saturating_sub(max_age);
+            doomed.extend(self.find_in_range(0, cutoff)?);
+        }
+
+        if let Some(max_per_wallet) = policy.max_per_wallet {
+            let wallets: Vec<String> = {
+                let db = self.db()?;
+                let mut statement = db.prepare("SELECT DISTINCT wallet_address FROM proofs")?;
+                let wallets = statement
+                    .query_map([], |row| row.get(0))?
+                    .collect::<rusqlite::Result<_>>()?;
+                wallets
+            };
+            for wallet in wallets {
+                doomed.extend(self.find_by_wallet(&wallet)?.into_iter().skip(max_per_wallet));
+            }
+        }
+
+        doomed.sort_by(|a, b| a.proof_hash.cmp(&b.proof_hash));
+        doomed.dedup_by(|a, b| a.proof_hash == b.proof_hash);
+        for proof in &doomed {
+            self.remove(&proof.proof_hash)?;
+        }
+        Ok(doomed.len())
+    }
+
+    pub fn remove(&self, proof_hash: &str) -> Result<()> {
+        let proof_hash = normalize_hash(proof_hash);
+        self.db()?
+            .execute("DELETE FROM proofs WHERE proof_hash = ?1", params![proof_hash])?;
+        match std::fs::remove_file(self.blob_path(&proof_hash)?) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Bundle the given proofs with their receipts
+    pub fn export_bundle(&self, proofs: &[StoredProof]) -> Result<ReceiptBundle> {
+        let entries = proofs
+            .iter()
+            .map(|proof| {
+                let receipt = self
+                    .receipt(&proof.proof_hash)?
+                    .ok_or_else(|| anyhow!("Proof {} is not in the store", proof.proof_hash))?;
+                Ok(BundleEntry {
+                    proof: proof.clone(),
+                    receipt,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(ReceiptBundle {
+            version: BUNDLE_VERSION,
+            entries,
+        })
+    }
+
+    /// Add every entry of a bundle, or none if any is invalid. Returns how
+    /// many were imported.
+    ///
+    /// Entries are indexed by what their journal commits to, not by the
+    /// metadata that came with them, and each journal must hash to the entry's
+    /// proof hash. Seals are not verified here; `host verify` does that.
+    pub fn import_bundle(&self, bundle: &ReceiptBundle) -> Result<usize> {
+        if bundle.version != BUNDLE_VERSION {
+            return Err(anyhow!("Unsupported bundle version {}", bundle.version));
+        }
+        let proofs = bundle
+            .entries
+            .iter()
+            .map(|entry| {
+                let proof_hash = crate::proof_hash(&entry.receipt.journal);
+                if hex::encode(proof_hash) != normalize_hash(&entry.proof.proof_hash) {
+                    return Err(anyhow!(
+                        "Bundle entry {} does not match its journal, which hashes to {}",
+                        entry.proof.proof_hash,
+                        hex::encode(proof_hash)
+                    ));
+                }
+                let output: VerificationOutput = risc0_zkvm::serde::from_slice(&entry.receipt.journal)
+                    .map_err(|e| anyhow!("Bundle entry {} has a malformed journal: {}", entry.proof.proof_hash, e))?;
+                Ok(StoredProof::new(&proof_hash, &output, entry.proof.prover))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (proof, entry) in proofs.iter().zip(&bundle.entries) {
+            self.insert(proof, &entry.receipt)?;
+        }
+        Ok(proofs.len())
+    }
+
+    /// Record the latest state of a relay job
+    pub fn put_relay_job(&self, job: &RelayJob) -> Result<()> {
+        self.db()?.execute(
+            "INSERT OR REPLACE INTO relay_jobs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                normalize_hash(&job.proof_hash),
+                serde_json::to_string(&job.step)?,
+                serde_json::to_string(&job.status)?,
+                job.request_id,
+                job.tx_hash,
+                serde_json::to_string(&job.tx_hashes)?,
+                job.nonce.map(|nonce| nonce as i64),
+                job.attempts,
+                job.block_number.map(|block| block as i64),
+                job.gas_used.map(|gas| gas as i64),
+                job.error,
+                job.updated_at as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn relay_job(&self, proof_hash: &str, step: RelayStep) -> Result<Option<RelayJob>> {
+        let db = self.db()?;
+        let job = db
+            .query_row(
+                "SELECT * FROM relay_jobs WHERE proof_hash = ?1 AND step = ?2",
+                params![normalize_hash(proof_hash), serde_json::to_string(&step)?],
+                relay_job,
+            )
+            .optional()?;
+        Ok(job)
+    }
+
+    /// Every step relayed for a proof, oldest first
+    pub fn relay_jobs(&self, proof_hash: &str) -> Result<Vec<RelayJob>> {
+        self.query_relay_jobs(
+            "SELECT * FROM relay_jobs WHERE proof_hash = ?1 ORDER BY updated_at",
+            params![normalize_hash(proof_hash)],
+        )
+    }
+
+    pub fn relay_jobs_with_status(&self, status: RelayStatus) -> Result<Vec<RelayJob>> {
+        self.query_relay_jobs(
+            "SELECT * FROM relay_jobs WHERE status = ?1 ORDER BY updated_at",
+            params![serde_json::to_string(&status)?],
+        )
+    }
+
+    /// Record a request seen in the logs. Returns false if it was already known,
+    /// leaving its state alone.
+    pub fn add_oracle_request(&self, request: &OracleRequest) -> Result<bool> {
+        Ok(self.write_oracle_request("IGNORE", request)? > 0)
+    }
+
+    pub fn put_oracle_request(&self, request: &OracleRequest) -> Result<()> {
+        self.write_oracle_request("REPLACE", request)?;
+        Ok(())
+    }
+
+    fn write_oracle_request(&self, on_conflict: &str, request: &OracleRequest) -> Result<usize> {
+        Ok(self.db()?.execute(
+            &format!(
+                "INSERT OR {} INTO oracle_requests VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                on_conflict
+            ),
+            params![
+                normalize_hash(&request.request_id),
+                request.requester.to_lowercase(),
+                request.wallet_address.to_lowercase(),
+                serde_json::to_string(&request.platform)?,
+                normalize_hash(&request.oauth_token_hash),
+                request.requested_at as i64,
+                request.block_number as i64,
+                serde_json::to_string(&request.status)?,
+                request.proof_hash,
+                request.error,
+                request.updated_at as i64,
+            ],
+        )?)
+    }
+
+    pub fn oracle_request(&self, request_id: &str) -> Result<Option<OracleRequest>> {
+        let db = self.db()?;
+        let request = db
+            .query_row(
+                "SELECT * FROM oracle_requests WHERE request_id = ?1",
+                params![normalize_hash(request_id)],
+                oracle_request,
+            )
+            .optional()?;
+        Ok(request)
+    }
+
+    /// Requests in `status`, oldest first
+    pub fn oracle_requests_with_status(&self, status: OracleRequestStatus) -> Result<Vec<OracleRequest>> {
+        let db = self.db()?;
+        let mut statement = db.prepare("SELECT * FROM oracle_requests WHERE status = ?1 ORDER BY requested_at")?;
+        let requests = statement
+            .query_map(params![serde_json::to_string(&status)?], oracle_request)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(requests)
+    }
+
+    /// Last block the oracle has fully scanned for `contract`
+    pub fn oracle_checkpoint(&self, contract: &str) -> Result<Option<u64>> {
+        let block: Option<i64> = self
+            .db()?
+            .query_row(
+                "SELECT block_number FROM oracle_checkpoints WHERE contract = ?1",
+                params![contract.to_lowercase()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(block.map(|block| block as u64))
+    }
+
+    pub fn set_oracle_checkpoint(&self, contract: &str, block_number: u64) -> Result<()> {
+        self.db()?.execute(
+            "INSERT OR REPLACE INTO oracle_checkpoints VALUES (?1, ?2)",
+            params![contract.to_lowercase(), block_number as i64],
+        )?;
+        Ok(())
+    }
+
+    fn query_relay_jobs(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<RelayJob>> {
+        let db = self.db()?;
+        let mut statement = db.prepare(sql)?;
+        let jobs = statement
+            .query_map(params, relay_job)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(jobs)
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<StoredProof>> {
+        let db = self.db()?;
+        let mut statement = db.prepare(sql)?;
+        let proofs = statement
+            .query_map(params, stored_proof)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(proofs)
+    }
+
+    fn db(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.db.lock().map_err(|_| anyhow!("Receipt index lock poisoned"))
+    }
+
+    fn blob_path(&self, proof_hash: &str) -> Result<PathBuf> {
+        let proof_hash = normalize_hash(proof_hash);
+        // The hash becomes a file name, so only accept what it should look like
+        if proof_hash.len() != 64 || !proof_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("Invalid proof hash: {}", proof_hash));
+        }
+        Ok(self.root.join("receipts").join(format!("{}.bin", proof_hash)))
+    }
+}
+
+fn normalize_hash(hash: &str) -> String {
+    hash.trim_start_matches("0x").to_lowercase()
+}
+
+fn stored_proof(row: &Row) -> rusqlite::Result<StoredProof> {
+    let json = |index: usize| -> rusqlite::Result<String> { row.get(index) };
+    let decode_error = |e: serde_json::Error| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e));
+
+    Ok(StoredProof {
+        proof_hash: row.get(0)?,
+        wallet_address: row.get(1)?,
+        social_account_hash: row.get(2)?,
+        platform: serde_json::from_str(&json(3)?).map_err(decode_error)?,
+        timestamp: row.get::<_, i64>(4)? as u64,
+        expires_at: row.get::<_, i64>(5)? as u64,
+        verification_success: row.get(6)?,
+        prover: serde_json::from_str(&json(7)?).map_err(decode_error)?,
+    })
+}
+
+fn relay_job(row: &Row) -> rusqlite::Result<RelayJob> {
+    let json = |index: usize| -> rusqlite::Result<String> { row.get(index) };
+    let decode_error = |e: serde_json::Error| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e));
+    let number = |index: usize| -> rusqlite::Result<Option<u64>> { Ok(row.get::<_, Option<i64>>(index)?.map(|n| n as u64)) };
+
+    Ok(RelayJob {
+        proof_hash: row.get(0)?,
+        step: serde_json::from_str(&json(1)?).map_err(decode_error)?,
+        status: serde_json::from_str(&json(2)?).map_err(decode_error)?,
+        request_id: row.get(3)?,
+        tx_hash: row.get(4)?,
+        tx_hashes: serde_json::from_str(&json(5)?).map_err(decode_error)?,
+        nonce: number(6)?,
+        attempts: row.get(7)?,
+        block_number: number(8)?,
+        gas_used: number(9)?,
+        error: row.get(10)?,
+        updated_at: row.get::<_, i64>(11)? as u64,
+    })
+}
+
+fn oracle_request(row: &Row) -> rusqlite::Result<OracleRequest> {
+    let json = |index: usize| -> rusqlite::Result<String> { row.get(index) };
+    let decode_error = |e: serde_json::Error| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e));
+
+    Ok(OracleRequest {
+        request_id: row.get(0)?,
+        requester: row.get(1)?,
+        wallet_address: row.get(2)?,
+        platform: serde_json::from_str(&json(3)?).map_err(decode_error)?,
+        oauth_token_hash: row.get(4)?,
+        requested_at: row.get::<_, i64>(5)? as u64,
+        block_number: row.get::<_, i64>(6)? as u64,
+        status: serde_json::from_str(&json(7)?).map_err(decode_error)?,
+        proof_hash: row.get(8)?,
+        error: row.get(9)?,
+        updated_at: row.get::<_, i64>(10)? as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PolicyOutcome, RiskModel, VerificationOutput, VerificationType};
+
+    fn proof(seed: u8, wallet: &str, account: u8, timestamp: u64) -> ProofResult {
+        ProofResult {
+            verification_output: VerificationOutput {
+                social_account_hash: [account; 32],
+                wallet_address: wallet.to_string(),
+                platform: SocialPlatform::Twitter,
+                account_age: 0,
+                follower_count: 150,
+                timestamp,
+                expires_at: timestamp + 3600,
+                nonce: seed as u64,
+                social_account_id: "123456789".to_string(),
+                verification_type: VerificationType::NewAccount,
+                account_consistency_score: 100,
+                chain_id: 11155111,
+                verifying_contract: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+                verification_success: true,
+                policy_hash: [0u8; 32],
+                policy_outcome: PolicyOutcome::Passed,
+                profile_commitment: [0u8; 32],
+                previous_profile_commitment: None,
+                self_image_id: [0u32; 8],
+                previous_proof_hash: None,
+                sybil_risk_score: 0,
+                risk_model_version: 1,
+                risk_model_hash: RiskModel::default().hash(),
+                oauth_token_hash: [0u8; 32],
+            },
+            receipt: envelope(seed),
+            proof_hash: [seed; 32],
+            prover: ProverKind::DevMode,
+            profile: None,
+        }
+    }
+
+    fn envelope(seed: u8) -> ProofEnvelope {
+        ProofEnvelope {
+            version: crate::envelope::ENVELOPE_VERSION,
+            kind: crate::ReceiptKind::Succinct,
+            image_id: format!("0x{}", hex::encode([7u8; 32])),
+            journal: vec![seed; 8],
+            encoding: crate::SealEncoding::Bincode,
+            seal: vec![seed; 16],
+        }
+    }
+
+    /// `proof` with a real journal, hashed to its proof hash, as imports need
+    fn journaled(seed: u8, wallet: &str, account: u8, timestamp: u64) -> ProofResult {
+        let mut result = proof(seed, wallet, account, timestamp);
+        result.receipt.journal = risc0_zkvm::serde::to_vec(&result.verification_output)
+            .unwrap()
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        result.proof_hash = crate::proof_hash(&result.receipt.journal);
+        result
+    }
+
+    const ALICE: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+    const BOB: &str = "0x1234567890123456789012345678901234567890";
+
+    #[test]
+    fn test_store_and_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+
+        let stored = store.put(&proof(1, ALICE, 7, 1000)).unwrap();
+        store.put(&proof(2, ALICE, 7, 2000)).unwrap();
+        store.put(&proof(3, BOB, 9, 3000)).unwrap();
+
+        assert_eq!(store.find_by_proof_hash(&stored.proof_hash).unwrap(), Some(stored.clone()));
+        assert_eq!(store.receipt(&format!("0x{}", stored.proof_hash)).unwrap(), Some(envelope(1)));
+        assert_eq!(store.receipt(&hex::encode([4u8; 32])).unwrap(), None);
+
+        // Wallet lookups ignore checksum casing and return newest first
+        let alice = store.find_by_wallet(&ALICE.to_uppercase().replace("0X", "0x")).unwrap();
+        assert_eq!(alice.iter().map(|p| p.timestamp).collect::<Vec<_>>(), vec![2000, 1000]);
+
+        assert_eq!(store.find_by_social_account(&hex::encode([9u8; 32])).unwrap().len(), 1);
+        assert_eq!(store.find_in_range(1000, 3000).unwrap().len(), 2);
+
+        // Survives reopening
+        drop(store);
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        assert_eq!(store.find_by_wallet(BOB).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_latest_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let with_profile = |seed: u8, timestamp: u64| ProofResult {
+            profile: Some(ProfileSnapshot::new("123456789", "2020-01-01T00:00:00.000Z", seed as u64, "testuser", timestamp)),
+            ..proof(seed, ALICE, 7, timestamp)
+        };
+
+        assert_eq!(store.latest_profile(ALICE, SocialPlatform::Twitter).unwrap(), None);
+        store.put(&with_profile(2, 2000)).unwrap();
+        // An older proof arriving late does not replace the snapshot
+        store.put(&with_profile(1, 1000)).unwrap();
+        store.put(&proof(3, ALICE, 7, 3000)).unwrap();
+
+        let latest = store.latest_profile(&ALICE.to_lowercase(), SocialPlatform::Twitter).unwrap().unwrap();
+        assert_eq!(latest.verified_at, 2000);
+        let receipt = store.latest_profile_receipt(ALICE, SocialPlatform::Twitter).unwrap();
+        assert_eq!(receipt, Some(envelope(2)));
+        assert_eq!(store.latest_profile(ALICE, SocialPlatform::Github).unwrap(), None);
+        assert_eq!(store.latest_profile(BOB, SocialPlatform::Twitter).unwrap(), None);
+    }
+
+    #[test]
+    fn test_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        for (seed, timestamp) in [(1, 1000), (2, 2000), (3, 3000)] {
+            store.put(&proof(seed, ALICE, 7, timestamp)).unwrap();
+        }
+        store.put(&proof(4, BOB, 9, 499)).unwrap();
+        store.put(&proof(5, BOB, 9, 500)).unwrap();
+
+        let removed = store
+            .apply_retention(&RetentionPolicy { max_age: Some(3500), max_per_wallet: Some(2) }, 4000)
+            .unwrap();
+
+        // Bob's first is older than max_age, his second exactly max_age old and
+        // kept; Alice's oldest is over the per-wallet limit
+        assert_eq!(removed, 2);
+        let bob: Vec<u64> = store.find_by_wallet(BOB).unwrap().iter().map(|proof| proof.timestamp).collect();
+        assert_eq!(bob, vec![500]);
+        assert_eq!(store.find_by_wallet(ALICE).unwrap().len(), 2);
+        assert!(!dir.path().join("receipts").join(format!("{}.bin", hex::encode([1u8; 32]))).exists());
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let source = tempfile::tempdir().unwrap();
+        let source = ReceiptStore::open(source.path()).unwrap();
+        let bob = journaled(2, BOB, 9, 2000);
+        source.put(&journaled(1, ALICE, 7, 1000)).unwrap();
+        source.put(&bob).unwrap();
+
+        let bundle = source.export_bundle(&source.find_in_range(0, u64::MAX >> 1).unwrap()).unwrap();
+        let bundle: ReceiptBundle = serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let target = ReceiptStore::open(target.path()).unwrap();
+        assert_eq!(target.import_bundle(&bundle).unwrap(), 2);
+        assert_eq!(target.receipt(&hex::encode(bob.proof_hash)).unwrap(), Some(bob.receipt.clone()));
+        assert_eq!(target.find_by_wallet(ALICE).unwrap(), source.find_by_wallet(ALICE).unwrap());
+    }
+
+    #[test]
+    fn test_bundle_is_indexed_by_its_journals() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let alice = journaled(1, ALICE, 7, 1000);
+        let entry = |proof: StoredProof, receipt: &ProofEnvelope| BundleEntry {
+            proof,
+            receipt: receipt.clone(),
+        };
+        let claimed = StoredProof::new(&alice.proof_hash, &alice.verification_output, ProverKind::Local);
+
+        // Metadata filing Alice's receipt under Bob is ignored
+        let misfiled = StoredProof {
+            wallet_address: BOB.to_lowercase(),
+            social_account_hash: hex::encode([9u8; 32]),
+            proof_hash: format!("0x{}", claimed.proof_hash.to_uppercase()),
+            ..claimed.clone()
+        };
+        let bundle = ReceiptBundle {
+            version: BUNDLE_VERSION,
+            entries: vec![entry(misfiled, &alice.receipt)],
+        };
+        assert_eq!(store.import_bundle(&bundle).unwrap(), 1);
+        assert!(store.find_by_wallet(BOB).unwrap().is_empty());
+        assert_eq!(store.find_by_wallet(ALICE).unwrap(), vec![claimed.clone()]);
+        assert_eq!(store.find_by_proof_hash(&claimed.proof_hash).unwrap(), Some(claimed.clone()));
+
+        // A journal that is not the one hashed, or not a journal at all, fails
+        // the whole bundle
+        let bob = journaled(2, BOB, 9, 2000);
+        let swapped = ReceiptBundle {
+            version: BUNDLE_VERSION,
+            entries: vec![
+                entry(StoredProof::new(&bob.proof_hash, &bob.verification_output, ProverKind::Local), &bob.receipt),
+                entry(claimed.clone(), &bob.receipt),
+            ],
+        };
+        assert!(store.import_bundle(&swapped).is_err());
+        assert!(store.find_by_wallet(BOB).unwrap().is_empty());
+
+        let garbage = envelope(3);
+        let malformed = ReceiptBundle {
+            version: BUNDLE_VERSION,
+            entries: vec![entry(
+                StoredProof {
+                    proof_hash: hex::encode(crate::proof_hash(&garbage.journal)),
+                    ..claimed
+                },
+                &garbage,
+            )],
+        };
+        assert!(store.import_bundle(&malformed).is_err());
+    }
+
+    #[test]
+    fn test_relay_jobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let proof_hash = hex::encode([1u8; 32]);
+
+        let mut submit = RelayJob::new(&proof_hash, RelayStep::SubmitProof, Some(format!("0x{}", hex::encode([2u8; 32]))), 1000);
+        store.put_relay_job(&submit).unwrap();
+        submit.status = RelayStatus::Sent;
+        submit.tx_hash = Some(format!("0x{}", hex::encode([5u8; 32])));
+        // The original and its replacement under the same nonce
+        submit.tx_hashes = vec![format!("0x{}", hex::encode([3u8; 32])), format!("0x{}", hex::encode([5u8; 32]))];
+        submit.nonce = Some(4);
+        submit.attempts = 1;
+        store.put_relay_job(&submit).unwrap();
+
+        let mut link = RelayJob::new(&proof_hash, RelayStep::LinkSocialAccount, None, 1001);
+        link.status = RelayStatus::Confirmed;
+        link.block_number = Some(12);
+        link.gas_used = Some(180_000);
+        store.put_relay_job(&link).unwrap();
+
+        // Updates replace the job for the same step
+        assert_eq!(store.relay_job(&format!("0x{}", proof_hash), RelayStep::SubmitProof).unwrap(), Some(submit.clone()));
+        assert_eq!(store.relay_job(&proof_hash, RelayStep::ReverifyAccount).unwrap(), None);
+        assert_eq!(store.relay_jobs(&proof_hash).unwrap(), vec![submit.clone(), link]);
+        assert_eq!(store.relay_jobs_with_status(RelayStatus::Sent).unwrap(), vec![submit]);
+        assert!(store.relay_jobs_with_status(RelayStatus::Failed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_oracle_requests_and_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let verifier = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+        let request = OracleRequest {
+            request_id: hex::encode([1u8; 32]),
+            requester: ALICE.to_lowercase(),
+            wallet_address: BOB.to_lowercase(),
+            platform: SocialPlatform::Twitter,
+            oauth_token_hash: hex::encode([2u8; 32]),
+            requested_at: 1000,
+            block_number: 7,
+            status: OracleRequestStatus::Pending,
+            proof_hash: None,
+            error: None,
+            updated_at: 1000,
+        };
+
+        // Seeing the same log again after a restart leaves the request alone
+        assert!(store.add_oracle_request(&request).unwrap());
+        let fulfilled = OracleRequest {
+            status: OracleRequestStatus::Fulfilled,
+            proof_hash: Some(hex::encode([3u8; 32])),
+            updated_at: 1100,
+            ..request.clone()
+        };
+        store.put_oracle_request(&fulfilled).unwrap();
+        assert!(!store.add_oracle_request(&request).unwrap());
+        assert_eq!(store.oracle_request(&format!("0x{}", request.request_id)).unwrap(), Some(fulfilled.clone()));
+        assert!(store.oracle_requests_with_status(OracleRequestStatus::Pending).unwrap().is_empty());
+        assert_eq!(store.oracle_requests_with_status(OracleRequestStatus::Fulfilled).unwrap(), vec![fulfilled]);
+
+        assert_eq!(store.oracle_checkpoint(verifier).unwrap(), None);
+        store.set_oracle_checkpoint(verifier, 100).unwrap();
+        store.set_oracle_checkpoint(verifier, 200).unwrap();
+        assert_eq!(store.oracle_checkpoint(&verifier.to_lowercase()).unwrap(), Some(200));
+    }
+}
@@ -0,0 +1,385 @@
+// Web service endpoints for social verification
+
+use super::*;
+use crate::config::RateLimitConfig;
+use crate::oracle::deliver_token;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerificationRequest {
+    pub platform: String,
+    pub oauth_token: String,
+    pub wallet_address: String,
+    #[serde(default)]
+    pub nonce: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerificationResponse {
+    pub success: bool,
+    pub social_account_hash: Option<String>,
+    pub account_age: Option<u64>,
+    pub follower_count: Option<u64>,
+    pub proof_hash: Option<String>,
+    pub expires_at: Option<u64>,
+    pub receipt: Option<String>, // Base64 `ProofEnvelope`
+    pub error: Option<String>,
+}
+
+/// Body of `POST /sponsorship/simulate`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SponsorshipRequest {
+    pub wallet_address: String,
+    /// Base64 `ProofEnvelope`s; the store's latest per platform if empty
+    #[serde(default)]
+    pub receipts: Vec<String>,
+    /// Wei; with `gas_per_tx`, turns the allowance into gas units and transactions
+    pub max_fee_per_gas: Option<u64>,
+    pub gas_per_tx: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SponsorshipSimulation {
+    #[serde(flatten)]
+    pub decision: SponsorshipDecision,
+    pub daily_gas_allowance: u64, // Wei
+    pub daily_gas_units: Option<u64>,
+    pub daily_transactions: Option<u64>,
+}
+
+/// Token bucket per client IP: `burst` requests at once, refilled at
+/// `requests_per_minute`
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<IpAddr, (f64, Instant)>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `client`, or refuse if its bucket is empty
+    pub fn check(&self, client: IpAddr, now: Instant) -> bool {
+        if self.config.requests_per_minute == 0 {
+            return true;
+        }
+        let capacity = f64::from(self.config.burst);
+        let refill_per_sec = f64::from(self.config.requests_per_minute) / 60.0;
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = buckets.entry(client).or_insert((capacity, now));
+        *tokens = (*tokens + now.saturating_duration_since(*last).as_secs_f64() * refill_per_sec).min(capacity);
+        *last = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct VerificationServer {
+    service: Arc<SocialVerificationService>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    token_inbox: Option<PathBuf>,
+}
+
+impl VerificationServer {
+    pub fn new(domain: DeploymentDomain) -> Self {
+        Self::from_service(SocialVerificationService::new(domain))
+    }
+
+    pub fn from_service(service: SocialVerificationService) -> Self {
+        Self {
+            service: Arc::new(service),
+            rate_limiter: None,
+            token_inbox: None,
+        }
+    }
+
+    pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// Accept `POST /oracle/tokens` into `dir`, the oracle's token directory.
+    /// Requests are looked up in the service's receipt store
+    pub fn with_token_inbox(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.token_inbox = Some(dir.into());
+        self
+    }
+
+    /// Serve `POST /verify`, `POST /sponsorship/simulate`, `POST /oracle/tokens`
+    /// (with a token inbox) and `GET /health` until the process exits
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let server = self.clone();
+            let client = conn.remote_addr().ip();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.route(client, request).await) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr)?.serve(make_service).await?;
+        Ok(())
+    }
+
+    async fn route(&self, client: IpAddr, request: Request<Body>) -> Response<Body> {
+        match (request.method(), request.uri().path()) {
+            (&Method::GET, "/health") => json_response(StatusCode::OK, &serde_json::json!({ "status": "ok" })),
+            (&Method::POST, "/verify") => {
+                if !self.within_rate_limit(client) {
+                    return error_response(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string());
+                }
+                let body = match hyper::body::to_bytes(request.into_body()).await {
+                    Ok(body) => body,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                let request: VerificationRequest = match serde_json::from_slice(&body) {
+                    Ok(request) => request,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                let response = self.handle_verification_request(request).await;
+                let status = if response.success { StatusCode::OK } else { StatusCode::UNPROCESSABLE_ENTITY };
+                json_response(status, &response)
+            }
+            (&Method::POST, "/sponsorship/simulate") => {
+                if !self.within_rate_limit(client) {
+                    return error_response(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string());
+                }
+                let body = match hyper::body::to_bytes(request.into_body()).await {
+                    Ok(body) => body,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                let request: SponsorshipRequest = match serde_json::from_slice(&body) {
+                    Ok(request) => request,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                match self.simulate_sponsorship(request) {
+                    Ok(simulation) => json_response(StatusCode::OK, &simulation),
+                    Err(e) => error_response(StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+                }
+            }
+            (&Method::POST, "/oracle/tokens") if self.token_inbox.is_some() => {
+                if !self.within_rate_limit(client) {
+                    return error_response(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded".to_string());
+                }
+                let body = match hyper::body::to_bytes(request.into_body()).await {
+                    Ok(body) => body,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                let delivery: TokenDelivery = match serde_json::from_slice(&body) {
+                    Ok(delivery) => delivery,
+                    Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+                };
+                match self.deliver_token(&delivery) {
+                    Ok(()) => json_response(
+                        StatusCode::ACCEPTED,
+                        &serde_json::json!({ "success": true, "request_id": delivery.request_id }),
+                    ),
+                    Err(e) => {
+                        let status = match e.downcast_ref::<TokenDeliveryError>() {
+                            Some(TokenDeliveryError::UnknownRequest(_)) => StatusCode::NOT_FOUND,
+                            Some(TokenDeliveryError::Settled(_)) => StatusCode::CONFLICT,
+                            Some(TokenDeliveryError::CommitmentMismatch) => StatusCode::FORBIDDEN,
+                            Some(_) => StatusCode::UNAUTHORIZED,
+                            None => StatusCode::INTERNAL_SERVER_ERROR,
+                        };
+                        error_response(status, e.to_string())
+                    }
+                }
+            }
+            _ => error_response(StatusCode::NOT_FOUND, "Not found".to_string()),
+        }
+    }
+
+    /// Check a token against the request it is for and leave it for the oracle
+    pub fn deliver_token(&self, delivery: &TokenDelivery) -> Result<()> {
+        let (Some(inbox), Some(store)) = (&self.token_inbox, &self.service.store) else {
+            return Err(anyhow::anyhow!("Token delivery needs a token inbox and a receipt store"));
+        };
+        let request = store.oracle_request(&delivery.request_id)?;
+        delivery.check(request.as_ref())?;
+        deliver_token(inbox, &delivery.request_id, &delivery.oauth_token)
+    }
+
+    fn within_rate_limit(&self, client: IpAddr) -> bool {
+        match &self.rate_limiter {
+            Some(rate_limiter) => rate_limiter.check(client, Instant::now()),
+            None => true,
+        }
+    }
+
+    /// The tier `request.wallet_address` would get and what it allows per day
+    pub fn simulate_sponsorship(&self, request: SponsorshipRequest) -> Result<SponsorshipSimulation> {
+        let envelopes = request
+            .receipts
+            .iter()
+            .map(|receipt| ProofEnvelope::from_base64(receipt))
+            .collect::<Result<Vec<_>>>()?;
+        let decision = self.service.sponsorship(&request.wallet_address, &envelopes)?;
+
+        let daily_gas_units = request.max_fee_per_gas.map(|fee| decision.daily_gas_units(fee));
+        let daily_transactions = match (request.gas_per_tx, request.max_fee_per_gas) {
+            (Some(gas), Some(fee)) => Some(decision.daily_transactions(gas, fee)),
+            _ => None,
+        };
+        Ok(SponsorshipSimulation {
+            daily_gas_allowance: decision.policy.daily_gas_limit,
+            decision,
+            daily_gas_units,
+            daily_transactions,
+        })
+    }
+
+    pub async fn handle_verification_request(
+        &self,
+        request: VerificationRequest,
+    ) -> VerificationResponse {
+        let platform = match parse_platform(&request.platform) {
+            Some(platform) => platform,
+            None => {
+                return VerificationResponse {
+                    success: false,
+                    social_account_hash: None,
+                    account_age: None,
+                    follower_count: None,
+                    proof_hash: None,
+                    expires_at: None,
+                    receipt: None,
+                    error: Some("Unsupported platform".to_string()),
+                };
+            }
+        };
+
+        match self.service.verify_social_account(
+            platform,
+            request.oauth_token,
+            request.wallet_address,
+            request.nonce,
+        ).await {
+            Ok(result) => {
+                if result.verification_output.verification_success {
+                    VerificationResponse {
+                        success: true,
+                        social_account_hash: Some(hex::encode(result.verification_output.social_account_hash)),
+                        account_age: Some(result.verification_output.account_age),
+                        follower_count: Some(result.verification_output.follower_count),
+                        proof_hash: Some(hex::encode(result.proof_hash)),
+                        expires_at: Some(result.verification_output.expires_at),
+                        receipt: result.receipt.to_base64().ok(),
+                        error: None,
+                    }
+                } else {
+                    VerificationResponse {
+                        success: false,
+                        social_account_hash: None,
+                        account_age: None,
+                        follower_count: None,
+                        proof_hash: None,
+                        expires_at: None,
+                        receipt: None,
+                        error: Some("Social account verification failed".to_string()),
+                    }
+                }
+            }
+            Err(e) => VerificationResponse {
+                success: false,
+                social_account_hash: None,
+                account_age: None,
+                follower_count: None,
+                proof_hash: None,
+                expires_at: None,
+                receipt: None,
+                error: Some(format!("Verification error: {}", e)),
+            },
+        }
+    }
+}
+
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    let mut response = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error_response(status: StatusCode, error: String) -> Response<Body> {
+    json_response(status, &serde_json::json!({ "success": false, "error": error }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 60,
+            burst: 2,
+        });
+        let client: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.check(client, start));
+        assert!(limiter.check(client, start));
+        assert!(!limiter.check(client, start));
+        // Buckets are per client
+        assert!(limiter.check(other, start));
+        // One request per second refills
+        assert!(limiter.check(client, start + Duration::from_secs(1)));
+        assert!(!limiter.check(client, start + Duration::from_secs(1)));
+
+        let unlimited = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 0,
+            burst: 0,
+        });
+        assert!((0..100).all(|_| unlimited.check(client, start)));
+    }
+
+    #[test]
+    fn test_simulate_sponsorship_without_verifications() {
+        let server = VerificationServer::new(DeploymentDomain::new(11155111, "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8"));
+        let simulation = server
+            .simulate_sponsorship(SponsorshipRequest {
+                wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+                receipts: Vec::new(),
+                max_fee_per_gas: Some(10_000_000_000),
+                gas_per_tx: Some(100_000),
+            })
+            .unwrap();
+        assert_eq!(simulation.decision.tier_id, 0);
+        assert_eq!(simulation.daily_gas_allowance, 100_000_000_000_000_000);
+        assert_eq!(simulation.daily_gas_units, Some(10_000_000));
+        assert_eq!(simulation.daily_transactions, Some(50));
+
+        let malformed = server.simulate_sponsorship(SponsorshipRequest {
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            receipts: vec!["not base64".to_string()],
+            max_fee_per_gas: None,
+            gas_per_tx: None,
+        });
+        assert!(malformed.is_err());
+    }
+}
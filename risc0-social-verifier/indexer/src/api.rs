@@ -0,0 +1,143 @@
+// Query API
+// Read-only JSON over the event store for dashboards:
+//   GET /health                         checkpoint, event count, registry verifier
+//   GET /wallets/<address>              linked accounts and activity
+//   GET /wallets/<address>/history      links and unlinks
+//   GET /accounts/<hash>/history        links and unlinks of a social account
+//   GET /platforms                      per-platform counts and config
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::store::EventStore;
+
+#[derive(Clone)]
+pub struct QueryServer {
+    store: Arc<EventStore>,
+}
+
+impl QueryServer {
+    pub fn new(store: Arc<EventStore>) -> Self {
+        Self { store }
+    }
+
+    /// Serve until the process exits
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let make_service = make_service_fn(move |_| {
+            let server = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.route(&request)) }
+                }))
+            }
+        });
+
+        Server::try_bind(&addr)?.serve(make_service).await?;
+        Ok(())
+    }
+
+    fn route(&self, request: &Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET {
+            return error_response(StatusCode::METHOD_NOT_ALLOWED, "Only GET is supported".to_string());
+        }
+        let segments: Vec<&str> = request.uri().path().trim_matches('/').split('/').collect();
+        let result = match segments.as_slice() {
+            ["health"] => self.store.status().map(|status| json_response(StatusCode::OK, &status)),
+            ["wallets", wallet] => self.store.wallet_status(wallet).map(|status| json_response(StatusCode::OK, &status)),
+            ["wallets", wallet, "history"] => self
+                .store
+                .link_history(Some(*wallet), None)
+                .map(|history| json_response(StatusCode::OK, &history)),
+            ["accounts", account, "history"] => self
+                .store
+                .link_history(None, Some(*account))
+                .map(|history| json_response(StatusCode::OK, &history)),
+            ["platforms"] => self.store.platform_stats().map(|stats| json_response(StatusCode::OK, &stats)),
+            _ => Ok(error_response(StatusCode::NOT_FOUND, "Not found".to_string())),
+        };
+        result.unwrap_or_else(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))
+    }
+}
+
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    let mut response = Response::new(Body::from(serde_json::to_vec(body).unwrap_or_default()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("application/json"));
+    response
+}
+
+fn error_response(status: StatusCode, error: String) -> Response<Body> {
+    json_response(status, &serde_json::json!({ "success": false, "error": error }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{ContractEvent, IndexedEvent};
+    use crate::store::Checkpoint;
+    use risc0_social_verifier::SocialPlatform;
+
+    const WALLET: &str = "0x1234567890123456789012345678901234567890";
+
+    fn get(server: &QueryServer, path: &str) -> (StatusCode, serde_json::Value) {
+        let response = server.route(&Request::get(path).body(Body::empty()).unwrap());
+        let status = response.status();
+        let body = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(hyper::body::to_bytes(response.into_body()))
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[test]
+    fn test_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(EventStore::open(dir.path().join("indexer.sqlite3")).unwrap());
+        store
+            .commit(
+                &[IndexedEvent {
+                    block_number: 7,
+                    block_hash: hex::encode([7u8; 32]),
+                    transaction_hash: hex::encode([8u8; 32]),
+                    log_index: 0,
+                    contract: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+                    event: ContractEvent::SocialAccountLinked {
+                        social_account_hash: hex::encode([1u8; 32]),
+                        wallet_address: WALLET.to_string(),
+                        platform: SocialPlatform::Discord,
+                        timestamp: 1_700_000_000,
+                    },
+                }],
+                &Checkpoint {
+                    block_number: 7,
+                    block_hash: hex::encode([7u8; 32]),
+                },
+            )
+            .unwrap();
+        let server = QueryServer::new(store);
+
+        let (status, health) = get(&server, "/health");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["checkpoint"]["block_number"], 7);
+
+        let (_, wallet) = get(&server, &format!("/wallets/{}", WALLET));
+        assert_eq!(wallet["linked_accounts"][0]["platform"], "Discord");
+
+        let (_, history) = get(&server, &format!("/accounts/0x{}/history", hex::encode([1u8; 32])));
+        assert_eq!(history[0]["event"], "social_account_linked");
+        assert_eq!(history[0]["wallet_address"], WALLET);
+
+        let (_, platforms) = get(&server, "/platforms");
+        assert_eq!(platforms[0]["linked_accounts"], 1);
+
+        assert_eq!(get(&server, "/wallets").0, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,215 @@
+// Indexer configuration
+// Built-in defaults, overridden by a TOML file, overridden by environment
+// variables, validated once at startup. Contract and RPC variables share their
+// names with the host's so one environment can drive both.
+
+use alloy::primitives::Address;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    pub rpc_url: Option<String>,
+    /// SQLite file the events are kept in
+    pub database: PathBuf,
+    /// `SocialAccountRegistry`: links, unlinks, platform configs, verifier changes
+    pub registry_contract: Option<String>,
+    /// `RiscZeroSocialVerifier`: `ProofVerified`
+    pub verifier_contract: Option<String>,
+    /// First block to scan when the store has no checkpoint yet, usually the
+    /// deployment block
+    pub start_block: u64,
+    /// Blocks behind the head a block must be before it is indexed. A reorg
+    /// deeper than this is rolled back when the checkpoint's hash changes
+    pub confirmations: u64,
+    /// Most blocks read per `eth_getLogs` call
+    pub max_block_range: u64,
+    pub poll_interval_secs: u64,
+    /// Where `run --serve` and `serve` answer queries
+    pub listen: SocketAddr,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: None,
+            database: PathBuf::from("data/indexer.sqlite3"),
+            registry_contract: None,
+            verifier_contract: None,
+            start_block: 0,
+            confirmations: 12,
+            max_block_range: 1000,
+            poll_interval_secs: 12,
+            listen: SocketAddr::from(([127, 0, 0, 1], 8090)),
+        }
+    }
+}
+
+impl IndexerConfig {
+    /// Defaults, then `path` if given, then the environment
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .map_err(|e| anyhow!("Cannot read config {}: {}", path.display(), e))?;
+                Self::from_toml(&contents).map_err(|e| anyhow!("{}: {}", path.display(), e))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|name| std::env::var(name).ok())?;
+        Ok(config)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Apply `RPC_URL`, `VERIFYING_CONTRACT` (the registry), `VERIFIER_CONTRACT`,
+    /// `INDEXER_DATABASE`, `INDEXER_START_BLOCK` and `INDEXER_LISTEN`, reading
+    /// variables through `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        let parse_error = |name: &str, value: &str, e: &dyn std::fmt::Display| anyhow!("{}={}: {}", name, value, e);
+
+        if let Some(rpc_url) = var("RPC_URL") {
+            self.rpc_url = Some(rpc_url);
+        }
+        if let Some(registry) = var("VERIFYING_CONTRACT") {
+            self.registry_contract = Some(registry);
+        }
+        if let Some(verifier) = var("VERIFIER_CONTRACT") {
+            self.verifier_contract = Some(verifier);
+        }
+        if let Some(database) = var("INDEXER_DATABASE") {
+            self.database = PathBuf::from(database);
+        }
+        if let Some(start_block) = var("INDEXER_START_BLOCK") {
+            self.start_block = start_block
+                .parse()
+                .map_err(|e| parse_error("INDEXER_START_BLOCK", &start_block, &e))?;
+        }
+        if let Some(listen) = var("INDEXER_LISTEN") {
+            self.listen = listen.parse().map_err(|e| parse_error("INDEXER_LISTEN", &listen, &e))?;
+        }
+        Ok(())
+    }
+
+    /// Check every value, reporting all problems at once
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if let Some(url) = &self.rpc_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(format!("rpc_url must be an http(s) URL, got {}", url));
+            }
+        }
+        for (key, address) in [
+            ("registry_contract", &self.registry_contract),
+            ("verifier_contract", &self.verifier_contract),
+        ] {
+            if let Some(address) = address {
+                if address.parse::<Address>().is_err() {
+                    problems.push(format!("{} is not a 20-byte hex address: {}", key, address));
+                }
+            }
+        }
+        if self.max_block_range == 0 {
+            problems.push("max_block_range must be at least 1".to_string());
+        }
+        if self.poll_interval_secs == 0 {
+            problems.push("poll_interval_secs must be greater than 0".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid configuration:\n  - {}", problems.join("\n  - ")))
+        }
+    }
+
+    /// The RPC endpoint indexing reads from. Queries do not need one
+    pub fn rpc_url(&self) -> Result<&str> {
+        self.rpc_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("rpc_url (or RPC_URL) must be set to index"))
+    }
+
+    /// Every configured contract; at least one must be set to index
+    pub fn contracts(&self) -> Result<Vec<Address>> {
+        let contracts = [&self.registry_contract, &self.verifier_contract]
+            .into_iter()
+            .flatten()
+            .map(|address| address.parse::<Address>().map_err(|e| anyhow!("{}: {}", address, e)))
+            .collect::<Result<Vec<_>>>()?;
+        if contracts.is_empty() {
+            return Err(anyhow!("Set registry_contract or verifier_contract (or VERIFYING_CONTRACT / VERIFIER_CONTRACT)"));
+        }
+        Ok(contracts)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut config = IndexerConfig::from_toml(
+            r#"
+            rpc_url = "http://127.0.0.1:8545"
+            start_block = 100
+            confirmations = 2
+            "#,
+        )
+        .unwrap();
+        config
+            .apply_env(env(&[("VERIFYING_CONTRACT", REGISTRY), ("INDEXER_START_BLOCK", "200")]))
+            .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.start_block, 200);
+        assert_eq!(config.confirmations, 2);
+        assert_eq!(config.contracts().unwrap(), vec![REGISTRY.parse::<Address>().unwrap()]);
+
+        assert!(config.apply_env(env(&[("INDEXER_START_BLOCK", "soon")])).is_err());
+        assert!(IndexerConfig::default().contracts().is_err());
+        assert!(IndexerConfig::default().rpc_url().is_err());
+        assert!(IndexerConfig::from_toml("confirmation = 2").is_err());
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let config = IndexerConfig::from_toml(
+            r#"
+            rpc_url = "ws://127.0.0.1:8545"
+            registry_contract = "0x1234"
+            max_block_range = 0
+            poll_interval_secs = 0
+            "#,
+        )
+        .unwrap();
+        let error = config.validate().unwrap_err().to_string();
+        for key in ["rpc_url", "registry_contract", "max_block_range", "poll_interval_secs"] {
+            assert!(error.contains(key), "{} not reported in {}", key, error);
+        }
+    }
+
+    #[test]
+    fn test_example_config() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.toml");
+        let config = IndexerConfig::from_toml(&std::fs::read_to_string(path).unwrap()).unwrap();
+        config.validate().unwrap();
+    }
+}
@@ -0,0 +1,317 @@
+// Contract events
+// The registry and verifier events the indexer keeps, decoded from raw logs.
+// Every field is a static ABI type, so each event is its indexed topics plus
+// fixed 32-byte words of data.
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::rpc::types::Log;
+use anyhow::{anyhow, Result};
+use risc0_social_verifier::SocialPlatform;
+use serde::{Deserialize, Serialize};
+
+/// `SocialAccountRegistry.SocialAccountLinked(bytes32,address,uint8,uint256)`
+pub fn social_account_linked_topic() -> B256 {
+    keccak256("SocialAccountLinked(bytes32,address,uint8,uint256)")
+}
+
+/// `SocialAccountRegistry.SocialAccountUnlinked(bytes32,address,uint8)`
+pub fn social_account_unlinked_topic() -> B256 {
+    keccak256("SocialAccountUnlinked(bytes32,address,uint8)")
+}
+
+/// `RiscZeroSocialVerifier.ProofVerified(bytes32,address,uint8,bool)`
+pub fn proof_verified_topic() -> B256 {
+    keccak256("ProofVerified(bytes32,address,uint8,bool)")
+}
+
+/// `SocialAccountRegistry.PlatformConfigUpdated(uint8,(bool,uint256,uint256,uint256,bool))`
+pub fn platform_config_updated_topic() -> B256 {
+    keccak256("PlatformConfigUpdated(uint8,(bool,uint256,uint256,uint256,bool))")
+}
+
+/// `SocialAccountRegistry.VerifierUpdated(address,address)`
+pub fn verifier_updated_topic() -> B256 {
+    keccak256("VerifierUpdated(address,address)")
+}
+
+/// Every topic the indexer asks the node for
+pub fn indexed_topics() -> Vec<B256> {
+    vec![
+        social_account_linked_topic(),
+        social_account_unlinked_topic(),
+        proof_verified_topic(),
+        platform_config_updated_topic(),
+        verifier_updated_topic(),
+    ]
+}
+
+/// `SocialAccountRegistry.PlatformConfig`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlatformConfig {
+    pub is_enabled: bool,
+    pub minimum_account_age: u64, // Seconds
+    pub minimum_followers: u64,
+    pub verification_cooldown: u64, // Seconds
+    pub requires_additional_verification: bool,
+}
+
+/// A decoded event. Addresses are checksummed, hashes are hex without `0x`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ContractEvent {
+    SocialAccountLinked {
+        social_account_hash: String,
+        wallet_address: String,
+        platform: SocialPlatform,
+        timestamp: u64,
+    },
+    SocialAccountUnlinked {
+        social_account_hash: String,
+        wallet_address: String,
+        platform: SocialPlatform,
+    },
+    ProofVerified {
+        proof_hash: String,
+        requester: String,
+        platform: SocialPlatform,
+        success: bool,
+    },
+    PlatformConfigUpdated {
+        platform: SocialPlatform,
+        config: PlatformConfig,
+    },
+    VerifierUpdated {
+        old_verifier: String,
+        new_verifier: String,
+    },
+}
+
+impl ContractEvent {
+    /// Event name in snake case, as stored
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::SocialAccountLinked { .. } => "social_account_linked",
+            Self::SocialAccountUnlinked { .. } => "social_account_unlinked",
+            Self::ProofVerified { .. } => "proof_verified",
+            Self::PlatformConfigUpdated { .. } => "platform_config_updated",
+            Self::VerifierUpdated { .. } => "verifier_updated",
+        }
+    }
+
+    /// The wallet the event is about: the linked wallet, or a proof's requester
+    pub fn wallet_address(&self) -> Option<&str> {
+        match self {
+            Self::SocialAccountLinked { wallet_address, .. } | Self::SocialAccountUnlinked { wallet_address, .. } => {
+                Some(wallet_address)
+            }
+            Self::ProofVerified { requester, .. } => Some(requester),
+            _ => None,
+        }
+    }
+
+    pub fn social_account_hash(&self) -> Option<&str> {
+        match self {
+            Self::SocialAccountLinked { social_account_hash, .. }
+            | Self::SocialAccountUnlinked { social_account_hash, .. } => Some(social_account_hash),
+            _ => None,
+        }
+    }
+
+    pub fn platform(&self) -> Option<SocialPlatform> {
+        match self {
+            Self::SocialAccountLinked { platform, .. }
+            | Self::SocialAccountUnlinked { platform, .. }
+            | Self::ProofVerified { platform, .. }
+            | Self::PlatformConfigUpdated { platform, .. } => Some(*platform),
+            Self::VerifierUpdated { .. } => None,
+        }
+    }
+}
+
+/// An event and where it happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    pub block_number: u64,
+    pub block_hash: String,
+    pub transaction_hash: String,
+    pub log_index: u64,
+    pub contract: String,
+    #[serde(flatten)]
+    pub event: ContractEvent,
+}
+
+/// Decode a log, or `None` if it is not one of `indexed_topics`
+pub fn decode_log(log: &Log) -> Result<Option<IndexedEvent>> {
+    let Some(event) = decode_event(log.topics(), &log.data().data)? else {
+        return Ok(None);
+    };
+    let missing = |field: &str| anyhow!("Log {:?} has no {}; only mined logs can be indexed", log, field);
+    Ok(Some(IndexedEvent {
+        block_number: log.block_number.ok_or_else(|| missing("block number"))?,
+        block_hash: hex::encode(log.block_hash.ok_or_else(|| missing("block hash"))?),
+        transaction_hash: hex::encode(log.transaction_hash.ok_or_else(|| missing("transaction hash"))?),
+        log_index: log.log_index.ok_or_else(|| missing("log index"))?,
+        contract: log.address().to_string(),
+        event,
+    }))
+}
+
+fn decode_event(topics: &[B256], data: &[u8]) -> Result<Option<ContractEvent>> {
+    let Some(signature) = topics.first() else {
+        return Ok(None);
+    };
+    let word = |index: usize| -> Result<U256> {
+        data.get(index * 32..(index + 1) * 32)
+            .map(U256::from_be_slice)
+            .ok_or_else(|| anyhow!("Event {} has {} bytes of data", signature, data.len()))
+    };
+    let topic = |index: usize| -> Result<B256> {
+        topics
+            .get(index)
+            .copied()
+            .ok_or_else(|| anyhow!("Event {} has {} topics", signature, topics.len()))
+    };
+    let address = |index: usize| -> Result<String> { Ok(Address::from_word(topic(index)?).to_string()) };
+
+    let event = if *signature == social_account_linked_topic() {
+        ContractEvent::SocialAccountLinked {
+            social_account_hash: hex::encode(topic(1)?),
+            wallet_address: address(2)?,
+            platform: platform(word(0)?)?,
+            timestamp: uint(word(1)?, "timestamp")?,
+        }
+    } else if *signature == social_account_unlinked_topic() {
+        ContractEvent::SocialAccountUnlinked {
+            social_account_hash: hex::encode(topic(1)?),
+            wallet_address: address(2)?,
+            platform: platform(word(0)?)?,
+        }
+    } else if *signature == proof_verified_topic() {
+        ContractEvent::ProofVerified {
+            proof_hash: hex::encode(topic(1)?),
+            requester: address(2)?,
+            platform: platform(word(0)?)?,
+            success: !word(1)?.is_zero(),
+        }
+    } else if *signature == platform_config_updated_topic() {
+        ContractEvent::PlatformConfigUpdated {
+            platform: platform(word(0)?)?,
+            config: PlatformConfig {
+                is_enabled: !word(1)?.is_zero(),
+                minimum_account_age: uint(word(2)?, "minimumAccountAge")?,
+                minimum_followers: uint(word(3)?, "minimumFollowers")?,
+                verification_cooldown: uint(word(4)?, "verificationCooldown")?,
+                requires_additional_verification: !word(5)?.is_zero(),
+            },
+        }
+    } else if *signature == verifier_updated_topic() {
+        ContractEvent::VerifierUpdated {
+            old_verifier: address(1)?,
+            new_verifier: address(2)?,
+        }
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(event))
+}
+
+fn uint(value: U256, field: &str) -> Result<u64> {
+    u64::try_from(value).map_err(|_| anyhow!("{} does not fit in u64: {}", field, value))
+}
+
+/// Solidity enums are uint8 in the ABI, in declaration order
+fn platform(value: U256) -> Result<SocialPlatform> {
+    match u8::try_from(value) {
+        Ok(0) => Ok(SocialPlatform::Twitter),
+        Ok(1) => Ok(SocialPlatform::Discord),
+        Ok(2) => Ok(SocialPlatform::Github),
+        Ok(3) => Ok(SocialPlatform::Telegram),
+        Ok(4) => Ok(SocialPlatform::LinkedIn),
+        _ => Err(anyhow!("Unknown platform: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Bytes, LogData};
+
+    fn log(topics: Vec<B256>, words: &[U256]) -> Log {
+        let data: Vec<u8> = words.iter().flat_map(|word| word.to_be_bytes::<32>()).collect();
+        Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(9),
+                data: LogData::new_unchecked(topics, Bytes::from(data)),
+            },
+            block_hash: Some(B256::repeat_byte(7)),
+            block_number: Some(42),
+            transaction_hash: Some(B256::repeat_byte(8)),
+            log_index: Some(3),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_log() {
+        let hash = B256::repeat_byte(1);
+        let wallet = Address::repeat_byte(2);
+
+        let linked = decode_log(&log(
+            vec![social_account_linked_topic(), hash, wallet.into_word()],
+            &[U256::from(2u8), U256::from(1_700_000_000u64)],
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(linked.block_number, 42);
+        assert_eq!(linked.block_hash, hex::encode(B256::repeat_byte(7)));
+        assert_eq!(linked.log_index, 3);
+        assert_eq!(linked.contract, Address::repeat_byte(9).to_string());
+        assert_eq!(
+            linked.event,
+            ContractEvent::SocialAccountLinked {
+                social_account_hash: hex::encode(hash),
+                wallet_address: wallet.to_string(),
+                platform: SocialPlatform::Github,
+                timestamp: 1_700_000_000,
+            }
+        );
+
+        let updated = decode_log(&log(
+            vec![platform_config_updated_topic()],
+            &[U256::from(0u8), U256::from(1u8), U256::from(86_400u64), U256::from(50u8), U256::from(3_600u64), U256::ZERO],
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            updated.event,
+            ContractEvent::PlatformConfigUpdated {
+                platform: SocialPlatform::Twitter,
+                config: PlatformConfig {
+                    is_enabled: true,
+                    minimum_account_age: 86_400,
+                    minimum_followers: 50,
+                    verification_cooldown: 3_600,
+                    requires_additional_verification: false,
+                },
+            }
+        );
+
+        let verifier = decode_log(&log(
+            vec![verifier_updated_topic(), Address::ZERO.into_word(), wallet.into_word()],
+            &[],
+        ))
+        .unwrap()
+        .unwrap();
+        assert_eq!(verifier.event.kind(), "verifier_updated");
+        assert_eq!(verifier.event.platform(), None);
+
+        // Other events are skipped; malformed ones are errors
+        assert_eq!(decode_log(&log(vec![B256::repeat_byte(5)], &[])).unwrap(), None);
+        assert!(decode_log(&log(vec![social_account_unlinked_topic(), hash, wallet.into_word()], &[])).is_err());
+        assert!(decode_log(&log(
+            vec![proof_verified_topic(), hash, wallet.into_word()],
+            &[U256::from(9u8), U256::from(1u8)]
+        ))
+        .is_err());
+    }
+}
@@ -0,0 +1,135 @@
+// Event indexer
+// Reads the contracts' logs one block range at a time, once blocks are
+// `confirmations` deep, and commits each range with its last block's hash.
+// Before the next range the checkpoint's hash is checked against the chain: if
+// it changed, a reorg went deeper than the confirmation depth, so the store is
+// rewound to an earlier checkpoint and that stretch is read again.
+
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::rpc::types::Filter;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::IndexerConfig;
+use crate::events::{decode_log, indexed_topics};
+use crate::store::{Checkpoint, EventStore};
+
+/// A checkpoint that was no longer on the chain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reorg {
+    /// The checkpoint whose hash changed
+    pub block_number: u64,
+    /// Checkpoint indexing resumes after; `None` means from `start_block`
+    pub rewound_to: Option<u64>,
+    pub dropped_events: usize,
+}
+
+/// What one `poll` saw and did
+#[derive(Debug, Default, Serialize)]
+pub struct PollReport {
+    pub head: u64,
+    /// Blocks indexed, if any were far enough behind the head
+    pub scanned: Option<(u64, u64)>,
+    pub events: usize,
+    pub reorg: Option<Reorg>,
+}
+
+pub struct Indexer {
+    provider: DynProvider,
+    store: Arc<EventStore>,
+    contracts: Vec<Address>,
+    config: IndexerConfig,
+}
+
+impl Indexer {
+    pub fn connect(store: Arc<EventStore>, config: IndexerConfig) -> Result<Self> {
+        let provider = ProviderBuilder::new().connect_http(config.rpc_url()?.parse()?).erased();
+        Ok(Self {
+            provider,
+            store,
+            contracts: config.contracts()?,
+            config,
+        })
+    }
+
+    /// Check the checkpoint is still canonical, then index the next range of
+    /// confirmed blocks
+    pub async fn poll(&self) -> Result<PollReport> {
+        let mut report = PollReport {
+            head: self.provider.get_block_number().await?,
+            ..PollReport::default()
+        };
+
+        let checkpoint = self.store.checkpoint()?;
+        if let Some(checkpoint) = &checkpoint {
+            // A missing block means the chain got shorter than the checkpoint
+            if self.block_hash(checkpoint.block_number).await?.as_ref() != Some(&checkpoint.block_hash) {
+                report.reorg = Some(self.rewind(checkpoint).await?);
+                return Ok(report);
+            }
+        }
+
+        let safe_block = report.head.saturating_sub(self.config.confirmations);
+        let from = match &checkpoint {
+            Some(checkpoint) => checkpoint.block_number + 1,
+            None => self.config.start_block,
+        };
+        if from > safe_block {
+            return Ok(report);
+        }
+        let to = safe_block.min(from.saturating_add(self.config.max_block_range - 1));
+
+        let to_hash = self.block_hash(to).await?.ok_or_else(|| anyhow!("Block {} not found", to))?;
+        let filter = Filter::new()
+            .address(self.contracts.clone())
+            .event_signature(indexed_topics())
+            .from_block(from)
+            .to_block(to);
+        let logs = self.provider.get_logs(&filter).await?;
+        // The range moved under us; read it again on the next poll
+        if self.block_hash(to).await?.as_ref() != Some(&to_hash) {
+            return Ok(report);
+        }
+
+        let mut events = Vec::new();
+        for log in logs.iter().filter(|log| !log.removed) {
+            events.extend(decode_log(log)?);
+        }
+        self.store.commit(
+            &events,
+            &Checkpoint {
+                block_number: to,
+                block_hash: to_hash,
+            },
+        )?;
+        report.scanned = Some((from, to));
+        report.events = events.len();
+        Ok(report)
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        self.config.poll_interval()
+    }
+
+    /// Go back to a checkpoint at least `confirmations` blocks before
+    /// `checkpoint`. Its hash is checked on the next poll, so a reorg that
+    /// reaches past it is rolled back further
+    async fn rewind(&self, checkpoint: &Checkpoint) -> Result<Reorg> {
+        let keep_through = checkpoint.block_number.checked_sub(self.config.confirmations.max(1));
+        let dropped_events = self.store.rewind(keep_through)?;
+        Ok(Reorg {
+            block_number: checkpoint.block_number,
+            rewound_to: self.store.checkpoint()?.map(|checkpoint| checkpoint.block_number),
+            dropped_events,
+        })
+    }
+
+    /// Hex hash without `0x`, as the store keeps it
+    async fn block_hash(&self, block_number: u64) -> Result<Option<String>> {
+        let block = self.provider.get_block_by_number(block_number.into()).await?;
+        Ok(block.map(|block| hex::encode(block.header.hash)))
+    }
+}
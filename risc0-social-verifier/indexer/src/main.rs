@@ -0,0 +1,155 @@
+// Event indexer for the social verification contracts
+// Follows `SocialAccountRegistry` and `RiscZeroSocialVerifier` into a local
+// SQLite database and answers "who linked what and when" from it. Every
+// command prints JSON.
+
+use anyhow::Result;
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+mod api;
+mod config;
+mod events;
+mod indexer;
+mod store;
+
+use api::QueryServer;
+use config::IndexerConfig;
+use indexer::Indexer;
+use store::EventStore;
+
+#[derive(Debug, Parser)]
+#[command(name = "indexer", version, about = "Index social verification events into SQLite")]
+struct Cli {
+    /// TOML config file; environment variables and flags override it
+    #[arg(long, global = true, env = "INDEXER_CONFIG", value_name = "PATH")]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Index confirmed blocks, polling for new ones until stopped
+    Run(RunArgs),
+    /// Answer queries over HTTP without indexing
+    Serve,
+    /// How far the index has got
+    Status,
+    /// Accounts linked to a wallet and its activity
+    Wallet { address: String },
+    /// Link, unlink and proof counts per platform
+    Platforms,
+    /// Links and unlinks, oldest first
+    History(HistoryArgs),
+}
+
+#[derive(Debug, Args)]
+struct RunArgs {
+    /// Index one range and exit
+    #[arg(long)]
+    once: bool,
+    /// Also answer queries at `listen` while indexing
+    #[arg(long)]
+    serve: bool,
+    #[arg(long)]
+    rpc_url: Option<String>,
+    /// First block to scan when the database has no checkpoint yet
+    #[arg(long)]
+    start_block: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+struct HistoryArgs {
+    #[arg(long)]
+    wallet: Option<String>,
+    /// Social account hash
+    #[arg(long)]
+    account: Option<String>,
+}
+
+fn load_config(path: Option<&Path>) -> Result<IndexerConfig> {
+    let config = IndexerConfig::load(path)?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn open_store(config: &IndexerConfig) -> Result<Arc<EventStore>> {
+    Ok(Arc::new(EventStore::open(&config.database)?))
+}
+
+fn print(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+async fn run(config: Option<&Path>, args: &RunArgs) -> Result<()> {
+    let mut config = IndexerConfig::load(config)?;
+    config.apply_env(|name| match name {
+        "RPC_URL" => args.rpc_url.clone(),
+        "INDEXER_START_BLOCK" => args.start_block.map(|block| block.to_string()),
+        _ => None,
+    })?;
+    config.validate()?;
+    let store = open_store(&config)?;
+    if args.serve {
+        let server = QueryServer::new(store.clone());
+        let listen = config.listen;
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(listen).await {
+                eprintln!("Error: query API on {}: {:#}", listen, e);
+            }
+        });
+        eprintln!("Listening on http://{}", listen);
+    }
+    let indexer = Indexer::connect(store, config)?;
+
+    if args.once {
+        return print(&indexer.poll().await?);
+    }
+    loop {
+        // A failed poll is retried on the next tick; nothing was committed
+        match indexer.poll().await {
+            // Keep polling without a pause until caught up
+            Ok(report) if report.scanned.is_some() || report.reorg.is_some() => {
+                println!("{}", serde_json::to_string(&report)?);
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+        tokio::time::sleep(indexer.poll_interval()).await;
+    }
+}
+
+async fn dispatch(cli: Cli) -> Result<()> {
+    let path = cli.config.as_deref();
+    match cli.command {
+        Command::Run(args) => run(path, &args).await,
+        Command::Serve => {
+            let config = load_config(path)?;
+            eprintln!("Listening on http://{}", config.listen);
+            QueryServer::new(open_store(&config)?).serve(config.listen).await
+        }
+        Command::Status => print(&open_store(&load_config(path)?)?.status()?),
+        Command::Wallet { address } => print(&open_store(&load_config(path)?)?.wallet_status(&address)?),
+        Command::Platforms => print(&open_store(&load_config(path)?)?.platform_stats()?),
+        Command::History(args) => print(
+            &open_store(&load_config(path)?)?.link_history(args.wallet.as_deref(), args.account.as_deref())?,
+        ),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match dispatch(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,498 @@
+// Event store
+// Indexed events live in one SQLite table keyed by block and log index, with
+// the columns the queries filter on pulled out of the JSON event. Each range
+// is committed with a checkpoint, its last block and that block's hash, in one
+// transaction, so the store never holds half a range. Recent checkpoints are
+// kept so a reorg can be rolled back to one whose hash can be checked again.
+
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::events::{ContractEvent, IndexedEvent, PlatformConfig};
+use risc0_social_verifier::SocialPlatform;
+
+const LINK_KINDS: &str = "('social_account_linked', 'social_account_unlinked')";
+
+/// Checkpoints kept to rewind to
+const CHECKPOINT_HISTORY: i64 = 256;
+
+/// The last block of an indexed range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub block_number: u64,
+    pub block_hash: String,
+}
+
+/// A social account currently linked to a wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkedAccount {
+    pub social_account_hash: String,
+    pub platform: SocialPlatform,
+    pub linked_at: u64, // Block timestamp of the link
+    pub block_number: u64,
+    pub transaction_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WalletStatus {
+    pub wallet_address: String,
+    pub linked_accounts: Vec<LinkedAccount>,
+    pub links: u64,
+    pub unlinks: u64,
+    /// `ProofVerified` events the wallet requested
+    pub proofs_verified: u64,
+    pub proofs_failed: u64,
+    pub last_block: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlatformStats {
+    pub platform: SocialPlatform,
+    /// Accounts whose latest event is a link
+    pub linked_accounts: u64,
+    pub links: u64,
+    pub unlinks: u64,
+    pub proofs_verified: u64,
+    pub proofs_failed: u64,
+    /// From the latest `PlatformConfigUpdated`
+    pub config: Option<PlatformConfig>,
+}
+
+impl PlatformStats {
+    fn new(platform: SocialPlatform) -> Self {
+        Self {
+            platform,
+            linked_accounts: 0,
+            links: 0,
+            unlinks: 0,
+            proofs_verified: 0,
+            proofs_failed: 0,
+            config: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexStatus {
+    pub checkpoint: Option<Checkpoint>,
+    pub events: u64,
+    /// `newVerifier` of the latest `VerifierUpdated`
+    pub registry_verifier: Option<String>,
+}
+
+pub struct EventStore {
+    db: Mutex<Connection>,
+}
+
+impl EventStore {
+    /// Open or create the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let db = Connection::open(path)?;
+        db.execute_batch(
+            "CREATE TABLE IF NOT EXISTS events (
+                block_number INTEGER NOT NULL,
+                log_index INTEGER NOT NULL,
+                block_hash TEXT NOT NULL,
+                transaction_hash TEXT NOT NULL,
+                contract TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                wallet_address TEXT,
+                social_account_hash TEXT,
+                platform TEXT,
+                event TEXT NOT NULL,
+                PRIMARY KEY (block_number, log_index)
+            );
+            CREATE INDEX IF NOT EXISTS events_wallet ON events (wallet_address, block_number);
+            CREATE INDEX IF NOT EXISTS events_social_account ON events (social_account_hash, block_number);
+            CREATE INDEX IF NOT EXISTS events_kind ON events (kind, platform);
+            CREATE TABLE IF NOT EXISTS checkpoints (
+                block_number INTEGER PRIMARY KEY,
+                block_hash TEXT NOT NULL
+            );",
+        )?;
+        Ok(Self { db: Mutex::new(db) })
+    }
+
+    /// Where indexing resumes after
+    pub fn checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let db = self.db()?;
+        let checkpoint = db
+            .query_row("SELECT * FROM checkpoints ORDER BY block_number DESC LIMIT 1", [], |row| {
+                Ok(Checkpoint {
+                    block_number: row.get::<_, i64>(0)? as u64,
+                    block_hash: row.get(1)?,
+                })
+            })
+            .optional()?;
+        Ok(checkpoint)
+    }
+
+    /// Record a range's events and checkpoint its last block. Events already
+    /// stored are replaced, so a range can be indexed twice
+    pub fn commit(&self, events: &[IndexedEvent], checkpoint: &Checkpoint) -> Result<()> {
+        let mut db = self.db()?;
+        let tx = db.transaction()?;
+        for event in events {
+            tx.execute(
+                "INSERT OR REPLACE INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    event.block_number as i64,
+                    event.log_index as i64,
+                    normalize_hash(&event.block_hash),
+                    normalize_hash(&event.transaction_hash),
+                    event.contract.to_lowercase(),
+                    event.event.kind(),
+                    event.event.wallet_address().map(str::to_lowercase),
+                    event.event.social_account_hash().map(normalize_hash),
+                    event.event.platform().map(|platform| serde_json::to_string(&platform)).transpose()?,
+                    serde_json::to_string(&event.event)?,
+                ],
+            )?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO checkpoints VALUES (?1, ?2)",
+            params![checkpoint.block_number as i64, normalize_hash(&checkpoint.block_hash)],
+        )?;
+        tx.execute(
+            "DELETE FROM checkpoints WHERE block_number NOT IN (
+                SELECT block_number FROM checkpoints ORDER BY block_number DESC LIMIT ?1
+            )",
+            params![CHECKPOINT_HISTORY],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Go back to the latest checkpoint at or before `keep_through`, or to
+    /// nothing, dropping every event after it. Returns how many were dropped
+    pub fn rewind(&self, keep_through: Option<u64>) -> Result<usize> {
+        let mut db = self.db()?;
+        let tx = db.transaction()?;
+        let keep_through = keep_through.map_or(-1, |block_number| block_number as i64);
+        tx.execute("DELETE FROM checkpoints WHERE block_number > ?1", params![keep_through])?;
+        let resume: i64 = tx.query_row("SELECT COALESCE(MAX(block_number), -1) FROM checkpoints", [], |row| row.get(0))?;
+        let dropped = tx.execute("DELETE FROM events WHERE block_number > ?1", params![resume])?;
+        tx.commit()?;
+        Ok(dropped)
+    }
+
+    pub fn status(&self) -> Result<IndexStatus> {
+        let checkpoint = self.checkpoint()?;
+        let db = self.db()?;
+        let events: i64 = db.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
+        let latest = db
+            .query_row(
+                "SELECT event FROM events WHERE kind = 'verifier_updated'
+                 ORDER BY block_number DESC, log_index DESC LIMIT 1",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        let registry_verifier = match latest.map(|json| serde_json::from_str(&json)).transpose()? {
+            Some(ContractEvent::VerifierUpdated { new_verifier, .. }) => Some(new_verifier),
+            _ => None,
+        };
+        Ok(IndexStatus {
+            checkpoint,
+            events: events as u64,
+            registry_verifier,
+        })
+    }
+
+    /// Accounts linked to `wallet` now, and what it has done so far
+    pub fn wallet_status(&self, wallet: &str) -> Result<WalletStatus> {
+        let events = self.query_events(
+            "SELECT * FROM events WHERE wallet_address = ?1 ORDER BY block_number, log_index",
+            params![wallet.to_lowercase()],
+        )?;
+        let mut status = WalletStatus {
+            wallet_address: wallet.to_string(),
+            linked_accounts: Vec::new(),
+            links: 0,
+            unlinks: 0,
+            proofs_verified: 0,
+            proofs_failed: 0,
+            last_block: events.last().map(|event| event.block_number),
+        };
+        for event in events {
+            match event.event {
+                ContractEvent::SocialAccountLinked { social_account_hash, platform, timestamp, .. } => {
+                    status.links += 1;
+                    status.linked_accounts.retain(|account| account.social_account_hash != social_account_hash);
+                    status.linked_accounts.push(LinkedAccount {
+                        social_account_hash,
+                        platform,
+                        linked_at: timestamp,
+                        block_number: event.block_number,
+                        transaction_hash: event.transaction_hash,
+                    });
+                }
+                ContractEvent::SocialAccountUnlinked { social_account_hash, .. } => {
+                    status.unlinks += 1;
+                    status.linked_accounts.retain(|account| account.social_account_hash != social_account_hash);
+                }
+                ContractEvent::ProofVerified { success: true, .. } => status.proofs_verified += 1,
+                ContractEvent::ProofVerified { success: false, .. } => status.proofs_failed += 1,
+                _ => {}
+            }
+        }
+        Ok(status)
+    }
+
+    /// Per-platform counts, in the contract's platform order
+    pub fn platform_stats(&self) -> Result<Vec<PlatformStats>> {
+        let db = self.db()?;
+        let mut stats: Vec<PlatformStats> = Vec::new();
+
+        let mut counts = db.prepare(
+            "SELECT platform, kind, json_extract(event, '$.success'), COUNT(*) FROM events
+             WHERE platform IS NOT NULL GROUP BY 1, 2, 3",
+        )?;
+        let rows = counts.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<bool>>(2)?, row.get::<_, i64>(3)?))
+        })?;
+        for row in rows {
+            let (platform, kind, success, count) = row?;
+            let entry = platform_entry(&mut stats, &platform)?;
+            let count = count as u64;
+            match (kind.as_str(), success) {
+                ("social_account_linked", _) => entry.links += count,
+                ("social_account_unlinked", _) => entry.unlinks += count,
+                ("proof_verified", Some(true)) => entry.proofs_verified += count,
+                ("proof_verified", _) => entry.proofs_failed += count,
+                _ => {}
+            }
+        }
+
+        // An account is linked if its latest link event is a link
+        let mut linked = db.prepare(&format!(
+            "SELECT platform, COUNT(*) FROM (
+                SELECT platform, kind, ROW_NUMBER() OVER (
+                    PARTITION BY social_account_hash ORDER BY block_number DESC, log_index DESC
+                ) AS latest
+                FROM events WHERE kind IN {}
+             ) WHERE latest = 1 AND kind = 'social_account_linked' GROUP BY platform",
+            LINK_KINDS
+        ))?;
+        let rows = linked.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (platform, count) = row?;
+            platform_entry(&mut stats, &platform)?.linked_accounts = count as u64;
+        }
+
+        let mut configs = db.prepare(
+            "SELECT event FROM events WHERE kind = 'platform_config_updated' ORDER BY block_number, log_index",
+        )?;
+        let rows = configs.query_map([], |row| row.get::<_, String>(0))?;
+        for row in rows {
+            if let ContractEvent::PlatformConfigUpdated { platform, config } = serde_json::from_str(&row?)? {
+                platform_entry(&mut stats, &serde_json::to_string(&platform)?)?.config = Some(config);
+            }
+        }
+
+        stats.sort_by_key(|stats| stats.platform as u8);
+        Ok(stats)
+    }
+
+    /// Links and unlinks, oldest first, for a wallet, a social account, both
+    /// or everything
+    pub fn link_history(&self, wallet: Option<&str>, social_account_hash: Option<&str>) -> Result<Vec<IndexedEvent>> {
+        self.query_events(
+            &format!(
+                "SELECT * FROM events WHERE kind IN {}
+                 AND (?1 IS NULL OR wallet_address = ?1)
+                 AND (?2 IS NULL OR social_account_hash = ?2)
+                 ORDER BY block_number, log_index",
+                LINK_KINDS
+            ),
+            params![wallet.map(str::to_lowercase), social_account_hash.map(normalize_hash)],
+        )
+    }
+
+    fn query_events(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<IndexedEvent>> {
+        let db = self.db()?;
+        let mut statement = db.prepare(sql)?;
+        let events = statement.query_map(params, indexed_event)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    fn db(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.db.lock().map_err(|_| anyhow!("Event store lock poisoned"))
+    }
+}
+
+/// The stats for a stored platform column, added if not seen yet
+fn platform_entry<'a>(stats: &'a mut Vec<PlatformStats>, platform: &str) -> Result<&'a mut PlatformStats> {
+    let platform: SocialPlatform = serde_json::from_str(platform)?;
+    let index = match stats.iter().position(|stats| stats.platform == platform) {
+        Some(index) => index,
+        None => {
+            stats.push(PlatformStats::new(platform));
+            stats.len() - 1
+        }
+    };
+    Ok(&mut stats[index])
+}
+
+fn normalize_hash(hash: &str) -> String {
+    hash.trim_start_matches("0x").to_lowercase()
+}
+
+fn indexed_event(row: &Row) -> rusqlite::Result<IndexedEvent> {
+    let json: String = row.get("event")?;
+    let event = serde_json::from_str(&json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))?;
+    Ok(IndexedEvent {
+        block_number: row.get::<_, i64>("block_number")? as u64,
+        block_hash: row.get("block_hash")?,
+        transaction_hash: row.get("transaction_hash")?,
+        log_index: row.get::<_, i64>("log_index")? as u64,
+        contract: row.get("contract")?,
+        event,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0x1234567890123456789012345678901234567890";
+    const OTHER: &str = "0x0987654321098765432109876543210987654321";
+
+    fn event(block_number: u64, log_index: u64, event: ContractEvent) -> IndexedEvent {
+        IndexedEvent {
+            block_number,
+            block_hash: format!("{:064x}", block_number),
+            transaction_hash: format!("{:064x}", block_number * 100 + log_index),
+            log_index,
+            contract: "0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string(),
+            event,
+        }
+    }
+
+    fn linked(account: u8, wallet: &str, platform: SocialPlatform, timestamp: u64) -> ContractEvent {
+        ContractEvent::SocialAccountLinked {
+            social_account_hash: hex::encode([account; 32]),
+            wallet_address: wallet.to_string(),
+            platform,
+            timestamp,
+        }
+    }
+
+    fn unlinked(account: u8, wallet: &str, platform: SocialPlatform) -> ContractEvent {
+        ContractEvent::SocialAccountUnlinked {
+            social_account_hash: hex::encode([account; 32]),
+            wallet_address: wallet.to_string(),
+            platform,
+        }
+    }
+
+    fn checkpoint(block_number: u64) -> Checkpoint {
+        Checkpoint {
+            block_number,
+            block_hash: format!("{:064x}", block_number),
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::open(dir.path().join("indexer.sqlite3")).unwrap();
+        assert_eq!(store.checkpoint().unwrap(), None);
+
+        let config = PlatformConfig {
+            is_enabled: true,
+            minimum_account_age: 86_400,
+            minimum_followers: 10,
+            verification_cooldown: 3_600,
+            requires_additional_verification: false,
+        };
+        store
+            .commit(
+                &[
+                    event(10, 0, ContractEvent::PlatformConfigUpdated { platform: SocialPlatform::Github, config: config.clone() }),
+                    event(11, 0, ContractEvent::ProofVerified {
+                        proof_hash: hex::encode([9u8; 32]),
+                        requester: WALLET.to_string(),
+                        platform: SocialPlatform::Github,
+                        success: true,
+                    }),
+                    event(11, 1, linked(1, WALLET, SocialPlatform::Github, 1_000)),
+                    event(12, 0, linked(2, WALLET, SocialPlatform::Twitter, 2_000)),
+                    event(13, 0, unlinked(1, WALLET, SocialPlatform::Github)),
+                    // Account 1 moves to another wallet
+                    event(14, 0, linked(1, OTHER, SocialPlatform::Github, 4_000)),
+                    event(15, 0, ContractEvent::VerifierUpdated {
+                        old_verifier: WALLET.to_string(),
+                        new_verifier: OTHER.to_string(),
+                    }),
+                ],
+                &checkpoint(15),
+            )
+            .unwrap();
+        assert_eq!(store.checkpoint().unwrap(), Some(checkpoint(15)));
+
+        // Addresses match whatever their case
+        let status = store.wallet_status(&WALLET.to_uppercase().replace("0X", "0x")).unwrap();
+        assert_eq!(status.links, 2);
+        assert_eq!(status.unlinks, 1);
+        assert_eq!(status.proofs_verified, 1);
+        assert_eq!(status.last_block, Some(13));
+        assert_eq!(status.linked_accounts.len(), 1);
+        assert_eq!(status.linked_accounts[0].platform, SocialPlatform::Twitter);
+        assert_eq!(status.linked_accounts[0].linked_at, 2_000);
+        let other = store.wallet_status(OTHER).unwrap();
+        assert_eq!(other.linked_accounts[0].social_account_hash, hex::encode([1u8; 32]));
+
+        let stats = store.platform_stats().unwrap();
+        let platforms: Vec<_> = stats.iter().map(|stats| stats.platform).collect();
+        assert_eq!(platforms, vec![SocialPlatform::Twitter, SocialPlatform::Github]);
+        assert_eq!(stats[1].linked_accounts, 1);
+        assert_eq!(stats[1].links, 2);
+        assert_eq!(stats[1].unlinks, 1);
+        assert_eq!(stats[1].proofs_verified, 1);
+        assert_eq!(stats[1].config, Some(config));
+        assert_eq!(stats[0].linked_accounts, 1);
+        assert_eq!(stats[0].config, None);
+
+        let history = store.link_history(None, Some(&format!("0x{}", hex::encode([1u8; 32])))).unwrap();
+        let blocks: Vec<_> = history.iter().map(|event| event.block_number).collect();
+        assert_eq!(blocks, vec![11, 13, 14]);
+        assert_eq!(store.link_history(Some(WALLET), None).unwrap().len(), 3);
+        assert_eq!(store.link_history(None, None).unwrap().len(), 4);
+
+        let status = store.status().unwrap();
+        assert_eq!(status.events, 7);
+        assert_eq!(status.registry_verifier.as_deref(), Some(OTHER));
+    }
+
+    #[test]
+    fn test_rewind() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::open(dir.path().join("indexer.sqlite3")).unwrap();
+        store.commit(&[event(5, 0, linked(1, WALLET, SocialPlatform::Github, 1_000))], &checkpoint(6)).unwrap();
+        store.commit(&[event(8, 0, unlinked(1, WALLET, SocialPlatform::Github))], &checkpoint(9)).unwrap();
+        store.commit(&[], &checkpoint(12)).unwrap();
+        assert!(store.wallet_status(WALLET).unwrap().linked_accounts.is_empty());
+
+        // The unlink was reorged away: back to the range ending at 6
+        assert_eq!(store.rewind(Some(8)).unwrap(), 1);
+        assert_eq!(store.checkpoint().unwrap(), Some(checkpoint(6)));
+        assert_eq!(store.wallet_status(WALLET).unwrap().linked_accounts.len(), 1);
+
+        // Indexing the same range again replaces rather than duplicates
+        store.commit(&[event(5, 0, linked(1, WALLET, SocialPlatform::Github, 1_000))], &checkpoint(6)).unwrap();
+        assert_eq!(store.status().unwrap().events, 1);
+
+        // Nothing at or before block 5 to go back to
+        assert_eq!(store.rewind(Some(5)).unwrap(), 1);
+        assert_eq!(store.checkpoint().unwrap(), None);
+    }
+}
@@ -0,0 +1,29 @@
+// Builds the guests for the zkVM and generates SOCIAL_VERIFIER_ELF / SOCIAL_VERIFIER_ID,
+// IDENTITY_BUNDLE_ELF / IDENTITY_BUNDLE_ID and AGGREGATOR_ELF / AGGREGATOR_ID
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use risc0_build::{embed_methods_with_options, DockerOptions, GuestOptions};
+
+fn main() {
+    // RISC0_REPRODUCIBLE_BUILD=1 builds the guest inside the pinned risc0 docker
+    // image, so the image ID does not depend on the local toolchain. Use it for
+    // anything that ends up in deployments/image-id.lock.
+    println!("cargo:rerun-if-env-changed=RISC0_REPRODUCIBLE_BUILD");
+    let use_docker = std::env::var("RISC0_REPRODUCIBLE_BUILD")
+        .map(|value| value == "1")
+        .unwrap_or(false)
+        .then(|| DockerOptions {
+            // The guest depends on the core crate one level up
+            root_dir: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..")),
+        });
+
+    embed_methods_with_options(HashMap::from([(
+        "social-verifier-guest",
+        GuestOptions {
+            features: Vec::new(),
+            use_docker,
+        },
+    )]));
+}
@@ -0,0 +1,25 @@
+// RISC Zero Guest Program for Batch Aggregation
+// Verifies many verification receipts by composition and commits the batch
+
+use risc0_social_verifier::{aggregate_verifications, AggregationInput, VerificationOutput};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::Digest;
+
+fn main() {
+    let input: AggregationInput = env::read();
+
+    // Each journal's receipt is supplied by the host as an assumption
+    let image_id = Digest::from(input.verifier_image_id);
+    let outputs: Vec<VerificationOutput> = input
+        .journals
+        .iter()
+        .map(|journal| {
+            env::verify(image_id, journal).expect("batched receipt does not verify");
+            risc0_zkvm::serde::from_slice(journal).expect("batched journal is not a verification output")
+        })
+        .collect();
+
+    // A batch that cannot be aggregated has no proof
+    let output = aggregate_verifications(&input, &outputs).expect("batch cannot be aggregated");
+    env::commit(&output);
+}
@@ -0,0 +1,25 @@
+// RISC Zero Guest Program for Identity Bundles
+// Verifies several platforms for one wallet in a single execution
+
+use risc0_social_verifier::{verify_identity_bundle, IdentityBundleInput, VerificationOutput};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::Digest;
+
+fn main() {
+    let input: IdentityBundleInput = env::read();
+
+    // Re-verified platforms compose their previous receipt, as in the
+    // single-platform guest
+    let previous: Vec<Option<VerificationOutput>> = input
+        .platforms
+        .iter()
+        .map(|platform| {
+            platform.previous_journal.as_ref().map(|journal| {
+                env::verify(Digest::from(platform.self_image_id), journal).expect("previous receipt does not verify");
+                risc0_zkvm::serde::from_slice(journal).expect("previous journal is not a verification output")
+            })
+        })
+        .collect();
+
+    env::commit(&verify_identity_bundle(&input, &previous));
+}
@@ -0,0 +1,23 @@
+// RISC Zero Guest Program for Social Account Verification
+// This program runs inside the zkVM and verifies OAuth tokens
+
+use risc0_social_verifier::{verify_social_account_composed, VerificationInput, VerificationOutput};
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::Digest;
+
+fn main() {
+    // Read input from the host
+    let input: VerificationInput = env::read();
+
+    // On re-verification, the previous receipt must be one of ours. The host
+    // supplies it as an assumption; proving fails if it does not verify.
+    let previous: Option<VerificationOutput> = input.previous_journal.as_ref().map(|journal| {
+        env::verify(Digest::from(input.self_image_id), journal).expect("previous receipt does not verify");
+        risc0_zkvm::serde::from_slice(journal).expect("previous journal is not a verification output")
+    });
+
+    let (verification_result, _) = verify_social_account_composed(&input, previous.as_ref());
+
+    // Commit the verification result to the journal
+    env::commit(&verification_result);
+}
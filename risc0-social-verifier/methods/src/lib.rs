@@ -0,0 +1,3 @@
+// Guest ELFs and image IDs generated by build.rs
+
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
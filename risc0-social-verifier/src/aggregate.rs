@@ -0,0 +1,145 @@
+// Batch aggregation
+// Many users' verification journals, each verified by composition, reduced to
+// one journal that links every (wallet, social account) pair at once.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{PolicyOutcome, SocialPlatform, VerificationOutput};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregationInput {
+    /// Image ID of the single-verification guest every journal came from
+    pub verifier_image_id: [u32; 8],
+    /// Journals of the receipts supplied as assumptions, in batch order
+    pub journals: Vec<Vec<u8>>,
+}
+
+/// One account the batch links
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregatedLink {
+    pub wallet_address: String,
+    pub social_account_hash: [u8; 32],
+    pub platform: SocialPlatform,
+    pub proof_hash: [u8; 32], // sha256 of the composed journal
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregationOutput {
+    pub verifier_image_id: [u32; 8],
+    pub chain_id: u64,
+    pub verifying_contract: String,
+    pub links: Vec<AggregatedLink>,
+    pub merkle_root: [u8; 32], // Over `link_leaf` of each link, in order
+}
+
+/// `sha256(abi.encode(address wallet, bytes32 socialAccountHash))`
+pub fn link_leaf(wallet_address: &str, social_account_hash: &[u8; 32]) -> Result<[u8; 32], &'static str> {
+    let wallet = hex::decode(wallet_address.trim_start_matches("0x")).map_err(|_| "Invalid wallet address")?;
+    if wallet.len() != 20 {
+        return Err("Invalid wallet address");
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update([0u8; 12]);
+    hasher.update(&wallet);
+    hasher.update(social_account_hash);
+    Ok(hasher.finalize().into())
+}
+
+/// Merkle root with sorted-pair sha256 nodes. An odd node at the end of a
+/// level moves up as is. OpenZeppelin's `MerkleProof` hashes nodes with
+/// keccak256, so it cannot check these proofs: an on-chain verifier needs a
+/// sha256 variant of `processProof` (the `sha256` precompile over the sorted pair).
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied().unwrap_or([0u8; 32])
+}
+
+/// Sibling path from `leaves[index]` to `merkle_root(leaves)`
+pub fn merkle_proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: &[u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(*leaf, |node, sibling| hash_pair(&node, sibling)) == *root
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// Aggregate decoded journals. `outputs[i]` is `journals[i]` decoded, with its
+/// receipt verified by the caller against `verifier_image_id`. Every journal
+/// must be a passed verification for one deployment, each account at most once.
+pub fn aggregate_verifications(
+    input: &AggregationInput,
+    outputs: &[VerificationOutput],
+) -> Result<AggregationOutput, &'static str> {
+    let first = outputs.first().ok_or("Empty batch")?;
+    if outputs.len() != input.journals.len() {
+        return Err("Journal count mismatch");
+    }
+
+    let mut links: Vec<AggregatedLink> = Vec::with_capacity(outputs.len());
+    let mut leaves = Vec::with_capacity(outputs.len());
+    for (journal, output) in input.journals.iter().zip(outputs) {
+        if !output.verification_success || output.policy_outcome != PolicyOutcome::Passed {
+            return Err("Batch contains a failed verification");
+        }
+        if output.self_image_id != input.verifier_image_id {
+            return Err("Batch contains a verification from another image");
+        }
+        if output.chain_id != first.chain_id || !output.verifying_contract.eq_ignore_ascii_case(&first.verifying_contract) {
+            return Err("Batch spans deployments");
+        }
+        if links.iter().any(|link| link.social_account_hash == output.social_account_hash) {
+            return Err("Social account appears twice in batch");
+        }
+
+        leaves.push(link_leaf(&output.wallet_address, &output.social_account_hash)?);
+        links.push(AggregatedLink {
+            wallet_address: output.wallet_address.clone(),
+            social_account_hash: output.social_account_hash,
+            platform: output.platform,
+            proof_hash: Sha256::digest(journal).into(),
+            expires_at: output.expires_at,
+        });
+    }
+
+    Ok(AggregationOutput {
+        verifier_image_id: input.verifier_image_id,
+        chain_id: first.chain_id,
+        verifying_contract: first.verifying_contract.clone(),
+        links,
+        merkle_root: merkle_root(&leaves),
+    })
+}
@@ -0,0 +1,146 @@
+// Identity bundles
+// Several platform verifications for one wallet in a single execution, so a
+// policy like "any two of Twitter, GitHub and Discord" is proven at once.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    verify_social_account_composed, PolicyOutcome, SocialPlatform, VerificationInput, VerificationOutput,
+};
+
+/// Domain separator for `nullifier`
+const NULLIFIER_DOMAIN: &[u8] = b"social-verifier/nullifier/v1";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityBundleInput {
+    /// One input per platform; all for the same wallet, deployment, nonce and time
+    pub platforms: Vec<VerificationInput>,
+    /// Distinct platforms that must pass for `bundle_success`
+    pub minimum_platforms: u32,
+}
+
+/// What the bundle reveals about one platform verification
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlatformAttestation {
+    pub platform: SocialPlatform,
+    pub nullifier: [u8; 32],
+    pub verification_success: bool,
+    pub policy_hash: [u8; 32],
+    pub policy_outcome: PolicyOutcome,
+    pub profile_commitment: [u8; 32],
+}
+
+/// Reputation from the number of distinct platforms that verified and met
+/// their policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReputationTier {
+    Unverified, // None
+    Basic,      // One platform
+    Verified,   // Two platforms
+    Trusted,    // Three or more
+}
+
+impl ReputationTier {
+    pub fn from_platform_count(count: u32) -> Self {
+        match count {
+            0 => Self::Unverified,
+            1 => Self::Basic,
+            2 => Self::Verified,
+            _ => Self::Trusted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdentityBundleOutput {
+    pub wallet_address: String,
+    pub chain_id: u64,
+    pub verifying_contract: String,
+    pub timestamp: u64,
+    pub expires_at: u64,
+    pub nonce: u64,
+    pub verifier_image_id: [u32; 8], // Single-verification guest that composed receipts came from
+    pub attestations: Vec<PlatformAttestation>,
+    pub distinct_platforms: u32, // Platforms that verified and passed their policy
+    pub reputation_tier: ReputationTier,
+    pub minimum_platforms: u32,
+    pub bundle_success: bool, // Inputs consistent and `distinct_platforms >= minimum_platforms`
+}
+
+/// Per-deployment identifier for a social account. It is unique per account
+/// like `social_account_hash`, but cannot be joined across deployments.
+pub fn nullifier(chain_id: u64, verifying_contract: &str, social_account_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(NULLIFIER_DOMAIN);
+    hasher.update(chain_id.to_be_bytes());
+    hasher.update(verifying_contract.to_lowercase().as_bytes());
+    hasher.update(social_account_hash);
+    hasher.finalize().into()
+}
+
+/// Verify every platform in the bundle. `previous[i]` is the decoded
+/// `previous_journal` of `platforms[i]`, verified by the caller as in
+/// `verify_social_account_composed`.
+pub fn verify_identity_bundle(
+    input: &IdentityBundleInput,
+    previous: &[Option<VerificationOutput>],
+) -> IdentityBundleOutput {
+    let first = input.platforms.first();
+    let consistent = first.is_some_and(|first| {
+        input.platforms.iter().all(|other| {
+            other.wallet_address.eq_ignore_ascii_case(&first.wallet_address)
+                && other.chain_id == first.chain_id
+                && other.verifying_contract.eq_ignore_ascii_case(&first.verifying_contract)
+                && other.timestamp == first.timestamp
+                && other.expires_at == first.expires_at
+                && other.nonce == first.nonce
+                && other.self_image_id == first.self_image_id
+        })
+    });
+
+    let mut attestations = Vec::with_capacity(input.platforms.len());
+    // Counted per platform, so two accounts on one platform count once
+    let mut passed_platforms: Vec<SocialPlatform> = Vec::new();
+    for (index, platform_input) in input.platforms.iter().enumerate() {
+        let previous = previous.get(index).and_then(Option::as_ref);
+        let (output, _) = verify_social_account_composed(platform_input, previous);
+
+        let nullifier = if output.verification_success {
+            nullifier(output.chain_id, &output.verifying_contract, &output.social_account_hash)
+        } else {
+            [0u8; 32]
+        };
+        if output.verification_success
+            && output.policy_outcome == PolicyOutcome::Passed
+            && !passed_platforms.contains(&output.platform)
+        {
+            passed_platforms.push(output.platform);
+        }
+
+        attestations.push(PlatformAttestation {
+            platform: output.platform,
+            nullifier,
+            verification_success: output.verification_success,
+            policy_hash: output.policy_hash,
+            policy_outcome: output.policy_outcome,
+            profile_commitment: output.profile_commitment,
+        });
+    }
+
+    let distinct_platforms = passed_platforms.len() as u32;
+    IdentityBundleOutput {
+        wallet_address: first.map(|first| first.wallet_address.clone()).unwrap_or_default(),
+        chain_id: first.map_or(0, |first| first.chain_id),
+        verifying_contract: first.map(|first| first.verifying_contract.clone()).unwrap_or_default(),
+        timestamp: first.map_or(0, |first| first.timestamp),
+        expires_at: first.map_or(0, |first| first.expires_at),
+        nonce: first.map_or(0, |first| first.nonce),
+        verifier_image_id: first.map_or([0u32; 8], |first| first.self_image_id),
+        attestations,
+        distinct_platforms,
+        reputation_tier: ReputationTier::from_platform_count(distinct_platforms),
+        minimum_platforms: input.minimum_platforms,
+        bundle_success: consistent && distinct_platforms >= input.minimum_platforms,
+    }
+}
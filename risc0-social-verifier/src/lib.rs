@@ -0,0 +1,612 @@
+// Social Account Verification core
+// Types and verification logic shared by the zkVM guest and the host
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+mod aggregate;
+mod bundle;
+mod risk;
+
+pub use aggregate::{
+    aggregate_verifications, link_leaf, merkle_proof, merkle_root, verify_merkle_proof, AggregatedLink,
+    AggregationInput, AggregationOutput,
+};
+pub use bundle::{
+    nullifier, verify_identity_bundle, IdentityBundleInput, IdentityBundleOutput, PlatformAttestation,
+    ReputationTier,
+};
+pub use risk::{sybil_risk_score, ActivitySignals, RiskModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SocialPlatform {
+    Twitter,
+    Discord,
+    Github,
+    Telegram,
+    LinkedIn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationInput {
+    pub platform: SocialPlatform,
+    pub oauth_token: String,
+    pub wallet_address: String,
+    pub timestamp: u64,
+    pub expires_at: u64, // Set by host policy, checked by verifiers
+    pub nonce: u64, // Prevent replay attacks
+    pub expected_account_id: Option<String>, // For re-verification
+    pub previous_profile: Option<ProfileSnapshot>, // Profile from the last verification
+    pub previous_journal: Option<Vec<u8>>, // Its journal; the guest verifies the receipt by composition
+    pub self_image_id: [u32; 8], // The guest's own image ID, which the previous receipt must match
+    pub chain_id: u64, // Deployment the proof is bound to
+    pub verifying_contract: String, // Registry/verifier address on that chain
+    pub policy: PlatformRequirements, // Registry requirements for `platform`
+    pub risk_model: RiskModel, // Parameters for `sybil_risk_score`
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerificationOutput {
+    pub social_account_hash: [u8; 32],
+    pub wallet_address: String,
+    pub platform: SocialPlatform,
+    pub account_age: u64,
+    pub follower_count: u64,
+    pub timestamp: u64,
+    pub expires_at: u64,
+    pub nonce: u64,
+    pub social_account_id: String, // Stable account ID
+    pub verification_type: VerificationType,
+    pub account_consistency_score: u8, // 0-100 consistency rating
+    pub chain_id: u64,
+    pub verifying_contract: String,
+    pub verification_success: bool,
+    pub policy_hash: [u8; 32], // `policy_hash` of the requirements evaluated
+    pub policy_outcome: PolicyOutcome,
+    pub profile_commitment: [u8; 32], // Commitment to this round's `ProfileSnapshot`
+    pub previous_profile_commitment: Option<[u8; 32]>, // The snapshot scored against
+    pub self_image_id: [u32; 8], // Image ID the previous receipt was verified against
+    pub previous_proof_hash: Option<[u8; 32]>, // sha256 of the composed previous journal
+    pub sybil_risk_score: u8, // 0-100, higher is riskier; 100 when verification failed
+    pub risk_model_version: u32,
+    pub risk_model_hash: [u8; 32], // `RiskModel::hash` of the model scored with
+    pub oauth_token_hash: [u8; 32], // `oauth_token_hash` of the token used, the request's commitment
+}
+
+/// What a verification remembers about a profile. Only its commitment is
+/// journaled; the host keeps the snapshot and passes it to the next round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSnapshot {
+    pub account_id: String,
+    pub created_at: String, // As reported by the platform, empty if it has none
+    pub follower_count: u64,
+    pub username_hash: [u8; 32],
+    pub verified_at: u64, // `timestamp` of the verification that took it
+}
+
+impl ProfileSnapshot {
+    pub fn new(account_id: &str, created_at: &str, follower_count: u64, username: &str, verified_at: u64) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            created_at: created_at.to_string(),
+            follower_count,
+            username_hash: Sha256::digest(username.as_bytes()).into(),
+            verified_at,
+        }
+    }
+
+    /// sha256 over the length-prefixed account ID and `created_at`, then the
+    /// follower count, username hash and `verified_at`
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for field in [&self.account_id, &self.created_at] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        hasher.update(self.follower_count.to_be_bytes());
+        hasher.update(self.username_hash);
+        hasher.update(self.verified_at.to_be_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// The parts of `SocialAccountRegistry.PlatformConfig` the guest enforces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformRequirements {
+    pub minimum_account_age: u64, // Seconds
+    pub minimum_followers: u64,
+    pub requires_additional_verification: bool,
+}
+
+/// Result of checking an account against `PlatformRequirements`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyOutcome {
+    Passed,
+    AccountTooYoung,
+    InsufficientFollowers,
+    /// The platform reports the account as unverified
+    AdditionalVerificationMissing,
+    /// The account itself did not verify
+    NotEvaluated,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationType {
+    NewAccount,
+    ReVerification,
+    AccountUpdate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwitterUserData {
+    pub id: String,
+    pub username: String,
+    pub name: String,
+    pub created_at: String,
+    pub public_metrics: TwitterMetrics,
+    pub verified: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwitterMetrics {
+    pub followers_count: u64,
+    pub following_count: u64,
+    pub tweet_count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiscordUserData {
+    pub id: String,
+    pub username: String,
+    pub discriminator: String,
+    pub verified: Option<bool>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GithubUserData {
+    pub id: u64,
+    pub login: String,
+    pub name: Option<String>,
+    pub created_at: String,
+    pub followers: u64,
+    pub following: u64,
+    pub public_repos: u64,
+}
+
+/// Run the full verification for one input. This is what the guest commits
+/// to the journal.
+pub fn verify_social_account(input: &VerificationInput) -> VerificationOutput {
+    verify_social_account_with_profile(input).0
+}
+
+/// Like `verify_social_account`, also returning the snapshot behind
+/// `profile_commitment` so the host can keep it for the next round
+pub fn verify_social_account_with_profile(input: &VerificationInput) -> (VerificationOutput, Option<ProfileSnapshot>) {
+    verify_social_account_composed(input, None)
+}
+
+/// Verify with `previous` as the decoded `previous_journal`. The caller (the
+/// guest, via `env::verify`) is responsible for checking the receipt behind
+/// it; this checks that it continues the same account on the same deployment.
+pub fn verify_social_account_composed(
+    input: &VerificationInput,
+    previous: Option<&VerificationOutput>,
+) -> (VerificationOutput, Option<ProfileSnapshot>) {
+    match (&input.previous_journal, previous) {
+        (None, None) => {}
+        (Some(_), Some(previous)) => {
+            if let Err(reason) = check_continuity(input, previous) {
+                return (create_failed_verification(input, reason), None);
+            }
+        }
+        _ => return (create_failed_verification(input, "Previous journal not verified"), None),
+    }
+
+    // A proof that is already expired is never useful
+    if input.expires_at <= input.timestamp {
+        return (create_failed_verification(input, "Invalid expiry"), None);
+    }
+
+    // Validate OAuth token first
+    if !validate_oauth_token(&input.oauth_token, &input.platform) {
+        return (create_failed_verification(input, "Invalid OAuth token"), None);
+    }
+
+    // Verify the OAuth token and extract user data
+    let verified = match input.platform {
+        SocialPlatform::Twitter => verify_twitter_account(input),
+        SocialPlatform::Discord => verify_discord_account(input),
+        SocialPlatform::Github => verify_github_account(input),
+        SocialPlatform::Telegram => verify_telegram_account(input),
+        SocialPlatform::LinkedIn => verify_linkedin_account(input),
+    };
+
+    match verified {
+        Ok(account) => {
+            let output = create_verified_output(input, &account);
+            (output, Some(account.profile))
+        }
+        Err(reason) => (create_failed_verification(input, reason), None),
+    }
+}
+
+/// What a platform verifier learned about the account
+struct VerifiedAccount {
+    profile: ProfileSnapshot,
+    policy_outcome: PolicyOutcome,
+    signals: ActivitySignals,
+}
+
+fn verify_twitter_account(input: &VerificationInput) -> Result<VerifiedAccount, &'static str> {
+    // In a real implementation, this would make HTTP requests to Twitter API
+    // For demonstration, we'll simulate the verification process
+    let data = simulate_twitter_api_call(&input.oauth_token).map_err(|_| "Twitter API call failed")?;
+
+    let account_age = calculate_account_age(&data.created_at, input.timestamp);
+    let followers = data.public_metrics.followers_count;
+    let policy_outcome = evaluate_policy(&input.policy, account_age, followers, data.verified);
+    let profile = ProfileSnapshot::new(&data.id, &data.created_at, followers, &data.username, input.timestamp);
+    let signals = ActivitySignals {
+        account_age,
+        followers,
+        following: data.public_metrics.following_count,
+        activity_count: data.public_metrics.tweet_count,
+        verified: data.verified,
+    };
+    Ok(VerifiedAccount {
+        profile,
+        policy_outcome,
+        signals,
+    })
+}
+
+fn verify_discord_account(input: &VerificationInput) -> Result<VerifiedAccount, &'static str> {
+    let data = simulate_discord_api_call(&input.oauth_token).map_err(|_| "Discord API call failed")?;
+
+    // Discord exposes neither account age nor followers in the basic API
+    let policy_outcome = evaluate_policy(&input.policy, 0, 0, data.verified);
+    let profile = ProfileSnapshot::new(&data.id, "", 0, &data.username, input.timestamp);
+    // Only the verified flag is a signal; the rest scores as risky
+    let signals = ActivitySignals {
+        verified: data.verified,
+        ..ActivitySignals::default()
+    };
+    Ok(VerifiedAccount {
+        profile,
+        policy_outcome,
+        signals,
+    })
+}
+
+fn verify_github_account(input: &VerificationInput) -> Result<VerifiedAccount, &'static str> {
+    let data = simulate_github_api_call(&input.oauth_token).map_err(|_| "GitHub API call failed")?;
+
+    let account_age = calculate_account_age(&data.created_at, input.timestamp);
+    // GitHub has no account verification flag
+    let policy_outcome = evaluate_policy(&input.policy, account_age, data.followers, None);
+    let profile =
+        ProfileSnapshot::new(&data.id.to_string(), &data.created_at, data.followers, &data.login, input.timestamp);
+    let signals = ActivitySignals {
+        account_age,
+        followers: data.followers,
+        following: data.following,
+        activity_count: data.public_repos,
+        verified: None,
+    };
+    Ok(VerifiedAccount {
+        profile,
+        policy_outcome,
+        signals,
+    })
+}
+
+fn create_verified_output(input: &VerificationInput, account: &VerifiedAccount) -> VerificationOutput {
+    let profile = &account.profile;
+    VerificationOutput {
+        // Always the same for the same account ID
+        social_account_hash: generate_social_account_hash(&input.platform, &profile.account_id),
+        wallet_address: input.wallet_address.clone(),
+        platform: input.platform,
+        account_age: account.signals.account_age,
+        follower_count: profile.follower_count,
+        timestamp: input.timestamp,
+        expires_at: input.expires_at,
+        nonce: input.nonce,
+        social_account_id: profile.account_id.clone(),
+        verification_type: determine_verification_type(input, &profile.account_id),
+        account_consistency_score: calculate_consistency_score(input, profile),
+        chain_id: input.chain_id,
+        verifying_contract: input.verifying_contract.clone(),
+        verification_success: true,
+        policy_hash: policy_hash(&input.platform, &input.policy),
+        policy_outcome: account.policy_outcome,
+        profile_commitment: profile.commitment(),
+        previous_profile_commitment: input.previous_profile.as_ref().map(ProfileSnapshot::commitment),
+        self_image_id: input.self_image_id,
+        previous_proof_hash: previous_proof_hash(input),
+        sybil_risk_score: sybil_risk_score(&input.risk_model, &account.signals),
+        risk_model_version: input.risk_model.version,
+        risk_model_hash: input.risk_model.hash(),
+        oauth_token_hash: oauth_token_hash(&input.oauth_token),
+    }
+}
+
+/// Whether `previous` is a successful verification, by the same guest, of
+/// the snapshot in `input.previous_profile`, for the same wallet, platform and
+/// deployment, made before this one
+pub fn check_continuity(input: &VerificationInput, previous: &VerificationOutput) -> Result<(), &'static str> {
+    let profile = input.previous_profile.as_ref().ok_or("Previous journal without previous profile")?;
+
+    if !previous.verification_success {
+        return Err("Previous verification failed");
+    }
+    // A receipt proven under another image ID could have committed anything
+    if previous.self_image_id != input.self_image_id {
+        return Err("Previous verification from another image");
+    }
+    if previous.profile_commitment != profile.commitment() {
+        return Err("Previous profile does not match its commitment");
+    }
+    if previous.platform != input.platform
+        || !previous.wallet_address.eq_ignore_ascii_case(&input.wallet_address)
+        || previous.chain_id != input.chain_id
+        || !previous.verifying_contract.eq_ignore_ascii_case(&input.verifying_contract)
+    {
+        return Err("Previous verification for another wallet or deployment");
+    }
+    if previous.timestamp >= input.timestamp {
+        return Err("Previous verification is not older");
+    }
+    Ok(())
+}
+
+fn previous_proof_hash(input: &VerificationInput) -> Option<[u8; 32]> {
+    input
+        .previous_journal
+        .as_ref()
+        .map(|journal| Sha256::digest(journal).into())
+}
+
+fn verify_telegram_account(_input: &VerificationInput) -> Result<VerifiedAccount, &'static str> {
+    // Telegram verification would be more complex as it requires bot integration
+    Err("Telegram verification not supported")
+}
+
+fn verify_linkedin_account(_input: &VerificationInput) -> Result<VerifiedAccount, &'static str> {
+    // LinkedIn verification placeholder
+    Err("LinkedIn verification not supported")
+}
+
+// Simulation functions (in real implementation, these would make actual HTTP requests)
+
+fn simulate_twitter_api_call(oauth_token: &str) -> Result<TwitterUserData, &'static str> {
+    // Simulate token validation
+    if oauth_token.len() < 10 {
+        return Err("Invalid token");
+    }
+    
+    // Return mock data
+    Ok(TwitterUserData {
+        id: "123456789".to_string(),
+        username: "testuser".to_string(),
+        name: "Test User".to_string(),
+        created_at: "2020-01-01T00:00:00.000Z".to_string(),
+        public_metrics: TwitterMetrics {
+            followers_count: 150,
+            following_count: 100,
+            tweet_count: 500,
+        },
+        verified: Some(false),
+    })
+}
+
+fn simulate_discord_api_call(oauth_token: &str) -> Result<DiscordUserData, &'static str> {
+    if oauth_token.len() < 10 {
+        return Err("Invalid token");
+    }
+    
+    Ok(DiscordUserData {
+        id: "987654321".to_string(),
+        username: "testuser".to_string(),
+        discriminator: "1234".to_string(),
+        verified: Some(true),
+        email: Some("test@example.com".to_string()),
+    })
+}
+
+fn simulate_github_api_call(oauth_token: &str) -> Result<GithubUserData, &'static str> {
+    if oauth_token.len() < 10 {
+        return Err("Invalid token");
+    }
+    
+    Ok(GithubUserData {
+        id: 12345,
+        login: "testuser".to_string(),
+        name: Some("Test User".to_string()),
+        created_at: "2019-06-01T00:00:00Z".to_string(),
+        followers: 25,
+        following: 50,
+        public_repos: 10,
+    })
+}
+
+// Utility functions
+
+pub fn generate_social_account_hash(platform: &SocialPlatform, account_id: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}", platform).as_bytes());
+    hasher.update(account_id.as_bytes());
+    hasher.finalize().into()
+}
+
+/// Commitment to an OAuth token: `sha256(bytes(token))`. Verification requests
+/// post only this, and `submitProof` matches it against the journal.
+pub fn oauth_token_hash(oauth_token: &str) -> [u8; 32] {
+    Sha256::digest(oauth_token.as_bytes()).into()
+}
+
+/// Hash of the requirements a proof was evaluated against. Matches
+/// `SocialAccountRegistry.platformPolicyHash`, i.e. `sha256(abi.encode(platform,
+/// minimumAccountAge, minimumFollowers, requiresAdditionalVerification))`.
+pub fn policy_hash(platform: &SocialPlatform, requirements: &PlatformRequirements) -> [u8; 32] {
+    let words = [
+        *platform as u64,
+        requirements.minimum_account_age,
+        requirements.minimum_followers,
+        requirements.requires_additional_verification as u64,
+    ];
+    let mut hasher = Sha256::new();
+    for word in words {
+        // Each value as a left-padded 32-byte ABI word
+        hasher.update([0u8; 24]);
+        hasher.update(word.to_be_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Check an account against the registry requirements. `platform_verified` is
+/// the platform's own verification flag, where it has one; without a flag the
+/// additional verification requirement is left to the registry.
+pub fn evaluate_policy(
+    requirements: &PlatformRequirements,
+    account_age: u64,
+    follower_count: u64,
+    platform_verified: Option<bool>,
+) -> PolicyOutcome {
+    if account_age < requirements.minimum_account_age {
+        PolicyOutcome::AccountTooYoung
+    } else if follower_count < requirements.minimum_followers {
+        PolicyOutcome::InsufficientFollowers
+    } else if requirements.requires_additional_verification && platform_verified == Some(false) {
+        PolicyOutcome::AdditionalVerificationMissing
+    } else {
+        PolicyOutcome::Passed
+    }
+}
+
+/// Seconds from `created_at` (RFC 3339) to `timestamp`, the verification's
+/// committed time, never the prover's clock, so the same input always proves
+/// the same age. 0 if the date does not parse or is after `timestamp`.
+pub fn calculate_account_age(created_at: &str, timestamp: u64) -> u64 {
+    match chrono::DateTime::parse_from_rfc3339(created_at) {
+        Ok(created) => u64::try_from(created.timestamp()).map_or(0, |created| timestamp.saturating_sub(created)),
+        Err(_) => 0,
+    }
+}
+
+pub fn validate_oauth_token(token: &str, platform: &SocialPlatform) -> bool {
+    // Basic token validation
+    if token.len() < 10 {
+        return false;
+    }
+
+    // Platform-specific token format validation
+    match platform {
+        SocialPlatform::Twitter => token.starts_with("Bearer ") || token.len() > 20,
+        SocialPlatform::Discord => token.len() > 15,
+        SocialPlatform::Github => token.starts_with("ghp_") || token.starts_with("gho_"),
+        _ => token.len() > 10,
+    }
+}
+
+/// The account a re-verification claims to be: the previous snapshot's, or
+/// `expected_account_id` when there is no snapshot
+fn expected_account_id(input: &VerificationInput) -> Option<&str> {
+    input
+        .previous_profile
+        .as_ref()
+        .map(|profile| profile.account_id.as_str())
+        .or(input.expected_account_id.as_deref())
+}
+
+pub fn determine_verification_type(
+    input: &VerificationInput,
+    account_id: &str,
+) -> VerificationType {
+    match expected_account_id(input) {
+        Some(expected_id) => {
+            if expected_id == account_id {
+                VerificationType::ReVerification
+            } else {
+                VerificationType::AccountUpdate
+            }
+        }
+        None => VerificationType::NewAccount,
+    }
+}
+
+/// Penalty for a different account ID than the one being re-verified
+pub const ACCOUNT_ID_MISMATCH_PENALTY: u8 = 100;
+/// Penalty for a changed `created_at`, which never changes for a real account
+pub const CREATED_AT_CHANGE_PENALTY: u8 = 50;
+/// Penalty for followers growing faster than `previous / 10 + 100` per day
+pub const FOLLOWER_JUMP_PENALTY: u8 = 30;
+/// Penalty for losing more than half the followers
+pub const FOLLOWER_DROP_PENALTY: u8 = 20;
+/// Penalty for a username change; common, but worth noting
+pub const USERNAME_CHANGE_PENALTY: u8 = 5;
+
+/// Consistency of `current` with the profile being re-verified, 0-100.
+///
+/// Starts at 100 and subtracts each penalty that applies, saturating at 0.
+/// Only the account ID check applies without a previous snapshot, so a
+/// first verification scores 100.
+pub fn calculate_consistency_score(input: &VerificationInput, current: &ProfileSnapshot) -> u8 {
+    let mut score: u8 = 100;
+
+    if expected_account_id(input).is_some_and(|expected_id| expected_id != current.account_id) {
+        score = score.saturating_sub(ACCOUNT_ID_MISMATCH_PENALTY);
+    }
+
+    if let Some(previous) = &input.previous_profile {
+        if previous.created_at != current.created_at {
+            score = score.saturating_sub(CREATED_AT_CHANGE_PENALTY);
+        }
+
+        // Whole days since the last verification, at least one
+        let days = (current.verified_at.saturating_sub(previous.verified_at) / 86_400).max(1);
+        let allowed_growth = (previous.follower_count / 10 + 100).saturating_mul(days);
+        if current.follower_count.saturating_sub(previous.follower_count) > allowed_growth {
+            score = score.saturating_sub(FOLLOWER_JUMP_PENALTY);
+        }
+        if current.follower_count < previous.follower_count / 2 {
+            score = score.saturating_sub(FOLLOWER_DROP_PENALTY);
+        }
+
+        if previous.username_hash != current.username_hash {
+            score = score.saturating_sub(USERNAME_CHANGE_PENALTY);
+        }
+    }
+
+    score
+}
+
+pub fn create_failed_verification(input: &VerificationInput, _reason: &str) -> VerificationOutput {
+    VerificationOutput {
+        social_account_hash: [0u8; 32],
+        wallet_address: input.wallet_address.clone(),
+        platform: input.platform,
+        account_age: 0,
+        follower_count: 0,
+        timestamp: input.timestamp,
+        expires_at: input.expires_at,
+        nonce: input.nonce,
+        social_account_id: String::new(),
+        verification_type: VerificationType::NewAccount,
+        account_consistency_score: 0,
+        chain_id: input.chain_id,
+        verifying_contract: input.verifying_contract.clone(),
+        verification_success: false,
+        policy_hash: policy_hash(&input.platform, &input.policy),
+        policy_outcome: PolicyOutcome::NotEvaluated,
+        profile_commitment: [0u8; 32],
+        previous_profile_commitment: input.previous_profile.as_ref().map(ProfileSnapshot::commitment),
+        self_image_id: input.self_image_id,
+        previous_proof_hash: previous_proof_hash(input),
+        sybil_risk_score: 100,
+        risk_model_version: input.risk_model.version,
+        risk_model_hash: input.risk_model.hash(),
+        oauth_token_hash: oauth_token_hash(&input.oauth_token),
+    }
+}
@@ -0,0 +1,113 @@
+// Sybil-risk scoring
+// A deterministic, integer-only model over the profile signals the platforms
+// return. The guest commits the score with the model's version and hash, so a
+// consumer can key off a proven score from a model it recognises.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Scoring parameters. Each matching signal adds its weight to the risk; a
+/// platform-verified account has `verified_bonus` taken off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RiskModel {
+    pub version: u32,
+    /// Accounts younger than this add `young_account_weight`
+    pub young_account_age: u64,
+    /// Accounts younger than this (but not young) add half of it
+    pub mature_account_age: u64,
+    pub young_account_weight: u8,
+    /// Followers as a percentage of following below this add `ratio_weight`
+    pub minimum_follower_ratio_percent: u64,
+    pub ratio_weight: u8,
+    /// Fewer followers than this add `audience_weight`
+    pub minimum_followers: u64,
+    pub audience_weight: u8,
+    /// Fewer posts/repos than this add `activity_weight`
+    pub minimum_activity: u64,
+    pub activity_weight: u8,
+    pub verified_bonus: u8,
+}
+
+impl Default for RiskModel {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            young_account_age: 30 * DAY,
+            mature_account_age: 365 * DAY,
+            young_account_weight: 30,
+            minimum_follower_ratio_percent: 10,
+            ratio_weight: 25,
+            minimum_followers: 5,
+            audience_weight: 15,
+            minimum_activity: 10,
+            activity_weight: 30,
+            verified_bonus: 20,
+        }
+    }
+}
+
+impl RiskModel {
+    /// sha256 over every parameter, big-endian, in declaration order
+    pub fn hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.version.to_be_bytes());
+        hasher.update(self.young_account_age.to_be_bytes());
+        hasher.update(self.mature_account_age.to_be_bytes());
+        hasher.update([self.young_account_weight]);
+        hasher.update(self.minimum_follower_ratio_percent.to_be_bytes());
+        hasher.update([self.ratio_weight]);
+        hasher.update(self.minimum_followers.to_be_bytes());
+        hasher.update([self.audience_weight]);
+        hasher.update(self.minimum_activity.to_be_bytes());
+        hasher.update([self.activity_weight]);
+        hasher.update([self.verified_bonus]);
+        hasher.finalize().into()
+    }
+}
+
+/// What the platform tells us about an account's behaviour
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivitySignals {
+    /// Seconds, measured at the verification's committed timestamp
+    pub account_age: u64,
+    pub followers: u64,
+    pub following: u64,
+    /// Tweets on Twitter, public repositories on GitHub
+    pub activity_count: u64,
+    pub verified: Option<bool>,
+}
+
+/// Sybil risk of an account, 0 (no signal of risk) to 100
+pub fn sybil_risk_score(model: &RiskModel, signals: &ActivitySignals) -> u8 {
+    let mut risk: u8 = 0;
+
+    if signals.account_age < model.young_account_age {
+        risk = risk.saturating_add(model.young_account_weight);
+    } else if signals.account_age < model.mature_account_age {
+        risk = risk.saturating_add(model.young_account_weight / 2);
+    }
+
+    // followers / following < minimum%, without division; following nobody is fine
+    if signals.following > 0
+        && signals.followers.saturating_mul(100) < signals.following.saturating_mul(model.minimum_follower_ratio_percent)
+    {
+        risk = risk.saturating_add(model.ratio_weight);
+    }
+
+    if signals.followers < model.minimum_followers {
+        risk = risk.saturating_add(model.audience_weight);
+    }
+
+    if signals.activity_count < model.minimum_activity {
+        risk = risk.saturating_add(model.activity_weight);
+    }
+
+    if signals.verified == Some(true) {
+        risk = risk.saturating_sub(model.verified_bonus);
+    }
+
+    risk.min(100)
+}
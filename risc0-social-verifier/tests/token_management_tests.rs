@@ -7,6 +7,10 @@ use std::collections::HashMap;
 mod token_management_tests {
     use super::*;
 
+    const SEPOLIA_CHAIN_ID: u64 = 11155111;
+    const REGISTRY: &str = "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8";
+    const IMAGE_ID: [u32; 8] = [7; 8];
+
     // Mock data for testing
     struct MockTwitterUser {
         id: String,
@@ -50,8 +54,16 @@ mod token_management_tests {
             oauth_token: scenario.tokens[0].clone(),
             wallet_address: scenario.wallet_address.clone(),
             timestamp: 1640995200, // 2022-01-01
+            expires_at: 1640995200 + 3600,
             nonce: 1,
             expected_account_id: None, // New account
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result1 = simulate_verification(&input1, &scenario.user_data);
@@ -70,15 +82,25 @@ mod token_management_tests {
             oauth_token: scenario.tokens[1].clone(),
             wallet_address: scenario.wallet_address.clone(),
             timestamp: 1643587200, // 2022-01-31
+            expires_at: 1643587200 + 3600,
             nonce: 2,
             expected_account_id: Some(scenario.user_data.id.clone()), // Re-verification
+            previous_profile: Some(snapshot(&scenario.user_data, input1.timestamp)),
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result2 = simulate_verification(&input2, &scenario.user_data);
         assert!(result2.verification_success);
         assert_eq!(result2.social_account_id, scenario.user_data.id);
         assert_eq!(result2.verification_type, VerificationType::ReVerification);
-        assert_eq!(result2.account_consistency_score, 95);
+        // Nothing changed since the committed snapshot
+        assert_eq!(result2.account_consistency_score, 100);
+        assert_eq!(result2.previous_profile_commitment, Some(result1.profile_commitment));
 
         // Most importantly: Same hash generated!
         assert_eq!(result1.social_account_hash, result2.social_account_hash);
@@ -93,8 +115,16 @@ mod token_management_tests {
             oauth_token: scenario.tokens[2].clone(),
             wallet_address: scenario.wallet_address.clone(),
             timestamp: 1646179200, // 2022-03-02
+            expires_at: 1646179200 + 3600,
             nonce: 3,
             expected_account_id: Some(scenario.user_data.id.clone()),
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result3 = simulate_verification(&input3, &scenario.user_data);
@@ -136,8 +166,16 @@ mod token_management_tests {
             oauth_token: "Bearer token1111".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 1,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result1 = simulate_verification(&input1, &original_user);
@@ -149,13 +187,22 @@ mod token_management_tests {
             oauth_token: "Bearer token2222".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1643587200,
+            expires_at: 1643587200 + 3600,
             nonce: 2,
             expected_account_id: Some(original_user.id.clone()),
+            previous_profile: Some(snapshot(&original_user, input1.timestamp)),
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result2 = simulate_verification(&input2, &updated_user);
         assert!(result2.verification_success);
         assert_eq!(result2.verification_type, VerificationType::ReVerification);
+        assert_eq!(result2.account_consistency_score, 100 - USERNAME_CHANGE_PENALTY);
 
         // Same account ID = same hash, despite username change
         assert_eq!(result1.social_account_hash, result2.social_account_hash);
@@ -186,8 +233,16 @@ mod token_management_tests {
             oauth_token: "".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 1,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result1 = simulate_verification(&input1, &user_data);
@@ -200,8 +255,16 @@ mod token_management_tests {
             oauth_token: "invalid".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 2,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result2 = simulate_verification(&input2, &user_data);
@@ -213,8 +276,16 @@ mod token_management_tests {
             oauth_token: "Bearer twitter_token".to_string(), // Twitter token for GitHub
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 3,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result3 = simulate_verification(&input3, &user_data);
@@ -249,8 +320,16 @@ mod token_management_tests {
             oauth_token: "Bearer legitimate_token".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 1,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result1 = simulate_verification(&input1, &legitimate_user);
@@ -262,8 +341,16 @@ mod token_management_tests {
             oauth_token: "Bearer attacker_token".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1643587200,
+            expires_at: 1643587200 + 3600,
             nonce: 2,
             expected_account_id: Some(legitimate_user.id.clone()), // Claims to be re-verification
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let result2 = simulate_verification(&input2, &attacker_user);
@@ -300,8 +387,16 @@ mod token_management_tests {
             oauth_token: "Bearer twitter_token".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 1,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let twitter_result = simulate_verification(&twitter_input, &twitter_user);
@@ -320,8 +415,16 @@ mod token_management_tests {
             oauth_token: "ghp_github_token_1234567890".to_string(),
             wallet_address: wallet_address.clone(),
             timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
             nonce: 2,
             expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
         };
 
         let github_result = simulate_verification_github(&github_input, &github_user);
@@ -338,6 +441,523 @@ mod token_management_tests {
         println!("   GitHub Hash: {:?}", hex::encode(github_result.social_account_hash));
     }
 
+    #[test]
+    fn test_consistency_against_previous_profile() {
+        let day = 24 * 60 * 60;
+        let mut input = VerificationInput {
+            platform: SocialPlatform::Twitter,
+            oauth_token: "Bearer aaaa1111bbbb2222cccc3333".to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
+        };
+        let (first, profile) = verify_social_account_with_profile(&input);
+        let profile = profile.unwrap();
+        assert_eq!(first.account_consistency_score, 100);
+        assert_eq!(first.profile_commitment, profile.commitment());
+        assert_eq!(first.previous_profile_commitment, None);
+
+        // The same profile a week later
+        input.timestamp += 7 * day;
+        input.expires_at += 7 * day;
+        input.previous_profile = Some(profile.clone());
+        let (second, _) = verify_social_account_with_profile(&input);
+        assert_eq!(second.verification_type, VerificationType::ReVerification);
+        assert_eq!(second.account_consistency_score, 100);
+        assert_eq!(second.previous_profile_commitment, Some(first.profile_commitment));
+        assert_ne!(second.profile_commitment, first.profile_commitment);
+
+        // created_at moved and followers grew 10x overnight
+        let mut previous = profile.clone();
+        previous.created_at = "2023-01-01T00:00:00.000Z".to_string();
+        previous.follower_count = 15;
+        previous.verified_at = input.timestamp - 60;
+        let current = ProfileSnapshot { verified_at: input.timestamp, ..profile.clone() };
+        input.previous_profile = Some(previous.clone());
+        assert_eq!(
+            calculate_consistency_score(&input, &current),
+            100 - CREATED_AT_CHANGE_PENALTY - FOLLOWER_JUMP_PENALTY
+        );
+
+        // Growth within the daily allowance is fine, a collapse is not
+        previous.created_at = profile.created_at.clone();
+        previous.follower_count = 1000;
+        previous.verified_at = input.timestamp - 2 * day;
+        input.previous_profile = Some(previous.clone());
+        let grown = ProfileSnapshot { follower_count: 1400, ..current.clone() };
+        assert_eq!(calculate_consistency_score(&input, &grown), 100);
+        let dropped = ProfileSnapshot { follower_count: 400, ..current.clone() };
+        assert_eq!(calculate_consistency_score(&input, &dropped), 100 - FOLLOWER_DROP_PENALTY);
+
+        // A different account scores 0 whatever else matches
+        previous.account_id = "555".to_string();
+        input.previous_profile = Some(previous);
+        let (takeover, _) = verify_social_account_with_profile(&input);
+        assert_eq!(takeover.verification_type, VerificationType::AccountUpdate);
+        assert_eq!(takeover.account_consistency_score, 0);
+    }
+
+    #[test]
+    fn test_recursive_reverification() {
+        use sha2::{Digest, Sha256};
+
+        let day = 24 * 60 * 60;
+        let first_input = VerificationInput {
+            platform: SocialPlatform::Twitter,
+            oauth_token: "Bearer aaaa1111bbbb2222cccc3333".to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
+        };
+        let (first, profile) = verify_social_account_with_profile(&first_input);
+        assert_eq!(first.self_image_id, IMAGE_ID);
+        assert_eq!(first.previous_proof_hash, None);
+
+        // Refreshed token; the guest has verified the receipt behind `journal`
+        // and decoded it to `first`
+        let journal = serde_json::to_vec(&first).unwrap();
+        let input = VerificationInput {
+            oauth_token: "Bearer dddd4444eeee5555ffff6666".to_string(),
+            timestamp: first_input.timestamp + 30 * day,
+            expires_at: first_input.timestamp + 30 * day + 3600,
+            nonce: 2,
+            previous_profile: profile,
+            previous_journal: Some(journal.clone()),
+            ..first_input.clone()
+        };
+        let (second, _) = verify_social_account_composed(&input, Some(&first));
+        assert!(second.verification_success);
+        assert_eq!(second.verification_type, VerificationType::ReVerification);
+        assert_eq!(second.social_account_hash, first.social_account_hash);
+        assert_eq!(second.previous_proof_hash, Some(Sha256::digest(&journal).into()));
+        assert_eq!(second.previous_profile_commitment, Some(first.profile_commitment));
+
+        // A journal that was not composed is rejected, and so is a composed
+        // one without it
+        assert!(!verify_social_account_composed(&input, None).0.verification_success);
+        let uncomposed = VerificationInput { previous_journal: None, ..input.clone() };
+        assert!(!verify_social_account_composed(&uncomposed, Some(&first)).0.verification_success);
+
+        // Each way the previous journal can fail to continue this account
+        let tampered_profile = VerificationInput {
+            previous_profile: input.previous_profile.clone().map(|profile| ProfileSnapshot {
+                follower_count: 10,
+                ..profile
+            }),
+            ..input.clone()
+        };
+        assert_eq!(
+            check_continuity(&tampered_profile, &first),
+            Err("Previous profile does not match its commitment")
+        );
+        let other_image = VerificationInput { self_image_id: [8; 8], ..input.clone() };
+        assert!(check_continuity(&other_image, &first).is_err());
+        let other_wallet = VerificationInput {
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            ..input.clone()
+        };
+        assert!(check_continuity(&other_wallet, &first).is_err());
+        let replayed = VerificationInput { timestamp: first.timestamp, ..input.clone() };
+        assert!(check_continuity(&replayed, &first).is_err());
+        let failed = VerificationOutput { verification_success: false, ..first.clone() };
+        assert!(check_continuity(&input, &failed).is_err());
+    }
+
+    #[test]
+    fn test_identity_bundle() {
+        let platform_input = |platform: SocialPlatform, oauth_token: &str| VerificationInput {
+            platform,
+            oauth_token: oauth_token.to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
+        };
+        let twitter = platform_input(SocialPlatform::Twitter, "Bearer aaaa1111bbbb2222cccc3333");
+        let github = platform_input(SocialPlatform::Github, "ghp_1234567890abcdef");
+        let discord = platform_input(SocialPlatform::Discord, "discord_token_1234567890");
+
+        // Any two of Twitter/GitHub/Discord
+        let bundle = IdentityBundleInput {
+            platforms: vec![twitter.clone(), github.clone(), discord.clone()],
+            minimum_platforms: 2,
+        };
+        let output = verify_identity_bundle(&bundle, &[]);
+        assert!(output.bundle_success);
+        assert_eq!(output.distinct_platforms, 3);
+        assert_eq!(output.reputation_tier, ReputationTier::Trusted);
+        assert_eq!(output.verifier_image_id, IMAGE_ID);
+        assert_eq!(
+            output.attestations[0].nullifier,
+            nullifier(SEPOLIA_CHAIN_ID, REGISTRY, &verify_social_account(&twitter).social_account_hash)
+        );
+        assert_ne!(
+            output.attestations[0].nullifier,
+            nullifier(1, REGISTRY, &verify_social_account(&twitter).social_account_hash)
+        );
+
+        // A failed platform and a repeated one do not count
+        let bundle = IdentityBundleInput {
+            platforms: vec![
+                twitter.clone(),
+                twitter.clone(),
+                platform_input(SocialPlatform::Github, "short"),
+            ],
+            minimum_platforms: 2,
+        };
+        let output = verify_identity_bundle(&bundle, &[]);
+        assert!(!output.bundle_success);
+        assert_eq!(output.distinct_platforms, 1);
+        assert_eq!(output.reputation_tier, ReputationTier::Basic);
+        assert!(!output.attestations[2].verification_success);
+        assert_eq!(output.attestations[2].nullifier, [0u8; 32]);
+
+        // Every platform must be for the same wallet
+        let other_wallet = VerificationInput {
+            wallet_address: "0x1234567890123456789012345678901234567890".to_string(),
+            ..github
+        };
+        let bundle = IdentityBundleInput {
+            platforms: vec![twitter, other_wallet],
+            minimum_platforms: 1,
+        };
+        assert!(!verify_identity_bundle(&bundle, &[]).bundle_success);
+        let empty = IdentityBundleInput { platforms: Vec::new(), minimum_platforms: 0 };
+        assert!(!verify_identity_bundle(&empty, &[]).bundle_success);
+    }
+
+    #[test]
+    fn test_batch_aggregation() {
+        let output_for = |wallet: &str, oauth_token: &str, platform: SocialPlatform| {
+            verify_social_account(&VerificationInput {
+                platform,
+                oauth_token: oauth_token.to_string(),
+                wallet_address: wallet.to_string(),
+                timestamp: 1640995200,
+                expires_at: 1640995200 + 3600,
+                nonce: 1,
+                expected_account_id: None,
+                previous_profile: None,
+                previous_journal: None,
+                self_image_id: IMAGE_ID,
+                chain_id: SEPOLIA_CHAIN_ID,
+                verifying_contract: REGISTRY.to_string(),
+                policy: PlatformRequirements::default(),
+                risk_model: RiskModel::default(),
+            })
+        };
+        let alice = output_for("0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8", "Bearer aaaa1111bbbb2222", SocialPlatform::Twitter);
+        let bob = output_for("0x1234567890123456789012345678901234567890", "ghp_1234567890abcdef", SocialPlatform::Github);
+        let carol = output_for("0x1111222233334444555566667777888899990000", "discord_token_1234567890", SocialPlatform::Discord);
+        let outputs = vec![alice.clone(), bob.clone(), carol.clone()];
+        let input = AggregationInput {
+            verifier_image_id: IMAGE_ID,
+            journals: outputs.iter().map(|output| serde_json::to_vec(output).unwrap()).collect(),
+        };
+
+        let aggregated = aggregate_verifications(&input, &outputs).unwrap();
+        assert_eq!(aggregated.links.len(), 3);
+        assert_eq!(aggregated.links[1].social_account_hash, bob.social_account_hash);
+        assert_eq!(aggregated.chain_id, SEPOLIA_CHAIN_ID);
+
+        // Every link can be proven against the root, including the odd one out
+        let leaves: Vec<[u8; 32]> = aggregated
+            .links
+            .iter()
+            .map(|link| link_leaf(&link.wallet_address, &link.social_account_hash).unwrap())
+            .collect();
+        assert_eq!(aggregated.merkle_root, merkle_root(&leaves));
+        for (index, leaf) in leaves.iter().enumerate() {
+            assert!(verify_merkle_proof(&aggregated.merkle_root, leaf, &merkle_proof(&leaves, index)));
+        }
+        assert!(!verify_merkle_proof(&aggregated.merkle_root, &[9u8; 32], &merkle_proof(&leaves, 0)));
+
+        // Batches the guest refuses to aggregate
+        let failed = output_for("0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8", "short", SocialPlatform::Twitter);
+        let rejected = [
+            vec![alice.clone(), failed],
+            vec![alice.clone(), alice.clone()],
+            vec![alice.clone(), VerificationOutput { chain_id: 1, ..bob.clone() }],
+            vec![alice.clone(), VerificationOutput { self_image_id: [8; 8], ..bob.clone() }],
+            vec![alice.clone(), VerificationOutput { policy_outcome: PolicyOutcome::AccountTooYoung, ..bob }],
+            Vec::new(),
+        ];
+        for outputs in rejected {
+            let input = AggregationInput {
+                verifier_image_id: IMAGE_ID,
+                journals: outputs.iter().map(|output| serde_json::to_vec(output).unwrap()).collect(),
+            };
+            assert!(aggregate_verifications(&input, &outputs).is_err());
+        }
+    }
+
+    #[test]
+    fn test_platform_policy_in_guest() {
+        // SocialAccountRegistry's GitHub config: 90 days, 5 followers
+        let github = PlatformRequirements {
+            minimum_account_age: 90 * 24 * 60 * 60,
+            minimum_followers: 5,
+            requires_additional_verification: true,
+        };
+        let day = 24 * 60 * 60;
+
+        assert_eq!(evaluate_policy(&github, 100 * day, 25, None), PolicyOutcome::Passed);
+        assert_eq!(evaluate_policy(&github, day, 25, None), PolicyOutcome::AccountTooYoung);
+        assert_eq!(evaluate_policy(&github, 100 * day, 4, None), PolicyOutcome::InsufficientFollowers);
+        assert_eq!(
+            evaluate_policy(&github, 100 * day, 25, Some(false)),
+            PolicyOutcome::AdditionalVerificationMissing
+        );
+
+        let input = VerificationInput {
+            platform: SocialPlatform::Github,
+            oauth_token: "ghp_1234567890abcdef".to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: github,
+            risk_model: RiskModel::default(),
+        };
+        let output = verify_social_account(&input);
+        assert!(output.verification_success);
+        assert_eq!(output.policy_outcome, PolicyOutcome::Passed);
+        assert_eq!(output.policy_hash, policy_hash(&SocialPlatform::Github, &github));
+
+        // The mock account has 25 followers
+        let strict = PlatformRequirements {
+            minimum_followers: 1000,
+            ..github
+        };
+        let output = verify_social_account(&VerificationInput { policy: strict, ..input.clone() });
+        assert!(output.verification_success);
+        assert_eq!(output.policy_outcome, PolicyOutcome::InsufficientFollowers);
+        assert_ne!(output.policy_hash, policy_hash(&SocialPlatform::Github, &github));
+
+        // Age is measured at the committed timestamp: the mock account was
+        // created 2019-06-01, so it is too young 60 days later, whenever proven
+        let created_at = 1559347200;
+        let early = VerificationInput {
+            timestamp: created_at + 60 * day,
+            expires_at: created_at + 60 * day + 3600,
+            ..input
+        };
+        let output = verify_social_account(&early);
+        assert_eq!(output.account_age, 60 * day);
+        assert_eq!(output.policy_outcome, PolicyOutcome::AccountTooYoung);
+        assert_eq!(verify_social_account(&early), output);
+        assert_eq!(calculate_account_age("2019-06-01T00:00:00Z", created_at - 1), 0);
+        assert_eq!(calculate_account_age("yesterday", created_at), 0);
+    }
+
+    #[test]
+    fn test_sybil_risk_score() {
+        println!("🧪 Testing: Sybil Risk Score");
+
+        const DAY: u64 = 24 * 60 * 60;
+        let model = RiskModel::default();
+
+        // New, follows many, followed by few, no activity
+        let fresh = ActivitySignals {
+            account_age: DAY,
+            followers: 2,
+            following: 500,
+            activity_count: 0,
+            verified: None,
+        };
+        assert_eq!(sybil_risk_score(&model, &fresh), 100);
+        let verified = ActivitySignals {
+            verified: Some(true),
+            ..fresh
+        };
+        assert_eq!(sybil_risk_score(&model, &verified), 100 - model.verified_bonus);
+
+        // Under a year old but otherwise established
+        let established = ActivitySignals {
+            account_age: 100 * DAY,
+            followers: 150,
+            following: 100,
+            activity_count: 500,
+            verified: Some(false),
+        };
+        assert_eq!(sybil_risk_score(&model, &established), model.young_account_weight / 2);
+        let mature = ActivitySignals {
+            account_age: 400 * DAY,
+            ..established
+        };
+        assert_eq!(sybil_risk_score(&model, &mature), 0);
+
+        // Weights that overflow still score within 0-100
+        let harsh = RiskModel {
+            version: 2,
+            young_account_weight: 255,
+            activity_weight: 255,
+            ..model
+        };
+        assert_eq!(sybil_risk_score(&harsh, &fresh), 100);
+        assert_ne!(harsh.hash(), model.hash());
+
+        // The guest commits the score with the model it used
+        let input = VerificationInput {
+            platform: SocialPlatform::Discord,
+            oauth_token: "discord_token_1234567890".to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: model,
+        };
+        let output = verify_social_account(&input);
+        assert!(output.verification_success);
+        // Discord reports no age, followers or activity, only that it is verified
+        assert_eq!(
+            output.sybil_risk_score,
+            model.young_account_weight + model.audience_weight + model.activity_weight - model.verified_bonus
+        );
+        assert_eq!(output.risk_model_version, 1);
+        assert_eq!(output.risk_model_hash, model.hash());
+
+        let output = verify_social_account(&VerificationInput {
+            platform: SocialPlatform::Github,
+            oauth_token: "ghp_1234567890abcdef".to_string(),
+            ..input.clone()
+        });
+        assert_eq!(output.sybil_risk_score, 0);
+
+        // Age is measured at the committed timestamp, so the score is exact.
+        // The mock Twitter account was created 2020-01-01 (1577836800)
+        let created_at = 1577836800;
+        let twitter = VerificationInput {
+            platform: SocialPlatform::Twitter,
+            oauth_token: "Bearer twitter_token_1234567890".to_string(),
+            timestamp: created_at + 100 * DAY,
+            expires_at: created_at + 100 * DAY + 3600,
+            ..input.clone()
+        };
+        let output = verify_social_account(&twitter);
+        assert_eq!(output.account_age, 100 * DAY);
+        assert_eq!(output.sybil_risk_score, 15);
+        assert_eq!(verify_social_account(&twitter), output);
+        let output = verify_social_account(&VerificationInput {
+            timestamp: created_at + 400 * DAY,
+            expires_at: created_at + 400 * DAY + 3600,
+            ..twitter.clone()
+        });
+        assert_eq!(output.sybil_risk_score, 0);
+        let output = verify_social_account(&VerificationInput {
+            timestamp: created_at + 10 * DAY,
+            expires_at: created_at + 10 * DAY + 3600,
+            ..twitter
+        });
+        assert_eq!(output.sybil_risk_score, 30);
+
+        let failed = verify_social_account(&VerificationInput {
+            oauth_token: "bad".to_string(),
+            ..input
+        });
+        assert!(!failed.verification_success);
+        assert_eq!(failed.sybil_risk_score, 100);
+
+        println!("✅ Sybil risk scored deterministically");
+    }
+
+    #[test]
+    fn test_policy_hash_matches_abi_encoding() {
+        use sha2::{Digest, Sha256};
+
+        // sha256(abi.encode(uint256(2), uint256(7776000), uint256(5), true))
+        let mut encoded = [0u8; 128];
+        encoded[31] = 2;
+        encoded[60..64].copy_from_slice(&7776000u32.to_be_bytes());
+        encoded[95] = 5;
+        encoded[127] = 1;
+        let expected: [u8; 32] = Sha256::digest(encoded).into();
+
+        let github = PlatformRequirements {
+            minimum_account_age: 7776000,
+            minimum_followers: 5,
+            requires_additional_verification: true,
+        };
+        assert_eq!(policy_hash(&SocialPlatform::Github, &github), expected);
+    }
+
+    #[test]
+    fn test_oauth_token_commitment() {
+        // sha256("abc"), as Solidity's sha256(bytes("abc"))
+        assert_eq!(
+            hex::encode(oauth_token_hash("abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let input = VerificationInput {
+            platform: SocialPlatform::Github,
+            oauth_token: "ghp_1234567890abcdef".to_string(),
+            wallet_address: "0x742d35Cc6634C0532925a3b8D4C2C4e0C8A8e8e8".to_string(),
+            timestamp: 1640995200,
+            expires_at: 1640995200 + 3600,
+            nonce: 1,
+            expected_account_id: None,
+            previous_profile: None,
+            previous_journal: None,
+            self_image_id: IMAGE_ID,
+            chain_id: SEPOLIA_CHAIN_ID,
+            verifying_contract: REGISTRY.to_string(),
+            policy: PlatformRequirements::default(),
+            risk_model: RiskModel::default(),
+        };
+        let output = verify_social_account(&input);
+        assert!(output.verification_success);
+        assert_eq!(output.oauth_token_hash, oauth_token_hash("ghp_1234567890abcdef"));
+
+        // Failed verifications commit the token too, so a request can be
+        // matched to the proof that rejected it
+        let failed = verify_social_account(&VerificationInput {
+            oauth_token: "invalid".to_string(),
+            ..input
+        });
+        assert!(!failed.verification_success);
+        assert_eq!(failed.oauth_token_hash, oauth_token_hash("invalid"));
+    }
+
     // Helper functions for testing
 
     fn simulate_verification(input: &VerificationInput, user_data: &MockTwitterUser) -> VerificationOutput {
@@ -347,25 +967,56 @@ mod token_management_tests {
         }
 
         let verification_type = determine_verification_type(input, &user_data.id);
-        let consistency_score = calculate_consistency_score(&verification_type, &user_data.id);
+        let profile = snapshot(user_data, input.timestamp);
+        let consistency_score = calculate_consistency_score(input, &profile);
         let social_account_hash = generate_social_account_hash(&input.platform, &user_data.id);
-        let account_age = calculate_account_age(&user_data.created_at);
+        let account_age = calculate_account_age(&user_data.created_at, input.timestamp);
 
         VerificationOutput {
             social_account_hash,
             wallet_address: input.wallet_address.clone(),
-            platform: input.platform.clone(),
+            platform: input.platform,
             account_age,
             follower_count: user_data.followers_count,
             timestamp: input.timestamp,
+            expires_at: input.expires_at,
             nonce: input.nonce,
             social_account_id: user_data.id.clone(),
             verification_type,
             account_consistency_score: consistency_score,
+            chain_id: input.chain_id,
+            verifying_contract: input.verifying_contract.clone(),
             verification_success: true,
+            policy_hash: policy_hash(&input.platform, &input.policy),
+            policy_outcome: evaluate_policy(&input.policy, account_age, user_data.followers_count, None),
+            profile_commitment: profile.commitment(),
+            previous_profile_commitment: input.previous_profile.as_ref().map(ProfileSnapshot::commitment),
+            self_image_id: input.self_image_id,
+            previous_proof_hash: None,
+            sybil_risk_score: sybil_risk_score(
+                &input.risk_model,
+                &ActivitySignals {
+                    account_age,
+                    followers: user_data.followers_count,
+                    ..ActivitySignals::default()
+                },
+            ),
+            risk_model_version: input.risk_model.version,
+            risk_model_hash: input.risk_model.hash(),
+            oauth_token_hash: oauth_token_hash(&input.oauth_token),
         }
     }
 
+    fn snapshot(user_data: &MockTwitterUser, verified_at: u64) -> ProfileSnapshot {
+        ProfileSnapshot::new(
+            &user_data.id,
+            &user_data.created_at,
+            user_data.followers_count,
+            &user_data.username,
+            verified_at,
+        )
+    }
+
     struct MockGithubUser {
         id: u64,
         login: String,
@@ -380,22 +1031,49 @@ mod token_management_tests {
 
         let account_id = user_data.id.to_string();
         let verification_type = determine_verification_type(input, &account_id);
-        let consistency_score = calculate_consistency_score(&verification_type, &account_id);
+        let profile = ProfileSnapshot::new(
+            &account_id,
+            &user_data.created_at,
+            user_data.followers,
+            &user_data.login,
+            input.timestamp,
+        );
+        let consistency_score = calculate_consistency_score(input, &profile);
         let social_account_hash = generate_social_account_hash(&input.platform, &account_id);
-        let account_age = calculate_account_age(&user_data.created_at);
+        let account_age = calculate_account_age(&user_data.created_at, input.timestamp);
 
         VerificationOutput {
             social_account_hash,
             wallet_address: input.wallet_address.clone(),
-            platform: input.platform.clone(),
+            platform: input.platform,
             account_age,
             follower_count: user_data.followers,
             timestamp: input.timestamp,
+            expires_at: input.expires_at,
             nonce: input.nonce,
             social_account_id: account_id,
             verification_type,
             account_consistency_score: consistency_score,
+            chain_id: input.chain_id,
+            verifying_contract: input.verifying_contract.clone(),
             verification_success: true,
+            policy_hash: policy_hash(&input.platform, &input.policy),
+            policy_outcome: evaluate_policy(&input.policy, account_age, user_data.followers, None),
+            profile_commitment: profile.commitment(),
+            previous_profile_commitment: input.previous_profile.as_ref().map(ProfileSnapshot::commitment),
+            self_image_id: input.self_image_id,
+            previous_proof_hash: None,
+            sybil_risk_score: sybil_risk_score(
+                &input.risk_model,
+                &ActivitySignals {
+                    account_age,
+                    followers: user_data.followers,
+                    ..ActivitySignals::default()
+                },
+            ),
+            risk_model_version: input.risk_model.version,
+            risk_model_hash: input.risk_model.hash(),
+            oauth_token_hash: oauth_token_hash(&input.oauth_token),
         }
     }
 }
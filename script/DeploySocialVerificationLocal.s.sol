@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Script.sol";
+import "../src/eip7702/SocialAccountRegistry.sol";
+import "../src/eip7702/RiscZeroSocialVerifier.sol";
+
+/**
+ * @title DeploySocialVerificationLocal
+ * @dev Deploys the registry and verifier to a local dev chain for the host relayer.
+ * The registry is the deployer's first contract and the verifier its second, so
+ * their addresses follow from the deployer's nonce.
+ */
+contract DeploySocialVerificationLocal is Script {
+    function run() external {
+        uint256 deployerPrivateKey = vm.envUint("PRIVATE_KEY");
+        address verifierKey = vm.envAddress("VERIFIER_KEY"); // Signs proof data
+        address relayer = vm.envAddress("RELAYER"); // Sends submitProof
+
+        vm.startBroadcast(deployerPrivateKey);
+
+        SocialAccountRegistry registry = new SocialAccountRegistry(verifierKey);
+        RiscZeroSocialVerifier verifier = new RiscZeroSocialVerifier(verifierKey, address(registry));
+        verifier.setAuthorizedVerifier(relayer, true);
+
+        vm.stopBroadcast();
+
+        console.log("SocialAccountRegistry:", address(registry));
+        console.log("RiscZeroSocialVerifier:", address(verifier));
+    }
+}
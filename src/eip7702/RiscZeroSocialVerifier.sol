@@ -16,6 +16,14 @@ contract RiscZeroSocialVerifier is Ownable, ReentrancyGuard {
     using MessageHashUtils for bytes32;
 
     // Events
+    event VerificationRequested(
+        bytes32 indexed requestId,
+        address indexed requester,
+        address indexed walletAddress,
+        SocialPlatform platform,
+        bytes32 oauthTokenHash,
+        uint256 requestedAt
+    );
     event ProofVerified(bytes32 indexed proofHash, address indexed requester, SocialPlatform platform, bool success);
     event VerifierKeyUpdated(address indexed oldKey, address indexed newKey);
     event PlatformEndpointUpdated(SocialPlatform platform, string endpoint);
@@ -86,17 +94,19 @@ contract RiscZeroSocialVerifier is Ownable, ReentrancyGuard {
     }
 
     /**
-     * @dev Request social account verification
+     * @dev Request social account verification without revealing the OAuth token.
+     * There is no variant taking the token itself, which would put it in calldata.
      * @param platform The social platform to verify
-     * @param oauthToken The OAuth token for the social account
+     * @param oauthTokenHash sha256 of the OAuth token, delivered to the prover off-chain
      * @param walletAddress The wallet address to link to
      * @return requestId The unique request identifier
      */
-    function requestVerification(SocialPlatform platform, string calldata oauthToken, address walletAddress)
+    function requestVerificationWithCommitment(SocialPlatform platform, bytes32 oauthTokenHash, address walletAddress)
         external
         nonReentrant
         returns (bytes32 requestId)
     {
+        require(oauthTokenHash != bytes32(0), "Invalid token commitment");
         require(bytes(platformEndpoints[platform]).length > 0, "Platform not supported");
         require(walletAddress != address(0), "Invalid wallet address");
 
@@ -114,7 +124,7 @@ contract RiscZeroSocialVerifier is Ownable, ReentrancyGuard {
                 followerCount: 0, // Will be filled by RISC Zero
                 timestamp: block.timestamp,
                 socialAccountId: "", // Will be filled by RISC Zero
-                oauthTokenHash: keccak256(abi.encodePacked(oauthToken))
+                oauthTokenHash: oauthTokenHash
             }),
             proofHash: bytes32(0), // Will be filled when proof is submitted
             requestedAt: block.timestamp,
@@ -122,12 +132,10 @@ contract RiscZeroSocialVerifier is Ownable, ReentrancyGuard {
             isCompleted: false
         });
 
-        // Note: In a real implementation, this would trigger the RISC Zero guest program
-        // The guest program would:
-        // 1. Use the OAuth token to fetch user data from the social platform
-        // 2. Verify the token validity and extract account information
-        // 3. Generate a ZK proof of the verification
-        // 4. Submit the proof back to this contract via submitProof()
+        emit VerificationRequested(requestId, msg.sender, walletAddress, platform, oauthTokenHash, block.timestamp);
+
+        // The prover receives the token off-chain, checks it against oauthTokenHash and
+        // submits a proof whose journal commits sha256(token) via submitProof()
 
         return requestId;
     }
@@ -147,6 +155,9 @@ contract RiscZeroSocialVerifier is Ownable, ReentrancyGuard {
     ) external onlyAuthorizedVerifier onlyValidRequest(requestId) {
         VerificationRequest storage request = verificationRequests[requestId];
 
+        // The proof must be for the token the requester committed to
+        require(proofData.oauthTokenHash == request.data.oauthTokenHash, "Token commitment mismatch");
+
         // Verify the proof signature
         bytes32 messageHash = keccak256(
             abi.encodePacked(
@@ -203,6 +203,23 @@ contract SocialAccountRegistry is Ownable, ReentrancyGuard {
         return walletToSocialAccounts[walletAddress];
     }
 
+    /**
+     * @dev Hash of the platform requirements the RISC Zero guest enforces
+     * @param platform The social platform
+     * @return policyHash Must equal the policy hash committed in the guest journal
+     */
+    function platformPolicyHash(SocialPlatform platform) external view returns (bytes32 policyHash) {
+        PlatformConfig memory config = platformConfigs[platform];
+        return sha256(
+            abi.encode(
+                uint256(platform),
+                config.minimumAccountAge,
+                config.minimumFollowers,
+                config.requiresAdditionalVerification
+            )
+        );
+    }
+
     /**
      * @dev Check if a social account hash is already linked
      * @param socialAccountHash The social account hash to check
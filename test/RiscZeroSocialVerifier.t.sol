@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+import "forge-std/Test.sol";
+import "@openzeppelin/contracts/utils/cryptography/MessageHashUtils.sol";
+import "../src/eip7702/RiscZeroSocialVerifier.sol";
+import "../src/eip7702/SocialAccountRegistry.sol";
+
+/**
+ * @title RiscZeroSocialVerifierTest
+ * @dev Token commitments from request to proof submission, and the policy hash
+ * the registry publishes for the guest
+ */
+contract RiscZeroSocialVerifierTest is Test {
+    RiscZeroSocialVerifier public verifier;
+    SocialAccountRegistry public registry;
+
+    uint256 public constant VERIFIER_KEY = 0xA11CE;
+    string public constant OAUTH_TOKEN = "mock_twitter_token_12345";
+
+    address public requester = makeAddr("requester");
+    address public wallet = makeAddr("wallet");
+    address public relayer = makeAddr("relayer");
+
+    bytes32 public tokenHash;
+
+    event VerificationRequested(
+        bytes32 indexed requestId,
+        address indexed requester,
+        address indexed walletAddress,
+        RiscZeroSocialVerifier.SocialPlatform platform,
+        bytes32 oauthTokenHash,
+        uint256 requestedAt
+    );
+
+    function setUp() public {
+        verifier = new RiscZeroSocialVerifier(vm.addr(VERIFIER_KEY), address(0));
+        registry = new SocialAccountRegistry(address(verifier));
+        verifier.setSocialRegistry(address(registry));
+        verifier.setAuthorizedVerifier(relayer, true);
+
+        tokenHash = sha256(bytes(OAUTH_TOKEN));
+    }
+
+    function test_Request_StoresTokenCommitment() public {
+        bytes32 expectedId = keccak256(
+            abi.encodePacked(requester, RiscZeroSocialVerifier.SocialPlatform.TWITTER, wallet, block.timestamp, block.number)
+        );
+
+        vm.expectEmit(true, true, true, true);
+        emit VerificationRequested(
+            expectedId, requester, wallet, RiscZeroSocialVerifier.SocialPlatform.TWITTER, tokenHash, block.timestamp
+        );
+
+        bytes32 requestId = _request();
+        assertEq(requestId, expectedId);
+
+        (bool isCompleted, bool isVerified, RiscZeroSocialVerifier.ProofData memory data) =
+            verifier.getVerificationResult(requestId);
+        assertFalse(isCompleted);
+        assertFalse(isVerified);
+        assertEq(data.oauthTokenHash, tokenHash);
+        assertEq(data.walletAddress, wallet);
+    }
+
+    function test_Request_RejectsMissingCommitment() public {
+        vm.prank(requester);
+        vm.expectRevert("Invalid token commitment");
+        verifier.requestVerificationWithCommitment(RiscZeroSocialVerifier.SocialPlatform.TWITTER, bytes32(0), wallet);
+
+        vm.prank(requester);
+        vm.expectRevert("Invalid wallet address");
+        verifier.requestVerificationWithCommitment(RiscZeroSocialVerifier.SocialPlatform.TWITTER, tokenHash, address(0));
+    }
+
+    function test_Request_HasNoPlaintextTokenEntryPoint() public {
+        vm.prank(requester);
+        (bool success,) = address(verifier).call(
+            abi.encodeWithSignature("requestVerification(uint8,string,address)", uint8(0), OAUTH_TOKEN, wallet)
+        );
+        assertFalse(success);
+    }
+
+    function test_SubmitProof_AcceptsMatchingCommitment() public {
+        bytes32 requestId = _request();
+        RiscZeroSocialVerifier.ProofData memory data = _proofData(tokenHash);
+
+        vm.prank(relayer);
+        verifier.submitProof(requestId, data, hex"01", _sign(requestId, data));
+
+        (bool isCompleted, bool isVerified, RiscZeroSocialVerifier.ProofData memory stored) =
+            verifier.getVerificationResult(requestId);
+        assertTrue(isCompleted);
+        assertTrue(isVerified);
+        assertEq(stored.socialAccountHash, data.socialAccountHash);
+        assertEq(stored.oauthTokenHash, tokenHash);
+    }
+
+    function test_SubmitProof_RejectsTokenCommitmentMismatch() public {
+        bytes32 requestId = _request();
+        // A valid proof, but for another token than the requester committed to
+        RiscZeroSocialVerifier.ProofData memory data = _proofData(sha256(bytes("another_token_67890")));
+        bytes memory signature = _sign(requestId, data);
+
+        vm.prank(relayer);
+        vm.expectRevert("Token commitment mismatch");
+        verifier.submitProof(requestId, data, hex"01", signature);
+
+        (bool isCompleted,,) = verifier.getVerificationResult(requestId);
+        assertFalse(isCompleted);
+    }
+
+    function test_SubmitProof_RequiresVerifierSignature() public {
+        bytes32 requestId = _request();
+        RiscZeroSocialVerifier.ProofData memory data = _proofData(tokenHash);
+        bytes32 digest = MessageHashUtils.toEthSignedMessageHash(_messageHash(requestId, data));
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(0xB0B, digest);
+
+        vm.prank(relayer);
+        vm.expectRevert("Invalid proof signature");
+        verifier.submitProof(requestId, data, hex"01", abi.encodePacked(r, s, v));
+
+        vm.prank(requester);
+        vm.expectRevert("Not authorized verifier");
+        verifier.submitProof(requestId, data, hex"01", _sign(requestId, data));
+    }
+
+    function test_Registry_PlatformPolicyHashMatchesGuest() public {
+        // sha256(abi.encode(uint256(platform), minimumAccountAge, minimumFollowers,
+        // requiresAdditionalVerification)), as `policy_hash` computes it in the guest
+        assertEq(
+            registry.platformPolicyHash(SocialAccountRegistry.SocialPlatform.GITHUB),
+            sha256(abi.encode(uint256(2), uint256(90 days), uint256(5), true))
+        );
+
+        registry.setPlatformConfig(
+            SocialAccountRegistry.SocialPlatform.GITHUB,
+            SocialAccountRegistry.PlatformConfig({
+                isEnabled: true,
+                minimumAccountAge: 90 days,
+                minimumFollowers: 50,
+                verificationCooldown: 14 days,
+                requiresAdditionalVerification: true
+            })
+        );
+        assertEq(
+            registry.platformPolicyHash(SocialAccountRegistry.SocialPlatform.GITHUB),
+            sha256(abi.encode(uint256(2), uint256(90 days), uint256(50), true))
+        );
+    }
+
+    function _request() internal returns (bytes32 requestId) {
+        vm.prank(requester);
+        return verifier.requestVerificationWithCommitment(RiscZeroSocialVerifier.SocialPlatform.TWITTER, tokenHash, wallet);
+    }
+
+    function _proofData(bytes32 oauthTokenHash) internal view returns (RiscZeroSocialVerifier.ProofData memory) {
+        return RiscZeroSocialVerifier.ProofData({
+            socialAccountHash: keccak256("twitter:123456789"),
+            walletAddress: wallet,
+            platform: RiscZeroSocialVerifier.SocialPlatform.TWITTER,
+            accountAge: 365 days,
+            followerCount: 150,
+            timestamp: block.timestamp,
+            socialAccountId: "123456789",
+            oauthTokenHash: oauthTokenHash
+        });
+    }
+
+    function _messageHash(bytes32 requestId, RiscZeroSocialVerifier.ProofData memory data)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return keccak256(
+            abi.encodePacked(
+                requestId,
+                data.socialAccountHash,
+                data.walletAddress,
+                uint256(data.platform),
+                data.accountAge,
+                data.followerCount,
+                data.timestamp
+            )
+        );
+    }
+
+    function _sign(bytes32 requestId, RiscZeroSocialVerifier.ProofData memory data)
+        internal
+        pure
+        returns (bytes memory)
+    {
+        bytes32 digest = MessageHashUtils.toEthSignedMessageHash(_messageHash(requestId, data));
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(VERIFIER_KEY, digest);
+        return abi.encodePacked(r, s, v);
+    }
+}